@@ -0,0 +1,15 @@
+// build.rs
+
+// The build script cannot depend on the crate it builds, so it compiles the embedding helper directly
+#[allow(dead_code, unused_macros)]
+#[path = "src/io/io_embed.rs"]
+mod io_embed;
+
+fn main() {
+    // Only the test binary serves the statics, so library users do not embed them unless asked to
+    println!("cargo:rerun-if-env-changed=CARGO_FEATURE_EMBED_STATICS");
+    if std::env::var_os("CARGO_FEATURE_EMBED_STATICS").is_some() {
+        // Ship the statics inside the binary instead of next to it
+        io_embed::embed_dir("statics", "statics.rs").expect("Failed to embed statics");
+    }
+}
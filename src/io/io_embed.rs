@@ -0,0 +1,163 @@
+use std::env;
+use std::fs;
+use std::io::{Error, ErrorKind, Result};
+use std::path::{Path, PathBuf};
+
+/// A set of files compiled into the binary, keyed by their `/`-separated path relative to
+/// the embedded directory.
+///
+/// Bundles are generated at build time by `embed_dir` and loaded with `embed_assets!`.
+pub struct AssetBundle {
+    files: &'static [(&'static str, &'static [u8])],
+}
+
+impl AssetBundle {
+    /// Creates a bundle from a list of `(path, contents)` pairs.
+    pub const fn new(files: &'static [(&'static str, &'static [u8])]) -> Self {
+        Self { files }
+    }
+
+    /// Returns the contents of the file at `path`, if it is in the bundle.
+    pub fn get(&self, path: &str) -> Option<&'static [u8]> {
+        self.files
+            .iter()
+            .find(|(file_path, _)| *file_path == path)
+            .map(|(_, contents)| *contents)
+    }
+
+    /// Returns `true` if any file in the bundle lives under the directory `path`.
+    pub fn is_dir(&self, path: &str) -> bool {
+        if path.is_empty() {
+            return !self.files.is_empty();
+        }
+        self.files
+            .iter()
+            .any(|(file_path, _)| file_path.strip_prefix(path).is_some_and(|rest| rest.starts_with('/')))
+    }
+
+    /// Returns the paths of every file in the bundle.
+    pub fn paths(&self) -> impl Iterator<Item = &'static str> {
+        self.files.iter().map(|(file_path, _)| *file_path)
+    }
+}
+
+/// Build-script helper that embeds every file under `dir` into the binary.
+///
+/// Call it from `build.rs`; relative directories are resolved against the crate's manifest
+/// directory. It writes `out_file` into `OUT_DIR`, to be loaded with `embed_assets!`.
+///
+/// ```ignore
+/// // build.rs
+/// fn main() {
+///     smn_view::io::io_embed::embed_dir("statics", "statics.rs").unwrap();
+/// }
+///
+/// // main.rs
+/// static STATICS: AssetBundle = smn_view::embed_assets!("statics.rs");
+/// ```
+pub fn embed_dir(dir: impl AsRef<Path>, out_file: &str) -> Result<()> {
+    let dir = dir.as_ref();
+    let dir = if dir.is_absolute() {
+        dir.to_path_buf()
+    } else {
+        let manifest_dir = env::var("CARGO_MANIFEST_DIR")
+            .map_err(|_| Error::new(ErrorKind::NotFound, "CARGO_MANIFEST_DIR is not set; call embed_dir from build.rs"))?;
+        PathBuf::from(manifest_dir).join(dir)
+    };
+    let out_dir = env::var("OUT_DIR")
+        .map_err(|_| Error::new(ErrorKind::NotFound, "OUT_DIR is not set; call embed_dir from build.rs"))?;
+
+    let mut files = Vec::new();
+    collect_files(&dir, "", &mut Vec::new(), &mut files)?;
+    files.sort();
+
+    let mut code = String::from("&[\n");
+    for (relative, absolute) in &files {
+        code.push_str(&format!(
+            "    ({:?}, include_bytes!({:?}) as &[u8]),\n",
+            relative,
+            absolute.to_string_lossy()
+        ));
+    }
+    code.push(']');
+
+    // Rebuild when the directory or any embedded file changes
+    println!("cargo:rerun-if-changed={}", dir.display());
+    for (_, absolute) in &files {
+        println!("cargo:rerun-if-changed={}", absolute.display());
+    }
+
+    fs::write(Path::new(&out_dir).join(out_file), code)
+}
+
+/// Collects the files under `dir`, following symlinks.
+///
+/// `ancestors` holds the canonical paths of the directories being walked, so a link back
+/// to one of them is reported instead of being followed forever.
+fn collect_files(dir: &Path, prefix: &str, ancestors: &mut Vec<PathBuf>, files: &mut Vec<(String, PathBuf)>) -> Result<()> {
+    let canonical = fs::canonicalize(dir)?;
+    if ancestors.contains(&canonical) {
+        return Err(Error::new(
+            ErrorKind::InvalidInput,
+            format!("Symlink cycle at {} while embedding assets", dir.display()),
+        ));
+    }
+    ancestors.push(canonical);
+
+    for entry in fs::read_dir(dir)? {
+        let entry = entry?;
+        let name = entry.file_name().to_string_lossy().into_owned();
+        let relative = if prefix.is_empty() { name } else { format!("{}/{}", prefix, name) };
+        let path = entry.path();
+
+        if path.is_dir() {
+            collect_files(&path, &relative, ancestors, files)?;
+        } else {
+            files.push((relative, fs::canonicalize(&path)?));
+        }
+    }
+
+    ancestors.pop();
+    Ok(())
+}
+
+/// Loads an `AssetBundle` generated by `embed_dir` into the binary.
+#[macro_export]
+macro_rules! embed_assets {
+    ($file:expr) => {
+        $crate::io::io_embed::AssetBundle::new(include!(concat!(env!("OUT_DIR"), "/", $file)))
+    };
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn bundle() -> AssetBundle {
+        AssetBundle::new(&[("index.html", b"<html>"), ("css/site.css", b"body {}")])
+    }
+
+    #[test]
+    fn looks_up_files_and_directories() {
+        let bundle = bundle();
+        assert_eq!(bundle.get("css/site.css"), Some(&b"body {}"[..]));
+        assert_eq!(bundle.get("css"), None);
+        assert!(bundle.is_dir("css"));
+        assert!(bundle.is_dir(""));
+        assert!(!bundle.is_dir("cs"));
+        assert!(!bundle.is_dir("index.html"));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn reports_symlink_cycles() {
+        let dir = env::temp_dir().join(format!("smn_view-embed-{}", std::process::id()));
+        fs::create_dir_all(dir.join("nested")).unwrap();
+        fs::write(dir.join("nested/file.txt"), b"x").unwrap();
+        std::os::unix::fs::symlink(&dir, dir.join("nested/loop")).unwrap();
+
+        let result = collect_files(&dir, "", &mut Vec::new(), &mut Vec::new());
+        fs::remove_dir_all(&dir).unwrap();
+        assert_eq!(result.unwrap_err().kind(), ErrorKind::InvalidInput);
+    }
+}
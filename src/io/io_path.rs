@@ -6,6 +6,6 @@ pub fn path_get_root() -> PathBuf {
     PathBuf::from(env_root)
 }
 
-pub fn get_extension(path: &PathBuf) -> Option<&str> {
+pub fn get_extension(path: &Path) -> Option<&str> {
     path.extension().and_then(|ext| ext.to_str())
 }
\ No newline at end of file
@@ -1,4 +1,6 @@
 #[allow(unused)]
 pub mod io_path;
 #[allow(unused)]
-pub mod io_file;
\ No newline at end of file
+pub mod io_file;
+#[allow(unused)]
+pub mod io_embed;
\ No newline at end of file
@@ -1,4 +1,5 @@
 pub mod server;
 pub mod util;
 pub mod window;
-pub mod io;
\ No newline at end of file
+pub mod io;
+pub mod plugins;
\ No newline at end of file
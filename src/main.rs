@@ -6,41 +6,64 @@ mod util;
 mod window;
 mod io;
 
+#[cfg(feature = "embed-statics")]
+use io::io_embed::AssetBundle;
 use plugins::{plugin_statics::PluginStatics, plugin_ui::PluginUI};
-use server::{plugin::plugin_manager::PluginManager, server_core::start_server};
-use std::net::SocketAddr;
+use server::{plugin::plugin_manager::PluginManager, server_core::start_server, server_protocol::start_protocol_server};
+use std::{net::SocketAddr, time::Duration};
 use util::logging::{log_line, log_line_header, logln, Color};
-use window::{structs::struct_windowconfig::WindowConfig, window_core::start_window};
+use window::{
+    structs::{struct_windowconfig::WindowConfig, struct_windowerror::WindowError},
+    window_core::start_window,
+};
 
-#[tokio::main]
-async fn main() -> wry::Result<()> {
-    let addr: SocketAddr = ([127, 0, 0, 1], 3030).into();
-    let url = format!("http://{}/", addr);
+/// The `statics` directory, embedded by `build.rs` when built with the `embed-statics` feature.
+#[cfg(feature = "embed-statics")]
+static STATICS: AssetBundle = embed_assets!("statics.rs");
 
+#[tokio::main]
+async fn main() -> Result<(), WindowError> {
     // Create the PluginManager and apply plugins
     logln("");
     log_line_header("Plugin Manager", Color::Cyan, 30);
     let mut plugin_manager = PluginManager::new();
+    #[cfg(feature = "embed-statics")]
+    let (plugin_ui, plugin_statics) = (PluginUI::embedded(&STATICS), PluginStatics::embedded(&STATICS));
+    #[cfg(not(feature = "embed-statics"))]
+    let (plugin_ui, plugin_statics) = (PluginUI::new(), PluginStatics::new("statics".to_string()));
+
     plugin_manager
-        .apply_plugin(Box::new(PluginUI::new()))
+        .apply_plugin(Box::new(plugin_ui))
         .expect("Failed to apply plugin");
 
     plugin_manager
-        .apply_plugin(Box::new(PluginStatics::new("statics".to_string())))
+        .apply_plugin(Box::new(plugin_statics.set_url_prefix("/statics")))
         .expect("Failed to apply plugin");
 
     log_line(Color::Cyan, 30);
     logln("");
 
-    // Start the server with the given address and PluginManager
+    // Serve the plugins over TCP, or through the webview's smn:// protocol when run with --protocol.
+    // The protocol blocks the UI thread while each request is answered and cannot hold a response
+    // open, so event streams and WebSockets, such as those of PluginEvents, get 501 Not Implemented there.
     log_line_header("Server", Color::Cyan, 30);
-    let mut server = start_server(addr, plugin_manager);
-    server.await_ready();
-
-    // Start the UI with the given URL
-    // Once the window closes, trigger server shutdown
-    let window_config = WindowConfig::default().set_title("SmnView").set_url(&url);
+    let use_protocol = std::env::args().any(|arg| arg == "--protocol");
+    let window_config = WindowConfig::default().set_title("SmnView");
+    let (mut server, window_config) = if use_protocol {
+        let (server, handler) = start_protocol_server(plugin_manager);
+        (server, window_config.set_protocol(handler))
+    } else {
+        // Port 0 lets the system pick a free port, which the window then points at
+        let addr: SocketAddr = ([127, 0, 0, 1], 0).into();
+        let server = start_server(addr, plugin_manager).expect("Failed to start server");
+        let url = server.url().expect("TCP server has a local address");
+        (server, window_config.set_url(&url))
+    };
+    server
+        .await_ready_timeout(Duration::from_secs(5))
+        .expect("Server failed to start");
 
+    // Start the UI, and once the window closes, trigger server shutdown
     start_window(window_config, || {
         server.shutdown();
     })?;
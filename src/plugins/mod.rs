@@ -1,2 +1,4 @@
 pub mod plugin_ui;
-pub mod plugin_statics;
\ No newline at end of file
+pub mod plugin_statics;
+pub mod plugin_events;
+pub mod plugin_commands;
\ No newline at end of file
@@ -0,0 +1,285 @@
+mod plugin_commands_error;
+
+#[allow(unused)]
+pub use plugin_commands_error::CommandError;
+
+use std::collections::HashMap;
+use std::future::Future;
+use std::io::{Error, ErrorKind, Result};
+use std::pin::Pin;
+use std::sync::Arc;
+
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+
+use crate::server::plugin::plugin_base::{AsyncPlugin, PluginFuture};
+use crate::server::plugin::plugin_router::Route;
+use crate::server::structs::structs_header::StatusCode;
+use crate::server::structs::structs_mime::Mime;
+use crate::server::structs::structs_request::Request;
+use crate::server::structs::structs_response::Response;
+use crate::util::logging::{logln, Color};
+
+/// The JavaScript client, defining `smn.invoke`.
+const CLIENT_JS: &str = include_str!("plugin_commands_client.js");
+
+type CommandFuture = Pin<Box<dyn Future<Output = std::result::Result<Value, CommandError>> + Send>>;
+type CommandHandler = Arc<dyn Fn(Value) -> CommandFuture + Send + Sync>;
+
+/// Body of a call: `{"command": "name", "args": ...}`.
+#[derive(Deserialize)]
+struct Invocation {
+    command: String,
+    #[serde(default)]
+    args: Value,
+}
+
+/// Registry of named Rust functions that pages call with `smn.invoke(name, args)`.
+///
+/// Commands take and return serde types. Every call goes through one endpoint,
+/// `POST {prefix}` (`/smn/invoke` by default), and is answered with
+/// `{"ok": true, "result": ..}` or a structured `CommandError`. The JavaScript client is
+/// served at `{prefix}/client.js`.
+///
+/// ```ignore
+/// #[derive(Deserialize)]
+/// struct RemapArgs { path: String, channels: String }
+///
+/// let mut commands = PluginCommands::new();
+/// commands.register("remap_texture", |args: RemapArgs| {
+///     remap(&args.path, &args.channels).map_err(|e| CommandError::new("remap_failed", &e.to_string()))
+/// })?;
+/// plugin_manager.apply_async_plugin(Box::new(commands))?;
+/// ```
+pub struct PluginCommands {
+    pub url_prefix: String,
+    commands: HashMap<String, CommandHandler>,
+}
+
+impl PluginCommands {
+    /// Creates a new instance of `PluginCommands` with no commands.
+    pub fn new() -> Self {
+        Self {
+            url_prefix: "/smn/invoke".to_string(),
+            commands: HashMap::new(),
+        }
+    }
+
+    /// Sets the URL prefix of the endpoint and client script.
+    pub fn set_url_prefix(mut self, url_prefix: &str) -> Self {
+        self.url_prefix = format!("/{}", url_prefix.trim_matches('/'));
+        self
+    }
+
+    /// Registers a command that may block; it runs on tokio's blocking thread pool.
+    ///
+    /// Fails if a command with the same name is already registered.
+    pub fn register<A, R, F>(&mut self, name: &str, command: F) -> Result<()>
+    where
+        A: DeserializeOwned + Send + 'static,
+        R: Serialize + 'static,
+        F: Fn(A) -> std::result::Result<R, CommandError> + Send + Sync + 'static,
+    {
+        let command = Arc::new(command);
+        self.insert(name, move |args: A| {
+            let command = Arc::clone(&command);
+            async move {
+                tokio::task::spawn_blocking(move || command(args).and_then(|result| to_value(&result)))
+                    .await
+                    .map_err(|e| CommandError::internal(&format!("Command panicked: {}", e)))?
+            }
+        })
+    }
+
+    /// Registers an async command, run directly on the server's runtime.
+    ///
+    /// Fails if a command with the same name is already registered.
+    pub fn register_async<A, R, F, Fut>(&mut self, name: &str, command: F) -> Result<()>
+    where
+        A: DeserializeOwned + Send + 'static,
+        R: Serialize + 'static,
+        F: Fn(A) -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = std::result::Result<R, CommandError>> + Send + 'static,
+    {
+        self.insert(name, move |args: A| {
+            let future = command(args);
+            async move { future.await.and_then(|result| to_value(&result)) }
+        })
+    }
+
+    fn insert<A, F, Fut>(&mut self, name: &str, call: F) -> Result<()>
+    where
+        A: DeserializeOwned + Send + 'static,
+        F: Fn(A) -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = std::result::Result<Value, CommandError>> + Send + 'static,
+    {
+        if self.commands.contains_key(name) {
+            return Err(Error::new(
+                ErrorKind::AlreadyExists,
+                format!("Command {} is already registered", name),
+            ));
+        }
+
+        let handler: CommandHandler = Arc::new(move |args: Value| -> CommandFuture {
+            match serde_json::from_value::<A>(args) {
+                Ok(args) => Box::pin(call(args)),
+                Err(e) => {
+                    let error = CommandError::invalid_args(&e.to_string());
+                    Box::pin(async move { Err(error) })
+                }
+            }
+        });
+        self.commands.insert(name.to_string(), handler);
+        Ok(())
+    }
+
+    /// Returns the names of the registered commands.
+    pub fn names(&self) -> Vec<&str> {
+        self.commands.keys().map(|name| name.as_str()).collect()
+    }
+
+    /// Parses a call, runs the command and returns its result.
+    async fn invoke(&self, request: &Request) -> std::result::Result<Value, CommandError> {
+        let invocation: Invocation = request
+            .parse_json()
+            .map_err(|e| CommandError::invalid_request(&e.to_string()))?;
+        let handler = self
+            .commands
+            .get(&invocation.command)
+            .ok_or_else(|| CommandError::unknown_command(&invocation.command))?;
+
+        let result = handler(invocation.args).await;
+        if let Err(e) = &result {
+            logln(&format!("Command {} failed: {}", invocation.command, e));
+        }
+        result
+    }
+}
+
+impl Default for PluginCommands {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl AsyncPlugin for PluginCommands {
+    fn init(&mut self) -> Result<()> {
+        logln(&format!(
+            "{} {}",
+            Color::BrightBlack.paint("Plugin initialized: "),
+            Color::BrightBlue.paint("PluginCommands")
+        ));
+        Ok(())
+    }
+
+    fn routes(&self) -> Vec<Route> {
+        let prefix = self.url_prefix.trim_end_matches('/');
+        vec![
+            Route::new("POST", prefix),
+            Route::new("GET", &format!("{}/client.js", prefix)),
+        ]
+    }
+
+    fn handle(&self, request: Request) -> PluginFuture<'_> {
+        Box::pin(async move {
+            if request.method == "GET" {
+                return Ok(Response::response_ok(CLIENT_JS.as_bytes().to_vec(), Mime::JavaScript));
+            }
+
+            let (status, body) = match self.invoke(&request).await {
+                Ok(result) => (StatusCode::Ok, json!({ "ok": true, "result": result })),
+                Err(e) => (e.status, e.to_json()),
+            };
+            let mut response = Response::response_ok(body.to_string().into_bytes(), Mime::ApplicationJson);
+            response.set_status(status);
+            Ok(response)
+        })
+    }
+}
+
+fn to_value<R: Serialize>(result: &R) -> std::result::Result<Value, CommandError> {
+    serde_json::to_value(result).map_err(|e| CommandError::internal(&format!("Cannot serialize result: {}", e)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Deserialize)]
+    struct AddArgs {
+        a: i64,
+        b: i64,
+    }
+
+    fn commands() -> PluginCommands {
+        let mut commands = PluginCommands::new();
+        commands.register("add", |args: AddArgs| Ok(args.a + args.b)).unwrap();
+        commands
+            .register("explode", |_: Value| -> std::result::Result<(), CommandError> { panic!("boom") })
+            .unwrap();
+        commands
+    }
+
+    /// Posts `body` to the endpoint and returns the status and parsed JSON response.
+    async fn call(commands: &PluginCommands, content_type: &str, body: &str) -> (u16, Value) {
+        let raw = format!(
+            "POST /smn/invoke HTTP/1.1\r\nContent-Type: {}\r\nContent-Length: {}\r\n\r\n{}",
+            content_type,
+            body.len(),
+            body
+        );
+        let request = Request::from_string(&raw).unwrap();
+        let response = commands.handle(request).await.unwrap();
+        (response.status_code, serde_json::from_slice(&response.body).unwrap())
+    }
+
+    #[tokio::test]
+    async fn invokes_commands() {
+        let (status, body) = call(&commands(), "application/json", r#"{"command":"add","args":{"a":2,"b":3}}"#).await;
+        assert_eq!(status, 200);
+        assert_eq!(body, json!({ "ok": true, "result": 5 }));
+    }
+
+    #[tokio::test]
+    async fn unknown_commands_are_not_found() {
+        let (status, body) = call(&commands(), "application/json", r#"{"command":"missing"}"#).await;
+        assert_eq!(status, 404);
+        assert_eq!(body["ok"], json!(false));
+        assert_eq!(body["error"]["code"], json!("unknown_command"));
+    }
+
+    #[tokio::test]
+    async fn rejects_bad_args() {
+        let (status, body) = call(&commands(), "application/json", r#"{"command":"add","args":{"a":"two"}}"#).await;
+        assert_eq!(status, 400);
+        assert_eq!(body["error"]["code"], json!("invalid_args"));
+    }
+
+    #[tokio::test]
+    async fn rejects_bodies_that_are_not_json_calls() {
+        let (status, body) = call(&commands(), "application/json", "not json").await;
+        assert_eq!(status, 400);
+        assert_eq!(body["error"]["code"], json!("invalid_request"));
+
+        let (status, body) = call(&commands(), "text/plain", r#"{"command":"add"}"#).await;
+        assert_eq!(status, 400);
+        assert_eq!(body["error"]["code"], json!("invalid_request"));
+    }
+
+    #[tokio::test]
+    async fn panicking_commands_are_internal_errors() {
+        let (status, body) = call(&commands(), "application/json", r#"{"command":"explode"}"#).await;
+        assert_eq!(status, 500);
+        assert_eq!(body["error"]["code"], json!("internal"));
+    }
+
+    #[test]
+    fn rejects_duplicate_names() {
+        let mut commands = commands();
+        let err = commands.register("add", |_: Value| Ok(0)).unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::AlreadyExists);
+        let err = commands.register_async("add", |_: Value| async { Ok(0) }).unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::AlreadyExists);
+    }
+}
@@ -0,0 +1,55 @@
+// Command client served by PluginCommands.
+//
+// Load it with <script src="/smn/invoke/client.js"></script>, then call Rust commands with:
+//
+//     const result = await smn.invoke("remap_texture", { path, channels: "bgra" });
+//
+// Failed calls reject with an smn.InvokeError carrying the command's error code and data.
+(function () {
+    "use strict";
+
+    const smn = (window.smn = window.smn || {});
+    const script = document.currentScript;
+    const endpoint = script ? script.src.replace(/\/client\.js(\?.*)?$/, "") : "/smn/invoke";
+
+    class InvokeError extends Error {
+        constructor(code, message, data, status) {
+            super(message);
+            this.name = "InvokeError";
+            this.code = code;
+            this.data = data;
+            this.status = status;
+        }
+    }
+
+    async function invoke(command, args) {
+        let response;
+        try {
+            response = await fetch(endpoint, {
+                method: "POST",
+                headers: { "Content-Type": "application/json" },
+                body: JSON.stringify({ command, args: args === undefined ? null : args }),
+            });
+        } catch (error) {
+            throw new InvokeError("network", error.message, undefined, 0);
+        }
+
+        let body = null;
+        try {
+            body = await response.json();
+        } catch (error) {
+            // Not a command response, e.g. a proxy error page
+        }
+
+        if (body && body.ok === true) {
+            return body.result;
+        }
+        if (body && body.error) {
+            throw new InvokeError(body.error.code, body.error.message, body.error.data, response.status);
+        }
+        throw new InvokeError("invalid_response", "Unexpected response with status " + response.status, undefined, response.status);
+    }
+
+    smn.invoke = invoke;
+    smn.InvokeError = InvokeError;
+})();
@@ -0,0 +1,83 @@
+// src/plugins/plugin_commands/plugin_commands_error.rs
+
+use std::fmt;
+
+use serde_json::{json, Value};
+
+use crate::server::structs::structs_header::StatusCode;
+
+/// Structured error returned by a command, sent to the page as
+/// `{"ok": false, "error": {"code": .., "message": .., "data": ..}}`.
+///
+/// `code` is a stable machine-readable identifier for the page to branch on, while `message`
+/// is meant for people.
+#[derive(Debug, Clone)]
+pub struct CommandError {
+    pub code: String,
+    pub message: String,
+    pub data: Option<Value>,
+    pub status: StatusCode,
+}
+
+impl CommandError {
+    /// Creates an error answered with `422 Unprocessable Entity`: the call was understood but failed.
+    pub fn new(code: &str, message: &str) -> Self {
+        Self {
+            code: code.to_string(),
+            message: message.to_string(),
+            data: None,
+            status: StatusCode::UnprocessableEntity,
+        }
+    }
+
+    /// Attaches extra details for the page.
+    pub fn set_data(mut self, data: Value) -> Self {
+        self.data = Some(data);
+        self
+    }
+
+    /// Overrides the HTTP status of the error response.
+    pub fn set_status(mut self, status: StatusCode) -> Self {
+        self.status = status;
+        self
+    }
+
+    pub(crate) fn unknown_command(name: &str) -> Self {
+        Self::new("unknown_command", &format!("No command named {}", name)).set_status(StatusCode::NotFound)
+    }
+
+    pub(crate) fn invalid_request(message: &str) -> Self {
+        Self::new("invalid_request", message).set_status(StatusCode::BadRequest)
+    }
+
+    pub(crate) fn invalid_args(message: &str) -> Self {
+        Self::new("invalid_args", message).set_status(StatusCode::BadRequest)
+    }
+
+    pub(crate) fn internal(message: &str) -> Self {
+        Self::new("internal", message).set_status(StatusCode::InternalServerError)
+    }
+
+    /// Returns the JSON body of the error response.
+    pub fn to_json(&self) -> Value {
+        let mut error = json!({ "code": self.code, "message": self.message });
+        if let Some(data) = &self.data {
+            error["data"] = data.clone();
+        }
+        json!({ "ok": false, "error": error })
+    }
+}
+
+impl fmt::Display for CommandError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: {}", self.code, self.message)
+    }
+}
+
+impl std::error::Error for CommandError {}
+
+impl From<std::io::Error> for CommandError {
+    fn from(err: std::io::Error) -> Self {
+        Self::new("io", &err.to_string())
+    }
+}
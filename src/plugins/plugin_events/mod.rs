@@ -0,0 +1,189 @@
+mod plugin_events_bus;
+
+#[allow(unused)]
+pub use plugin_events_bus::{topic_matches, BusEvent, EventBus, Subscription};
+
+use std::io::Result;
+use std::time::Duration;
+
+use serde::Deserialize;
+
+use crate::server::plugin::plugin_base::{AsyncPlugin, PluginError, PluginFuture};
+use crate::server::plugin::plugin_router::Route;
+use crate::server::structs::structs_event::Event;
+use crate::server::structs::structs_mime::Mime;
+use crate::server::structs::structs_request::Request;
+use crate::server::structs::structs_response::Response;
+use crate::server::websocket::websocket_core::{Message, WebSocket};
+use crate::util::logging::{logln, Color};
+
+/// The JavaScript client, defining `smn.events`.
+const CLIENT_JS: &str = include_str!("plugin_events_client.js");
+
+/// How often an idle event stream sends a comment, so dead clients are noticed.
+const KEEP_ALIVE_INTERVAL: Duration = Duration::from_secs(15);
+
+/// Delivers `EventBus` events to pages over Server-Sent Events or a WebSocket.
+///
+/// Under its URL prefix (`/smn/events` by default) it serves:
+///
+/// * `GET {prefix}?topics=a,b.*` - an event stream for the given topic patterns.
+/// * `GET {prefix}/ws` - a WebSocket; the page sends `{"subscribe": [..]}` and
+///   `{"unsubscribe": [..]}` messages to choose its topics.
+/// * `GET {prefix}/client.js` - the JavaScript client, which exposes `smn.events.on(topic, callback)`.
+///
+/// Both the event stream and the WebSocket are long-lived, so they need the TCP server
+/// from `start_server`. Served through `start_protocol_server` (the window's `smn://`
+/// scheme) they are answered with `501 Not Implemented`; only `client.js` loads.
+///
+/// The WebSocket only accepts pages served by this server, or from `allowed_origins`.
+pub struct PluginEvents {
+    pub bus: EventBus,
+    pub url_prefix: String,
+    pub allowed_origins: Vec<String>,
+}
+
+/// Subscription change sent by a page over the WebSocket.
+#[derive(Deserialize)]
+struct ControlMessage {
+    #[serde(default)]
+    subscribe: Vec<String>,
+    #[serde(default)]
+    unsubscribe: Vec<String>,
+}
+
+impl PluginEvents {
+    /// Creates a new instance of `PluginEvents` delivering the events of `bus`.
+    pub fn new(bus: EventBus) -> Self {
+        Self {
+            bus,
+            url_prefix: "/smn/events".to_string(),
+            allowed_origins: Vec::new(),
+        }
+    }
+
+    /// Sets the URL prefix the endpoints are served under.
+    pub fn set_url_prefix(mut self, url_prefix: &str) -> Self {
+        self.url_prefix = format!("/{}", url_prefix.trim_matches('/'));
+        self
+    }
+
+    /// Sets the origins, such as `http://localhost:5173`, whose pages may also open the WebSocket.
+    pub fn set_allowed_origins(mut self, origins: &[&str]) -> Self {
+        self.allowed_origins = origins.iter().map(|origin| origin.to_string()).collect();
+        self
+    }
+
+    fn serve_stream(&self, request: &Request) -> Response {
+        let topics = parse_topics(request.query("topics").unwrap_or("*"));
+        let since = request.last_event_id().and_then(|id| id.trim().parse().ok());
+        let mut subscription = self.bus.subscribe(since);
+        let (response, events) = Response::response_sse();
+
+        tokio::spawn(async move {
+            let mut keep_alive = tokio::time::interval(KEEP_ALIVE_INTERVAL);
+            keep_alive.tick().await;
+            loop {
+                let event = tokio::select! {
+                    event = subscription.next() => match event {
+                        Some(event) if matches_any(&topics, &event.topic) => {
+                            Event::new(&event.to_json()).set_id(&event.id.to_string())
+                        }
+                        Some(_) => continue,
+                        None => break,
+                    },
+                    _ = keep_alive.tick() => Event::comment("keep-alive"),
+                };
+                // Fails once the page is gone or the server shuts down
+                if events.send(event).await.is_err() {
+                    break;
+                }
+            }
+        });
+
+        response
+    }
+
+    fn serve_websocket(&self, request: &Request) -> std::result::Result<Response, PluginError> {
+        let mut topics = parse_topics(request.query("topics").unwrap_or(""));
+        let mut subscription = self.bus.subscribe(None);
+
+        let response = Response::response_websocket_with_origins(request, &self.allowed_origins, move |mut socket: WebSocket| async move {
+            loop {
+                tokio::select! {
+                    message = socket.recv() => match message {
+                        Some(Message::Text(text)) => match serde_json::from_str::<ControlMessage>(&text) {
+                            Ok(control) => {
+                                topics.retain(|topic| !control.unsubscribe.contains(topic));
+                                for topic in control.subscribe {
+                                    if !topics.contains(&topic) {
+                                        topics.push(topic);
+                                    }
+                                }
+                            }
+                            Err(e) => logln(&format!("Invalid event subscription message: {}", e)),
+                        },
+                        Some(Message::Binary(_)) => {}
+                        None => break,
+                    },
+                    event = subscription.next() => match event {
+                        Some(event) if matches_any(&topics, &event.topic) => {
+                            if socket.send(Message::Text(event.to_json())).await.is_err() {
+                                break;
+                            }
+                        }
+                        Some(_) => {}
+                        None => break,
+                    },
+                }
+            }
+        })?;
+
+        Ok(response)
+    }
+}
+
+impl AsyncPlugin for PluginEvents {
+    fn init(&mut self) -> Result<()> {
+        logln(&format!(
+            "{} {}",
+            Color::BrightBlack.paint("Plugin initialized: "),
+            Color::BrightBlue.paint("PluginEvents")
+        ));
+        Ok(())
+    }
+
+    fn routes(&self) -> Vec<Route> {
+        let prefix = self.url_prefix.trim_end_matches('/');
+        vec![
+            Route::new("GET", prefix),
+            Route::new("GET", &format!("{}/ws", prefix)),
+            Route::new("GET", &format!("{}/client.js", prefix)),
+        ]
+    }
+
+    fn handle(&self, request: Request) -> PluginFuture<'_> {
+        Box::pin(async move {
+            let relative = request.path.strip_prefix(self.url_prefix.trim_end_matches('/')).unwrap_or("");
+            match relative {
+                "/ws" => self.serve_websocket(&request),
+                "/client.js" => Ok(Response::response_ok(CLIENT_JS.as_bytes().to_vec(), Mime::JavaScript)),
+                _ => Ok(self.serve_stream(&request)),
+            }
+        })
+    }
+}
+
+/// Splits a comma-separated list of topic patterns.
+fn parse_topics(topics: &str) -> Vec<String> {
+    topics
+        .split(',')
+        .map(|topic| topic.trim())
+        .filter(|topic| !topic.is_empty())
+        .map(|topic| topic.to_string())
+        .collect()
+}
+
+fn matches_any(patterns: &[String], topic: &str) -> bool {
+    patterns.iter().any(|pattern| topic_matches(pattern, topic))
+}
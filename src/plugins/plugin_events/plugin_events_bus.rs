@@ -0,0 +1,236 @@
+// src/plugins/plugin_events/plugin_events_bus.rs
+
+use std::collections::VecDeque;
+use std::sync::{Arc, Mutex, MutexGuard};
+
+use serde::Serialize;
+use tokio::sync::broadcast;
+
+use crate::server::plugin::plugin_base::PluginError;
+use crate::util::logging::logln;
+
+/// Number of events buffered for each subscriber before slow subscribers start missing events.
+const BUS_CAPACITY: usize = 256;
+
+/// Number of recent events kept so reconnecting clients can catch up.
+const HISTORY_LEN: usize = 100;
+
+/// An event published on the bus.
+#[derive(Debug, Clone)]
+pub struct BusEvent {
+    /// Sequence number, increasing by one per emitted event.
+    pub id: u64,
+    pub topic: String,
+    /// The payload serialized as JSON.
+    pub payload: Arc<str>,
+}
+
+impl BusEvent {
+    /// Returns the JSON object sent to pages: `{"id": .., "topic": .., "payload": ..}`.
+    pub fn to_json(&self) -> String {
+        let topic = serde_json::to_string(&self.topic).unwrap_or_else(|_| "\"\"".to_string());
+        format!("{{\"id\":{},\"topic\":{},\"payload\":{}}}", self.id, topic, self.payload)
+    }
+}
+
+struct History {
+    next_id: u64,
+    events: VecDeque<BusEvent>,
+}
+
+struct BusInner {
+    sender: broadcast::Sender<BusEvent>,
+    history: Mutex<History>,
+}
+
+/// Publish/subscribe bus that carries events from Rust code to open pages.
+///
+/// The bus is cheap to clone, and `emit` can be called from any thread, with or without a
+/// tokio runtime. Pages receive the events through `PluginEvents`.
+///
+/// ```ignore
+/// let events = EventBus::new();
+/// plugin_manager.apply_async_plugin(Box::new(PluginEvents::new(events.clone())))?;
+///
+/// events.emit("job.progress", &json!({ "done": 3, "total": 10 }))?;
+/// ```
+#[derive(Clone)]
+pub struct EventBus {
+    inner: Arc<BusInner>,
+}
+
+impl EventBus {
+    pub fn new() -> Self {
+        let (sender, _) = broadcast::channel(BUS_CAPACITY);
+        Self {
+            inner: Arc::new(BusInner {
+                sender,
+                history: Mutex::new(History {
+                    next_id: 1,
+                    events: VecDeque::with_capacity(HISTORY_LEN),
+                }),
+            }),
+        }
+    }
+
+    fn history(&self) -> MutexGuard<'_, History> {
+        self.inner.history.lock().unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+
+    /// Publishes `payload` on `topic` to every subscriber, returning the event id.
+    ///
+    /// Topics are dot-separated names such as `job.progress`. Emitting with no open page is
+    /// not an error; the event is kept in the recent history only.
+    pub fn emit<T: Serialize>(&self, topic: &str, payload: &T) -> Result<u64, PluginError> {
+        let payload = serde_json::to_string(payload)
+            .map_err(|e| PluginError::Internal(format!("Cannot serialize event {}: {}", topic, e)))?;
+
+        // Sending while holding the history lock keeps ids, history and delivery in the same order
+        let mut history = self.history();
+        let event = BusEvent {
+            id: history.next_id,
+            topic: topic.to_string(),
+            payload: payload.into(),
+        };
+        history.next_id += 1;
+        if history.events.len() == HISTORY_LEN {
+            history.events.pop_front();
+        }
+        history.events.push_back(event.clone());
+        let _ = self.inner.sender.send(event.clone());
+
+        Ok(event.id)
+    }
+
+    /// Subscribes to all events emitted from now on.
+    ///
+    /// With `since`, events after that id still in the recent history are delivered first, so
+    /// a client that reconnects does not miss what was emitted in between. An id the bus has
+    /// not reached, such as one from before the app restarted, is treated as the latest event.
+    pub fn subscribe(&self, since: Option<u64>) -> Subscription {
+        let history = self.history();
+        let receiver = self.inner.sender.subscribe();
+        let since = since.map(|since| since.min(history.next_id - 1));
+        let backlog = match since {
+            Some(since) => history.events.iter().filter(|event| event.id > since).cloned().collect(),
+            None => VecDeque::new(),
+        };
+        Subscription {
+            receiver,
+            backlog,
+            last_id: since.unwrap_or(history.next_id - 1),
+        }
+    }
+}
+
+impl Default for EventBus {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// A stream of events from an `EventBus`.
+pub struct Subscription {
+    receiver: broadcast::Receiver<BusEvent>,
+    backlog: VecDeque<BusEvent>,
+    last_id: u64,
+}
+
+impl Subscription {
+    /// Waits for the next event, or returns `None` once the bus is dropped.
+    ///
+    /// A subscriber that falls more than `BUS_CAPACITY` events behind skips the oldest ones.
+    pub async fn next(&mut self) -> Option<BusEvent> {
+        if let Some(event) = self.backlog.pop_front() {
+            self.last_id = event.id;
+            return Some(event);
+        }
+
+        loop {
+            match self.receiver.recv().await {
+                // Already delivered from the backlog
+                Ok(event) if event.id <= self.last_id => continue,
+                Ok(event) => {
+                    self.last_id = event.id;
+                    return Some(event);
+                }
+                Err(broadcast::error::RecvError::Lagged(skipped)) => {
+                    logln(&format!("Event subscriber fell behind, skipped {} events", skipped));
+                }
+                Err(broadcast::error::RecvError::Closed) => return None,
+            }
+        }
+    }
+}
+
+/// Returns `true` if `topic` matches `pattern`.
+///
+/// A pattern is either `*` for every topic, a prefix such as `job.*` for `job.progress` and
+/// `job.step.done`, or an exact topic name.
+pub fn topic_matches(pattern: &str, topic: &str) -> bool {
+    match pattern.strip_suffix('*') {
+        Some("") => true,
+        Some(prefix) if prefix.ends_with('.') => topic.starts_with(prefix),
+        _ => pattern == topic,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn wildcard_matches_every_topic() {
+        assert!(topic_matches("*", "job.progress"));
+        assert!(topic_matches("*", ""));
+    }
+
+    #[test]
+    fn prefix_matches_nested_topics() {
+        assert!(topic_matches("job.*", "job.progress"));
+        assert!(topic_matches("job.*", "job.step.done"));
+        assert!(!topic_matches("job.*", "job"));
+        assert!(!topic_matches("job.*", "jobs.progress"));
+        assert!(!topic_matches("job.*", "other.job.progress"));
+    }
+
+    #[test]
+    fn other_patterns_match_exactly() {
+        assert!(topic_matches("job.progress", "job.progress"));
+        assert!(!topic_matches("job.progress", "job.progress.extra"));
+        // A star not following a dot is not a wildcard
+        assert!(!topic_matches("job*", "jobs"));
+        assert!(topic_matches("job*", "job*"));
+    }
+
+    #[tokio::test]
+    async fn subscribers_catch_up_from_history() {
+        let bus = EventBus::new();
+        let first = bus.emit("a", &1).unwrap();
+        bus.emit("b", &"two").unwrap();
+
+        let mut subscription = bus.subscribe(Some(first));
+        bus.emit("c", &[3]).unwrap();
+
+        let events = [
+            subscription.next().await.unwrap(),
+            subscription.next().await.unwrap(),
+        ];
+        assert_eq!(events[0].to_json(), r#"{"id":2,"topic":"b","payload":"two"}"#);
+        assert_eq!(events[1].to_json(), r#"{"id":3,"topic":"c","payload":[3]}"#);
+    }
+
+    #[tokio::test]
+    async fn future_ids_do_not_hide_live_events() {
+        let bus = EventBus::new();
+        bus.emit("a", &1).unwrap();
+
+        // Sent by a page that connected before the app restarted
+        let mut subscription = bus.subscribe(Some(500));
+        bus.emit("b", &2).unwrap();
+
+        let event = subscription.next().await.unwrap();
+        assert_eq!(event.id, 2);
+        assert_eq!(event.topic, "b");
+    }
+}
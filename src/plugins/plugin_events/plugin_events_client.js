@@ -0,0 +1,140 @@
+// Event bus client served by PluginEvents.
+//
+// Load it with <script src="/smn/events/client.js"></script>, optionally with
+// data-transport="websocket", then subscribe with:
+//
+//     const unsubscribe = smn.events.on("job.*", (payload, topic) => { ... });
+(function () {
+    "use strict";
+
+    const smn = (window.smn = window.smn || {});
+    const script = document.currentScript;
+    const baseUrl = script ? script.src.replace(/\/client\.js(\?.*)?$/, "") : "/smn/events";
+    const transport = (script && script.dataset.transport) || "sse";
+
+    // Topic pattern -> set of callbacks
+    const listeners = new Map();
+    let source = null;
+    let socket = null;
+    let reconnectScheduled = false;
+    let retryDelay = 1000;
+
+    function matches(pattern, topic) {
+        if (pattern === "*") {
+            return true;
+        }
+        if (pattern.endsWith(".*")) {
+            return topic.startsWith(pattern.slice(0, -1));
+        }
+        return pattern === topic;
+    }
+
+    function dispatch(data) {
+        const event = JSON.parse(data);
+        for (const [pattern, callbacks] of listeners) {
+            if (!matches(pattern, event.topic)) {
+                continue;
+            }
+            for (const callback of callbacks) {
+                try {
+                    callback(event.payload, event.topic);
+                } catch (error) {
+                    console.error("smn.events listener failed:", error);
+                }
+            }
+        }
+    }
+
+    // Server-Sent Events: the topic list is part of the URL, so reconnect when it changes
+    function connectSse() {
+        if (source) {
+            source.close();
+            source = null;
+        }
+        if (listeners.size === 0) {
+            return;
+        }
+        const topics = Array.from(listeners.keys()).join(",");
+        source = new EventSource(baseUrl + "?topics=" + encodeURIComponent(topics));
+        source.onmessage = (message) => dispatch(message.data);
+    }
+
+    function scheduleSse() {
+        if (reconnectScheduled) {
+            return;
+        }
+        reconnectScheduled = true;
+        queueMicrotask(() => {
+            reconnectScheduled = false;
+            connectSse();
+        });
+    }
+
+    // WebSocket: subscriptions are sent over the open socket
+    function connectWebSocket() {
+        const url = new URL(baseUrl + "/ws", window.location.href);
+        url.protocol = url.protocol === "https:" ? "wss:" : "ws:";
+        socket = new WebSocket(url);
+        socket.onopen = () => {
+            retryDelay = 1000;
+            sendControl({ subscribe: Array.from(listeners.keys()) });
+        };
+        socket.onmessage = (message) => dispatch(message.data);
+        socket.onclose = () => {
+            socket = null;
+            if (listeners.size > 0) {
+                setTimeout(connectWebSocket, retryDelay);
+                retryDelay = Math.min(retryDelay * 2, 30000);
+            }
+        };
+    }
+
+    function sendControl(message) {
+        if (socket && socket.readyState === WebSocket.OPEN) {
+            socket.send(JSON.stringify(message));
+        }
+    }
+
+    function topicsChanged(topic, subscribed) {
+        if (transport !== "websocket") {
+            scheduleSse();
+        } else if (!socket) {
+            connectWebSocket();
+        } else {
+            sendControl(subscribed ? { subscribe: [topic] } : { unsubscribe: [topic] });
+        }
+    }
+
+    function on(topic, callback) {
+        let callbacks = listeners.get(topic);
+        if (!callbacks) {
+            callbacks = new Set();
+            listeners.set(topic, callbacks);
+            topicsChanged(topic, true);
+        }
+        callbacks.add(callback);
+        return () => off(topic, callback);
+    }
+
+    function off(topic, callback) {
+        const callbacks = listeners.get(topic);
+        if (!callbacks) {
+            return;
+        }
+        callbacks.delete(callback);
+        if (callbacks.size === 0) {
+            listeners.delete(topic);
+            topicsChanged(topic, false);
+        }
+    }
+
+    function once(topic, callback) {
+        const unsubscribe = on(topic, (payload, eventTopic) => {
+            unsubscribe();
+            callback(payload, eventTopic);
+        });
+        return unsubscribe;
+    }
+
+    smn.events = { on, off, once };
+})();
@@ -1,23 +1,171 @@
-use std::io::{Result, Write};
-use std::net::TcpStream;
+use std::io::Result;
+use std::path::{Path, PathBuf};
 
-use crate::server::plugin::plugin_base::Plugin;
+use crate::io::io_embed::AssetBundle;
+use crate::io::io_file::file_read_bytes;
+use crate::io::io_path::{get_extension, path_get_root};
+use crate::server::plugin::plugin_base::{Plugin, PluginError};
+use crate::server::plugin::plugin_router::Route;
+use crate::server::structs::structs_header::StatusCode;
 use crate::server::structs::structs_mime::Mime;
 use crate::server::structs::structs_request::Request;
 use crate::server::structs::structs_response::Response;
+use crate::util::encoding::percent_encode_segment;
 use crate::util::logging::{logln, Color};
 
+/// Serves the files of a directory, or of an embedded `AssetBundle`, under a URL prefix.
+///
+/// Relative directories are resolved against `SMNVIEW_ROOT`. Requests for a directory serve
+/// its `index.html`, and any path that would leave the directory is rejected.
 pub struct PluginStatics{
     pub path_statics: String,
+    pub url_prefix: String,
+    pub bundle: Option<&'static AssetBundle>,
+}
+
+/// Outcome of resolving a request path against the statics source.
+enum StaticTarget {
+    File { content: Vec<u8>, extension: String },
+    Redirect,
+    NotFound,
+    Forbidden,
 }
 
 impl PluginStatics {
-    /// Creates a new instance of `PluginStatics`.
+    /// Creates a new instance of `PluginStatics` serving `path_statics` under `/{path_statics}`.
     pub fn new(path_statics: String) -> Self {
+        let url_prefix = normalize_prefix(&path_statics);
         Self {
             path_statics,
+            url_prefix,
+            bundle: None,
+        }
+    }
+
+    /// Creates a new instance of `PluginStatics` serving an embedded bundle under `/`.
+    pub fn embedded(bundle: &'static AssetBundle) -> Self {
+        Self {
+            path_statics: String::new(),
+            url_prefix: "/".to_string(),
+            bundle: Some(bundle),
+        }
+    }
+
+    /// Sets the URL prefix the files are served under, e.g. `/assets` or `/`.
+    pub fn set_url_prefix(mut self, url_prefix: &str) -> Self {
+        self.url_prefix = normalize_prefix(url_prefix);
+        self
+    }
+
+    /// Resolves a URL path, relative to the prefix, to a file in the statics source.
+    ///
+    /// `is_dir_url` tells whether the request path ends with a slash; directories requested
+    /// without one are redirected so relative links in their index resolve correctly.
+    fn resolve(&self, relative: &str, is_dir_url: bool) -> StaticTarget {
+        let segments = match sanitize_segments(relative) {
+            Some(segments) => segments,
+            None => return StaticTarget::Forbidden,
+        };
+
+        match self.bundle {
+            Some(bundle) => resolve_embedded(bundle, &segments, is_dir_url),
+            None => self.resolve_disk(&segments, is_dir_url),
+        }
+    }
+
+    /// Builds the URL of a directory with its trailing slash, keeping any query string after it.
+    ///
+    /// The URL is rebuilt from the prefix and the cleaned segments rather than the request
+    /// target, so a path such as `//host` cannot redirect to another site.
+    fn directory_location(&self, relative: &str, query: Option<&str>) -> String {
+        let mut location = self.url_prefix.trim_end_matches('/').to_string();
+        for segment in sanitize_segments(relative).unwrap_or_default() {
+            location.push('/');
+            location.push_str(&percent_encode_segment(&segment));
+        }
+        location.push('/');
+        if let Some(query) = query {
+            location.push('?');
+            location.push_str(query);
+        }
+        location
+    }
+
+    fn resolve_disk(&self, segments: &[String], is_dir_url: bool) -> StaticTarget {
+        let root = Path::new(&self.path_statics);
+        let root = if root.is_absolute() { root.to_path_buf() } else { path_get_root().join(root) };
+        let root = match root.canonicalize() {
+            Ok(root) => root,
+            Err(_) => return StaticTarget::NotFound,
+        };
+
+        // Canonicalizing resolves symlinks, so links pointing outside the root are caught too
+        let path = root.join(segments.iter().collect::<PathBuf>());
+        let mut path = match path.canonicalize() {
+            Ok(path) => path,
+            Err(_) => return StaticTarget::NotFound,
+        };
+        if !path.starts_with(&root) {
+            return StaticTarget::Forbidden;
+        }
+
+        if path.is_dir() {
+            if !is_dir_url {
+                return StaticTarget::Redirect;
+            }
+            // The index may itself be a symlink, so it is checked against the root as well
+            path = match path.join("index.html").canonicalize() {
+                Ok(path) => path,
+                Err(_) => return StaticTarget::NotFound,
+            };
+            if !path.starts_with(&root) {
+                return StaticTarget::Forbidden;
+            }
+        }
+
+        match file_read_bytes(&path) {
+            Ok(content) => StaticTarget::File {
+                content,
+                extension: get_extension(&path).unwrap_or("").to_string(),
+            },
+            Err(_) => StaticTarget::NotFound,
+        }
+    }
+}
+
+fn resolve_embedded(bundle: &AssetBundle, segments: &[String], is_dir_url: bool) -> StaticTarget {
+    let mut key = segments.join("/");
+
+    if bundle.get(&key).is_none() && bundle.is_dir(&key) {
+        if !is_dir_url {
+            return StaticTarget::Redirect;
+        }
+        key = if key.is_empty() { "index.html".to_string() } else { format!("{}/index.html", key) };
+    }
+
+    match bundle.get(&key) {
+        Some(content) => StaticTarget::File {
+            content: content.to_vec(),
+            extension: key.rsplit_once('.').map(|(_, extension)| extension.to_string()).unwrap_or_default(),
+        },
+        None => StaticTarget::NotFound,
+    }
+}
+
+/// Splits a decoded relative URL path, returning `None` if any segment could escape the
+/// statics root.
+///
+/// The request path is percent-decoded before routing, so `%2e%2e` and `%2f` arrive here
+/// as `..` and `/` and are caught like their literal forms.
+fn sanitize_segments(relative: &str) -> Option<Vec<String>> {
+    let mut segments = Vec::new();
+    for segment in relative.split('/').filter(|segment| !segment.is_empty()) {
+        if segment == "." || segment == ".." || segment.contains(['\\', ':', '\0']) {
+            return None;
         }
+        segments.push(segment.to_string());
     }
+    Some(segments)
 }
 
 impl Plugin for PluginStatics {
@@ -26,22 +174,101 @@ impl Plugin for PluginStatics {
         Ok(())
     }
 
-    fn catch(&self, request: &Request) -> bool {
-        request.path == "/" && request.method == "GET" 
+    fn routes(&self) -> Vec<Route> {
+        vec![Route::new("GET", &format!("{}/*path", self.url_prefix.trim_end_matches('/')))]
     }
 
-    fn serve(&self, stream: &mut TcpStream, request: &Request) -> Result<()> {
-        // Response: Path: {path},\n Method: {method}
-        let msg = format!("Path: {},\nMethod: {} Static: {}", request.path, request.method, self.path_statics);
-        let body = msg.as_bytes().to_vec();
+    fn serve(&self, request: &Request) -> std::result::Result<Response, PluginError> {
+        let relative = request.params.get("path").map(String::as_str).unwrap_or("");
+        match self.resolve(relative, request.path.ends_with('/')) {
+            StaticTarget::File { content, extension } => {
+                let mime = Mime::from_extension_or_sniff(&extension, &content);
+                Ok(Response::response_ok(content, mime))
+            }
+            StaticTarget::Redirect => {
+                let query = request.target.split_once('?').map(|(_, query)| query);
+                let location = self.directory_location(relative, query);
+                Ok(Response::response_redirect(&location, StatusCode::MovedPermanently)?)
+            }
+            StaticTarget::NotFound => {
+                Ok(Response::response_error("File not found".to_owned(), StatusCode::NotFound))
+            }
+            StaticTarget::Forbidden => {
+                logln(&format!("{} {}", Color::BrightBlack.paint("Rejected static path: "), Color::Yellow.paint(&request.path)));
+                Ok(Response::response_error("Forbidden".to_owned(), StatusCode::Forbidden))
+            }
+        }
+    }
+}
 
-        let mime = Mime::TextPlain;
+/// Turns a prefix such as `statics/` or `/assets` into the `/statics` form used for routing.
+fn normalize_prefix(prefix: &str) -> String {
+    format!("/{}", prefix.trim_start_matches("./").trim_matches('/'))
+}
 
-        let mut response = Response::response_ok(body, mime);
-        response.body = msg.as_bytes().to_vec();
+#[cfg(all(test, unix))]
+mod tests {
+    use std::fs;
+    use std::os::unix::fs::symlink;
 
-        stream.write_all(&response.to_bytes())?;
-        stream.flush()?;
-        Ok(())
+    use super::*;
+
+    /// Creates an empty directory under the system temp directory, unique to this test.
+    fn temp_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("smn_view-statics-{}-{}", std::process::id(), name));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    fn plugin(root: &Path) -> PluginStatics {
+        PluginStatics::new(root.to_string_lossy().into_owned())
+    }
+
+    #[test]
+    fn serves_directory_index() {
+        let root = temp_dir("index");
+        fs::create_dir(root.join("docs")).unwrap();
+        fs::write(root.join("docs/index.html"), "docs").unwrap();
+
+        let statics = plugin(&root);
+        assert!(matches!(statics.resolve("docs", false), StaticTarget::Redirect));
+        match statics.resolve("docs", true) {
+            StaticTarget::File { content, extension } => {
+                assert_eq!(content, b"docs");
+                assert_eq!(extension, "html");
+            }
+            _ => panic!("expected the directory index"),
+        }
+        fs::remove_dir_all(root).unwrap();
+    }
+
+    #[test]
+    fn redirects_to_the_directory_under_the_prefix() {
+        let statics = PluginStatics::new("statics".to_string()).set_url_prefix("/");
+        assert_eq!(statics.directory_location("/docs", None), "/docs/");
+        assert_eq!(statics.directory_location("docs//my files", Some("a=1")), "/docs/my%20files/?a=1");
+
+        let statics = statics.set_url_prefix("/assets");
+        assert_eq!(statics.directory_location("", None), "/assets/");
+        assert_eq!(statics.directory_location("docs", None), "/assets/docs/");
+    }
+
+    #[test]
+    fn rejects_symlinks_leaving_the_root() {
+        let outside = temp_dir("outside");
+        fs::write(outside.join("secret.txt"), "secret").unwrap();
+        let root = temp_dir("root");
+        fs::create_dir(root.join("docs")).unwrap();
+        symlink(outside.join("secret.txt"), root.join("docs/index.html")).unwrap();
+        symlink(outside.join("secret.txt"), root.join("link.txt")).unwrap();
+
+        let statics = plugin(&root);
+        assert!(matches!(statics.resolve("docs/", true), StaticTarget::Forbidden));
+        assert!(matches!(statics.resolve("link.txt", false), StaticTarget::Forbidden));
+        assert!(matches!(statics.resolve("../outside/secret.txt", false), StaticTarget::Forbidden));
+
+        fs::remove_dir_all(root).unwrap();
+        fs::remove_dir_all(outside).unwrap();
     }
 }
@@ -1,21 +1,39 @@
-use std::io::{Result, Write};
-use std::net::TcpStream;
+use std::io::Result;
 
+use crate::io::io_embed::AssetBundle;
 use crate::io::io_file::file_read_bytes;
 use crate::io::io_path::{get_extension, path_get_root};
-use crate::server::plugin::plugin_base::Plugin;
+use crate::server::plugin::plugin_base::{Plugin, PluginError};
+use crate::server::plugin::plugin_router::Route;
 use crate::server::structs::structs_header::StatusCode;
 use crate::server::structs::structs_mime::Mime;
 use crate::server::structs::structs_request::Request;
 use crate::server::structs::structs_response::Response;
 use crate::util::logging::{logln, Color};
 
-pub struct PluginUI;
+/// Serves the app's landing page, `index.html`, at `/`.
+///
+/// The page is read from `statics/index.html` under `SMNVIEW_ROOT`, or from an embedded
+/// `AssetBundle` so the binary does not depend on files shipped next to it.
+pub struct PluginUI {
+    pub bundle: Option<&'static AssetBundle>,
+}
+
+impl Default for PluginUI {
+    fn default() -> Self {
+        Self::new()
+    }
+}
 
 impl PluginUI {
-    /// Creates a new instance of `PluginUI`.
+    /// Creates a new instance of `PluginUI` serving `statics/index.html` from disk.
     pub fn new() -> Self {
-        Self {}
+        Self { bundle: None }
+    }
+
+    /// Creates a new instance of `PluginUI` serving the `index.html` of an embedded bundle.
+    pub fn embedded(bundle: &'static AssetBundle) -> Self {
+        Self { bundle: Some(bundle) }
     }
 }
 
@@ -29,11 +47,19 @@ impl Plugin for PluginUI {
         Ok(())
     }
 
-    fn catch(&self, request: &Request) -> bool {
-        request.path == "/" && request.method == "GET"
+    fn routes(&self) -> Vec<Route> {
+        vec![Route::new("GET", "/")]
     }
 
-    fn serve(&self, stream: &mut TcpStream, _request: &Request) -> Result<()> {
+    fn serve(&self, _request: &Request) -> std::result::Result<Response, PluginError> {
+        if let Some(bundle) = self.bundle {
+            logln(&format!("{} {}", Color::BrightBlack.paint("Serving: "), Color::BrightBlue.paint("embedded index.html")));
+            return Ok(match bundle.get("index.html") {
+                Some(content) => Response::response_ok(content.to_vec(), Mime::TextHtml),
+                None => Response::response_error("File not found".to_owned(), StatusCode::NotFound),
+            });
+        }
+
         let path_landing = path_get_root().join("statics/index.html");
 
         logln(&format!(
@@ -45,19 +71,13 @@ impl Plugin for PluginUI {
         let content = match file_read_bytes(path_landing.as_path()) {
             Ok(content) => content,
             Err(_) => {
-                let response = Response::response_error("File not found".to_owned(), StatusCode::NotFound);
-                stream.write_all(&response.to_bytes())?;
-                return Ok(());
+                return Ok(Response::response_error("File not found".to_owned(), StatusCode::NotFound));
             }
         };
 
         let extension = get_extension(&path_landing).unwrap_or("html");
         let mime = Mime::from_extension(extension);
 
-        let response = Response::response_ok(content, mime);
-
-        stream.write_all(&response.to_bytes())?;
-        stream.flush()?;
-        Ok(())
+        Ok(Response::response_ok(content, mime))
     }
 }
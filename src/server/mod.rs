@@ -1,4 +1,11 @@
 pub mod structs;
 pub mod plugin;
+pub mod session;
+pub mod websocket;
 
-pub mod server_core;
\ No newline at end of file
+pub mod server_core;
+pub mod server_reader;
+pub mod server_chunked;
+pub mod server_compression;
+pub mod server_protocol;
+pub mod server_lifecycle;
\ No newline at end of file
@@ -1,2 +1,4 @@
 pub mod plugin_base;
-pub mod plugin_manager;
\ No newline at end of file
+pub mod plugin_manager;
+pub mod plugin_middleware;
+pub mod plugin_router;
\ No newline at end of file
@@ -1,19 +1,136 @@
 // src/server/plugin/plugin_base.rs
 
+use std::fmt;
+use std::future::Future;
 use std::io::Result;
-use std::net::TcpStream;
+use std::pin::Pin;
 
-use crate::server::structs::structs_request::Request;
+use crate::server::plugin::plugin_router::Route;
+use crate::server::structs::structs_header::StatusCode;
+use crate::server::structs::structs_request::{Request, RequestError};
+use crate::server::structs::structs_response::{Response, ResponseError};
+
+/// The future returned by `AsyncPlugin::handle`.
+pub type PluginFuture<'a> = Pin<Box<dyn Future<Output = std::result::Result<Response, PluginError>> + Send + 'a>>;
+
+/// Error returned by a plugin that could not produce a response.
+///
+/// The server converts it into an error response, so plugins can use `?` freely.
+#[derive(Debug)]
+pub enum PluginError {
+    /// An I/O operation failed while building the response.
+    Io(std::io::Error),
+    /// The request could not be understood by the plugin; answered with 400, or 415 for a
+    /// body of the wrong content type.
+    InvalidRequest(RequestError),
+    /// Any other failure; answered with 500.
+    Internal(String),
+}
+
+impl PluginError {
+    /// Returns the status code the server responds with for this error.
+    pub fn status_code(&self) -> StatusCode {
+        match self {
+            PluginError::InvalidRequest(RequestError::ContentTypeMismatch { .. }) => StatusCode::UnsupportedMediaType,
+            PluginError::InvalidRequest(_) => StatusCode::BadRequest,
+            PluginError::Io(_) | PluginError::Internal(_) => StatusCode::InternalServerError,
+        }
+    }
+}
+
+impl fmt::Display for PluginError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PluginError::Io(err) => write!(f, "I/O error: {}", err),
+            PluginError::InvalidRequest(err) => write!(f, "{}", err),
+            PluginError::Internal(msg) => write!(f, "Internal error: {}", msg),
+        }
+    }
+}
+
+impl std::error::Error for PluginError {}
+
+impl From<std::io::Error> for PluginError {
+    fn from(err: std::io::Error) -> Self {
+        PluginError::Io(err)
+    }
+}
+
+impl From<RequestError> for PluginError {
+    fn from(err: RequestError) -> Self {
+        PluginError::InvalidRequest(err)
+    }
+}
+
+impl From<ResponseError> for PluginError {
+    fn from(err: ResponseError) -> Self {
+        PluginError::Internal(err.to_string())
+    }
+}
 
 /// The `Plugin` trait defines the necessary methods that all plugins must implement.
+///
+/// `serve` may block, so the server runs it on tokio's blocking thread pool.
 pub trait Plugin: Send + Sync + 'static {
     /// Initializes the plugin. Called when the plugin is added to the server.
     fn init(&mut self) -> Result<()>;
 
-    /// Determines if the plugin should handle the current request.
+    /// Returns the method and path patterns this plugin handles through the route table.
+    fn routes(&self) -> Vec<Route> {
+        Vec::new()
+    }
+
+    /// Determines if the plugin should handle a request that matched no route.
+    /// Returns `true` if the plugin will handle the request.
+    fn catch(&self, _request: &Request) -> bool {
+        false
+    }
+
+    /// Serves the request by returning the response for the server to send.
+    fn serve(&self, request: &Request) -> std::result::Result<Response, PluginError>;
+}
+
+/// The `AsyncPlugin` trait is the non-blocking counterpart of `Plugin`, run directly on the
+/// server's tokio runtime.
+pub trait AsyncPlugin: Send + Sync + 'static {
+    /// Initializes the plugin. Called when the plugin is added to the server.
+    fn init(&mut self) -> Result<()>;
+
+    /// Returns the method and path patterns this plugin handles through the route table.
+    fn routes(&self) -> Vec<Route> {
+        Vec::new()
+    }
+
+    /// Determines if the plugin should handle a request that matched no route.
     /// Returns `true` if the plugin will handle the request.
-    fn catch(&self, request: &Request) -> bool;
+    fn catch(&self, _request: &Request) -> bool {
+        false
+    }
+
+    /// Handles the request and returns the response for the server to send.
+    fn handle(&self, request: Request) -> PluginFuture<'_>;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::ErrorKind;
+
+    #[test]
+    fn maps_errors_to_status_codes() {
+        let io = PluginError::from(std::io::Error::new(ErrorKind::NotFound, "missing"));
+        assert_eq!(io.status_code(), StatusCode::InternalServerError);
+        assert_eq!(PluginError::Internal("failed".to_string()).status_code(), StatusCode::InternalServerError);
+
+        let invalid = PluginError::from(RequestError::InvalidRequest("bad".to_string()));
+        assert_eq!(invalid.status_code(), StatusCode::BadRequest);
+        let mismatch = PluginError::from(RequestError::ContentTypeMismatch {
+            expected: "application/json".to_string(),
+            found: "text/plain".to_string(),
+        });
+        assert_eq!(mismatch.status_code(), StatusCode::UnsupportedMediaType);
 
-    /// Serves the request by writing the response to the stream.
-    fn serve(&self, stream: &mut TcpStream, request: &Request) -> Result<()>;
+        let response = PluginError::from(ResponseError::InvalidStatusCode(42));
+        assert_eq!(response.status_code(), StatusCode::InternalServerError);
+    }
 }
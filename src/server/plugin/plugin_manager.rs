@@ -5,41 +5,244 @@
 use std::io::Result;
 use std::sync::Arc;
 
+use crate::server::structs::structs_header::StatusCode;
 use crate::server::structs::structs_request::Request;
+use crate::server::structs::structs_response::Response;
 
-use super::plugin_base::Plugin;
+use super::plugin_base::{AsyncPlugin, Plugin, PluginError};
+use super::plugin_middleware::{Middleware, Next};
+use super::plugin_router::{Route, RouteMatch, Router};
+
+/// A plugin registered with the manager, in either its blocking or async form.
+#[derive(Clone)]
+pub enum PluginHandle {
+    Blocking(Arc<dyn Plugin + Send + Sync>),
+    Async(Arc<dyn AsyncPlugin + Send + Sync>),
+}
+
+impl PluginHandle {
+    fn catch(&self, request: &Request) -> bool {
+        match self {
+            PluginHandle::Blocking(plugin) => plugin.catch(request),
+            PluginHandle::Async(plugin) => plugin.catch(request),
+        }
+    }
+
+    /// Runs the plugin on the request. Blocking plugins are moved onto tokio's blocking thread pool.
+    pub async fn handle(self, request: Request) -> std::result::Result<Response, PluginError> {
+        match self {
+            PluginHandle::Async(plugin) => plugin.handle(request).await,
+            PluginHandle::Blocking(plugin) => tokio::task::spawn_blocking(move || plugin.serve(&request))
+                .await
+                .map_err(|e| PluginError::Internal(format!("Plugin task failed: {}", e)))?,
+        }
+    }
+}
+
+/// Adapts a closure registered with `PluginManager::route` into a blocking plugin.
+struct FnPlugin<F> {
+    handler: F,
+}
+
+impl<F> Plugin for FnPlugin<F>
+where
+    F: Fn(&Request) -> std::result::Result<Response, PluginError> + Send + Sync + 'static,
+{
+    fn init(&mut self) -> Result<()> {
+        Ok(())
+    }
+
+    fn serve(&self, request: &Request) -> std::result::Result<Response, PluginError> {
+        (self.handler)(request)
+    }
+}
 
 /// Manages a collection of plugins.
+///
+/// Requests are matched against the route table first; plugins that only implement `catch`
+/// are consulted in registration order when no route matches.
 pub struct PluginManager {
-    plugins: Vec<Arc<dyn Plugin + Send + Sync>>,
+    router: Router,
+    plugins: Vec<PluginHandle>,
+    middlewares: Vec<Arc<dyn Middleware + Send + Sync>>,
+}
+
+impl Default for PluginManager {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 impl PluginManager {
     /// Creates a new `PluginManager` instance.
     pub fn new() -> Self {
         Self {
+            router: Router::new(),
             plugins: Vec::new(),
+            middlewares: Vec::new(),
         }
     }
 
     /// Applies a new plugin to the manager.
+    ///
+    /// Fails if one of the plugin's routes is invalid or conflicts with an existing route.
     pub fn apply_plugin(&mut self, mut plugin: Box<dyn Plugin + Send + Sync>) -> Result<()> {
         plugin.init()?;
+        let routes = plugin.routes();
 
         // Convert `Box<dyn Plugin + Send + Sync>` into `Arc<dyn Plugin + Send + Sync>`
         let arc_plugin: Arc<dyn Plugin + Send + Sync> = Arc::from(plugin);
 
-        self.plugins.push(arc_plugin);
-        Ok(())
+        self.register(routes, PluginHandle::Blocking(arc_plugin))
+    }
+
+    /// Applies a new async plugin to the manager.
+    ///
+    /// Fails if one of the plugin's routes is invalid or conflicts with an existing route.
+    pub fn apply_async_plugin(&mut self, mut plugin: Box<dyn AsyncPlugin + Send + Sync>) -> Result<()> {
+        plugin.init()?;
+        let routes = plugin.routes();
+
+        let arc_plugin: Arc<dyn AsyncPlugin + Send + Sync> = Arc::from(plugin);
+
+        self.register(routes, PluginHandle::Async(arc_plugin))
+    }
+
+    /// Registers a closure as the handler for a single route.
+    pub fn route<F>(&mut self, method: &str, pattern: &str, handler: F) -> Result<()>
+    where
+        F: Fn(&Request) -> std::result::Result<Response, PluginError> + Send + Sync + 'static,
+    {
+        let plugin: Arc<dyn Plugin + Send + Sync> = Arc::new(FnPlugin { handler });
+        self.router.insert(Route::new(method, pattern), PluginHandle::Blocking(plugin))
+    }
+
+    /// Applies a middleware, which will run after every middleware applied before it.
+    pub fn apply_middleware(&mut self, middleware: Box<dyn Middleware + Send + Sync>) {
+        self.middlewares.push(Arc::from(middleware));
+    }
+
+    /// Runs the request through the middleware chain and the matching plugin.
+    pub async fn dispatch(&self, request: Request) -> std::result::Result<Response, PluginError> {
+        Next::new(&self.middlewares, self).run(request).await
+    }
+
+    /// Runs the plugin matching the request, answering 404 or 405 if there is none.
+    pub(crate) async fn route_request(&self, mut request: Request) -> std::result::Result<Response, PluginError> {
+        match self.find_plugin(&request) {
+            RouteMatch::Found(plugin, params) => {
+                request.params = params;
+                plugin.handle(request).await
+            }
+            RouteMatch::MethodNotAllowed(allowed) => Ok(Response::response_method_not_allowed(&allowed)),
+            RouteMatch::NotFound => {
+                // No plugin found, respond with No Content
+                Ok(Response::response_error("No Content".to_owned(), StatusCode::NotFound))
+            }
+        }
     }
 
-    /// Finds a plugin that can handle the given request.
-    pub fn find_plugin(&self, request: &Request) -> Option<Arc<dyn Plugin + Send + Sync>> {
+    /// Finds the plugin that should handle the given request, along with any captured path parameters.
+    pub fn find_plugin(&self, request: &Request) -> RouteMatch {
+        let route_match = self.router.find(&request.method, &request.path);
+        if let RouteMatch::Found(..) = route_match {
+            return route_match;
+        }
+
         for plugin in &self.plugins {
             if plugin.catch(request) {
-                return Some(Arc::clone(plugin));
+                return RouteMatch::Found(plugin.clone(), Default::default());
             }
         }
-        None
+        route_match
+    }
+
+    fn register(&mut self, routes: Vec<Route>, handle: PluginHandle) -> Result<()> {
+        // A plugin with a bad route is rejected as a whole, so drop any routes it already added
+        let routes_before = self.router.len();
+        for route in routes {
+            if let Err(e) = self.router.insert(route, handle.clone()) {
+                self.router.truncate(routes_before);
+                return Err(e);
+            }
+        }
+
+        self.plugins.push(handle);
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::server::structs::structs_mime::Mime;
+    use crate::server::plugin::plugin_base::PluginFuture;
+
+    struct Greeter;
+
+    impl AsyncPlugin for Greeter {
+        fn init(&mut self) -> Result<()> {
+            Ok(())
+        }
+
+        fn routes(&self) -> Vec<Route> {
+            vec![Route::new("GET", "/hello/:name")]
+        }
+
+        fn handle(&self, request: Request) -> PluginFuture<'_> {
+            Box::pin(async move {
+                tokio::task::yield_now().await;
+                let name: String = request.param("name")?;
+                Ok(Response::response_ok(format!("hello {}", name).into_bytes(), Mime::TextPlain))
+            })
+        }
+    }
+
+    /// Blocks on the runtime from `serve`, which only works off the runtime's worker threads.
+    struct Blocker;
+
+    impl Plugin for Blocker {
+        fn init(&mut self) -> Result<()> {
+            Ok(())
+        }
+
+        fn routes(&self) -> Vec<Route> {
+            vec![Route::new("GET", "/block")]
+        }
+
+        fn serve(&self, _request: &Request) -> std::result::Result<Response, PluginError> {
+            let value = tokio::runtime::Handle::current().block_on(async { 7 });
+            Ok(Response::response_ok(value.to_string().into_bytes(), Mime::TextPlain))
+        }
+    }
+
+    fn request(raw: &str) -> Request {
+        Request::from_string(raw).unwrap()
+    }
+
+    #[tokio::test]
+    async fn dispatches_async_plugins() {
+        let mut plugin_manager = PluginManager::new();
+        plugin_manager.apply_async_plugin(Box::new(Greeter)).unwrap();
+
+        let response = plugin_manager.dispatch(request("GET /hello/ada HTTP/1.1\r\n\r\n")).await.unwrap();
+        assert_eq!(response.body, b"hello ada");
+
+        let mut hello = request("GET /hello/bob HTTP/1.1\r\n\r\n");
+        hello.params.insert("name".to_string(), "bob".to_string());
+        let response = PluginHandle::Async(Arc::new(Greeter)).handle(hello).await.unwrap();
+        assert_eq!(response.body, b"hello bob");
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn runs_blocking_plugins_off_the_runtime() {
+        let mut plugin_manager = PluginManager::new();
+        plugin_manager.apply_plugin(Box::new(Blocker)).unwrap();
+
+        let response = plugin_manager.dispatch(request("GET /block HTTP/1.1\r\n\r\n")).await.unwrap();
+        assert_eq!(response.body, b"7");
+
+        let response = PluginHandle::Blocking(Arc::new(Blocker)).handle(request("GET /block HTTP/1.1\r\n\r\n")).await.unwrap();
+        assert_eq!(response.body, b"7");
     }
 }
@@ -0,0 +1,205 @@
+// src/server/plugin/plugin_middleware.rs
+
+use std::sync::Arc;
+
+use crate::server::structs::structs_request::Request;
+
+use super::plugin_base::PluginFuture;
+use super::plugin_manager::PluginManager;
+
+/// The `Middleware` trait wraps plugin dispatch for every request.
+///
+/// Middleware runs in the order it was applied. Each one receives the request and the rest
+/// of the chain as `next`; it may modify the request before calling `next.run`, modify the
+/// response it gets back, or return a response of its own without calling `next` at all.
+///
+/// ```ignore
+/// struct RequireToken;
+///
+/// impl Middleware for RequireToken {
+///     fn handle<'a>(&'a self, request: Request, next: Next<'a>) -> PluginFuture<'a> {
+///         Box::pin(async move {
+///             if request.get_header_value("x-token").is_err() {
+///                 return Ok(Response::response_error("Missing token".to_owned(), StatusCode::BadRequest));
+///             }
+///             let mut response = next.run(request).await?;
+///             response.set_header("X-Checked", "1")?;
+///             Ok(response)
+///         })
+///     }
+/// }
+/// ```
+pub trait Middleware: Send + Sync + 'static {
+    /// Handles the request, usually by passing it on with `next.run`.
+    fn handle<'a>(&'a self, request: Request, next: Next<'a>) -> PluginFuture<'a>;
+}
+
+/// The remainder of the middleware chain, ending in plugin dispatch.
+pub struct Next<'a> {
+    middlewares: &'a [Arc<dyn Middleware + Send + Sync>],
+    plugin_manager: &'a PluginManager,
+}
+
+impl<'a> Next<'a> {
+    pub(crate) fn new(middlewares: &'a [Arc<dyn Middleware + Send + Sync>], plugin_manager: &'a PluginManager) -> Self {
+        Self {
+            middlewares,
+            plugin_manager,
+        }
+    }
+
+    /// Passes the request to the next middleware, or to the matching plugin at the end of the chain.
+    pub fn run(self, request: Request) -> PluginFuture<'a> {
+        match self.middlewares.split_first() {
+            Some((middleware, rest)) => middleware.handle(request, Next::new(rest, self.plugin_manager)),
+            None => Box::pin(self.plugin_manager.route_request(request)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Mutex;
+
+    use crate::server::plugin::plugin_base::PluginError;
+    use crate::server::structs::structs_header::StatusCode;
+    use crate::server::structs::structs_mime::Mime;
+    use crate::server::structs::structs_response::Response;
+
+    type Log = Arc<Mutex<Vec<String>>>;
+
+    /// Logs when the request passes through on its way in and the response on its way out.
+    struct Trace {
+        name: &'static str,
+        log: Log,
+    }
+
+    impl Middleware for Trace {
+        fn handle<'a>(&'a self, request: Request, next: Next<'a>) -> PluginFuture<'a> {
+            Box::pin(async move {
+                self.log.lock().unwrap().push(format!("{} in", self.name));
+                let response = next.run(request).await;
+                self.log.lock().unwrap().push(format!("{} out", self.name));
+                response
+            })
+        }
+    }
+
+    /// Answers requests without an `X-Token` header itself.
+    struct RequireToken;
+
+    impl Middleware for RequireToken {
+        fn handle<'a>(&'a self, request: Request, next: Next<'a>) -> PluginFuture<'a> {
+            Box::pin(async move {
+                if !request.header_fields.contains("x-token") {
+                    return Ok(Response::response_error("Missing token".to_owned(), StatusCode::Unauthorized));
+                }
+                next.run(request).await
+            })
+        }
+    }
+
+    /// Adds a header to the request before it is handled and another to the response after.
+    struct Stamp;
+
+    impl Middleware for Stamp {
+        fn handle<'a>(&'a self, mut request: Request, next: Next<'a>) -> PluginFuture<'a> {
+            Box::pin(async move {
+                request.header_fields.insert("X-User", "ada")?;
+                let mut response = next.run(request).await?;
+                response.set_header("X-Stamped", "1")?;
+                Ok(response)
+            })
+        }
+    }
+
+    /// Returns an error without calling the rest of the chain.
+    struct Fail;
+
+    impl Middleware for Fail {
+        fn handle<'a>(&'a self, _request: Request, _next: Next<'a>) -> PluginFuture<'a> {
+            Box::pin(async move { Err(PluginError::Internal("middleware failed".to_string())) })
+        }
+    }
+
+    fn manager(log: &Log) -> PluginManager {
+        let mut plugin_manager = PluginManager::new();
+        let plugin_log = Arc::clone(log);
+        plugin_manager
+            .route("GET", "/whoami", move |request| {
+                plugin_log.lock().unwrap().push("plugin".to_string());
+                let user = request.header_fields.get("x-user").unwrap_or("nobody");
+                Ok(Response::response_ok(user.as_bytes().to_vec(), Mime::TextPlain))
+            })
+            .unwrap();
+        plugin_manager
+    }
+
+    fn request(raw: &str) -> Request {
+        Request::from_string(raw).unwrap()
+    }
+
+    #[tokio::test]
+    async fn runs_in_the_order_applied() {
+        let log = Log::default();
+        let mut plugin_manager = manager(&log);
+        for name in ["first", "second", "third"] {
+            plugin_manager.apply_middleware(Box::new(Trace { name, log: Arc::clone(&log) }));
+        }
+
+        let response = plugin_manager.dispatch(request("GET /whoami HTTP/1.1\r\n\r\n")).await.unwrap();
+        assert_eq!(response.status_code, 200);
+        assert_eq!(
+            *log.lock().unwrap(),
+            ["first in", "second in", "third in", "plugin", "third out", "second out", "first out"]
+        );
+    }
+
+    #[tokio::test]
+    async fn short_circuits_without_calling_the_plugin() {
+        let log = Log::default();
+        let mut plugin_manager = manager(&log);
+        plugin_manager.apply_middleware(Box::new(RequireToken));
+        plugin_manager.apply_middleware(Box::new(Trace { name: "inner", log: Arc::clone(&log) }));
+
+        let response = plugin_manager.dispatch(request("GET /whoami HTTP/1.1\r\n\r\n")).await.unwrap();
+        assert_eq!(response.status_code, 401);
+        assert!(log.lock().unwrap().is_empty());
+
+        let response = plugin_manager.dispatch(request("GET /whoami HTTP/1.1\r\nX-Token: t\r\n\r\n")).await.unwrap();
+        assert_eq!(response.status_code, 200);
+        assert_eq!(*log.lock().unwrap(), ["inner in", "plugin", "inner out"]);
+    }
+
+    #[tokio::test]
+    async fn modifies_the_request_and_the_response() {
+        let log = Log::default();
+        let mut plugin_manager = manager(&log);
+        plugin_manager.apply_middleware(Box::new(Stamp));
+
+        let response = plugin_manager.dispatch(request("GET /whoami HTTP/1.1\r\n\r\n")).await.unwrap();
+        assert_eq!(response.body, b"ada");
+        assert_eq!(response.get_header("x-stamped"), Some("1"));
+    }
+
+    #[tokio::test]
+    async fn propagates_errors_through_the_chain() {
+        let log = Log::default();
+        let mut plugin_manager = manager(&log);
+        plugin_manager.apply_middleware(Box::new(Trace { name: "outer", log: Arc::clone(&log) }));
+        plugin_manager.apply_middleware(Box::new(Stamp));
+        plugin_manager.apply_middleware(Box::new(Fail));
+
+        let err = plugin_manager.dispatch(request("GET /whoami HTTP/1.1\r\n\r\n")).await.unwrap_err();
+        assert!(matches!(err, PluginError::Internal(ref msg) if msg == "middleware failed"));
+        assert_eq!(*log.lock().unwrap(), ["outer in", "outer out"]);
+
+        // Errors from the plugin reach the middleware the same way
+        let mut plugin_manager = PluginManager::new();
+        plugin_manager.route("GET", "/fail", |_| Err(PluginError::Internal("plugin failed".to_string()))).unwrap();
+        plugin_manager.apply_middleware(Box::new(Stamp));
+        let err = plugin_manager.dispatch(request("GET /fail HTTP/1.1\r\n\r\n")).await.unwrap_err();
+        assert_eq!(err.status_code(), StatusCode::InternalServerError);
+    }
+}
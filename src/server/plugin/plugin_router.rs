@@ -0,0 +1,384 @@
+// src/server/plugin/plugin_router.rs
+
+use std::collections::HashMap;
+use std::io::{Error, ErrorKind, Result};
+
+use super::plugin_manager::PluginHandle;
+
+/// A method and path pattern a plugin wants to handle.
+///
+/// Patterns are split on `/`. A segment starting with `:` captures one path segment as a
+/// named parameter, and a final segment starting with `*` captures the rest of the path.
+///
+/// ```ignore
+/// Route::new("GET", "/api/items/:id");
+/// Route::new("GET", "/assets/*rest");
+/// ```
+#[derive(Clone, Debug)]
+pub struct Route {
+    pub method: String,
+    pub pattern: String,
+}
+
+impl Route {
+    /// Creates a route for the given method and pattern.
+    pub fn new(method: &str, pattern: &str) -> Self {
+        Self {
+            method: method.to_uppercase(),
+            pattern: pattern.to_string(),
+        }
+    }
+}
+
+/// One segment of a parsed route pattern.
+#[derive(Clone, Debug, PartialEq)]
+enum Segment {
+    Static(String),
+    Param(String),
+    Wildcard(String),
+}
+
+impl Segment {
+    /// Orders segments by specificity, with static segments winning over parameters and wildcards.
+    fn rank(&self) -> u8 {
+        match self {
+            Segment::Static(_) => 0,
+            Segment::Param(_) => 1,
+            Segment::Wildcard(_) => 2,
+        }
+    }
+}
+
+/// A parsed route pattern.
+#[derive(Clone, Debug)]
+struct RoutePattern {
+    segments: Vec<Segment>,
+}
+
+impl RoutePattern {
+    fn parse(pattern: &str) -> Result<Self> {
+        if !pattern.starts_with('/') {
+            return Err(invalid_pattern(pattern, "must start with '/'"));
+        }
+
+        let parts: Vec<&str> = split_path(pattern).collect();
+        let mut segments = Vec::with_capacity(parts.len());
+
+        for (index, part) in parts.iter().enumerate() {
+            let segment = if let Some(name) = part.strip_prefix(':') {
+                Segment::Param(name.to_string())
+            } else if let Some(name) = part.strip_prefix('*') {
+                if index != parts.len() - 1 {
+                    return Err(invalid_pattern(pattern, "wildcard must be the last segment"));
+                }
+                Segment::Wildcard(name.to_string())
+            } else {
+                Segment::Static(part.to_string())
+            };
+
+            if let Segment::Param(name) | Segment::Wildcard(name) = &segment {
+                if name.is_empty() {
+                    return Err(invalid_pattern(pattern, "parameter name is empty"));
+                }
+            }
+            segments.push(segment);
+        }
+
+        Ok(Self { segments })
+    }
+
+    /// Matches the path against the pattern, returning the captured parameters.
+    fn matches(&self, path: &str) -> Option<HashMap<String, String>> {
+        let parts: Vec<&str> = split_path(path).collect();
+        let mut params = HashMap::new();
+
+        for (index, segment) in self.segments.iter().enumerate() {
+            match segment {
+                Segment::Static(value) => {
+                    if parts.get(index) != Some(&value.as_str()) {
+                        return None;
+                    }
+                }
+                Segment::Param(name) => {
+                    let part = parts.get(index)?;
+                    params.insert(name.clone(), part.to_string());
+                }
+                Segment::Wildcard(name) => {
+                    params.insert(name.clone(), parts[index.min(parts.len())..].join("/"));
+                    return Some(params);
+                }
+            }
+        }
+
+        if parts.len() == self.segments.len() {
+            Some(params)
+        } else {
+            None
+        }
+    }
+
+    /// Returns `true` if both patterns match exactly the same set of paths.
+    fn conflicts_with(&self, other: &RoutePattern) -> bool {
+        self.segments.len() == other.segments.len()
+            && self.segments.iter().zip(&other.segments).all(|(a, b)| match (a, b) {
+                (Segment::Static(a), Segment::Static(b)) => a == b,
+                (a, b) => a.rank() == b.rank(),
+            })
+    }
+
+    fn specificity(&self) -> Vec<u8> {
+        self.segments.iter().map(Segment::rank).collect()
+    }
+}
+
+/// Outcome of looking up a request in the route table.
+pub enum RouteMatch {
+    /// A route matched; the handler and the captured path parameters.
+    Found(PluginHandle, HashMap<String, String>),
+    /// The path matched one or more routes, but none for this method.
+    MethodNotAllowed(Vec<String>),
+    /// No route matched the path.
+    NotFound,
+}
+
+struct RouteEntry {
+    route: Route,
+    pattern: RoutePattern,
+    handler: PluginHandle,
+}
+
+/// Maps method and path patterns to plugins.
+pub struct Router {
+    entries: Vec<RouteEntry>,
+}
+
+impl Default for Router {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Router {
+    /// Creates an empty route table.
+    pub fn new() -> Self {
+        Self {
+            entries: Vec::new(),
+        }
+    }
+
+    /// Registers a route, failing if the pattern is invalid or an equivalent route is already registered.
+    pub fn insert(&mut self, route: Route, handler: PluginHandle) -> Result<()> {
+        let pattern = RoutePattern::parse(&route.pattern)?;
+
+        if let Some(existing) = self
+            .entries
+            .iter()
+            .find(|entry| entry.route.method == route.method && entry.pattern.conflicts_with(&pattern))
+        {
+            return Err(Error::new(
+                ErrorKind::AlreadyExists,
+                format!(
+                    "Route {} {} conflicts with {} {}",
+                    route.method, route.pattern, existing.route.method, existing.route.pattern
+                ),
+            ));
+        }
+
+        self.entries.push(RouteEntry { route, pattern, handler });
+        Ok(())
+    }
+
+    /// Returns the number of registered routes.
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Removes every route registered after the first `len` routes.
+    pub fn truncate(&mut self, len: usize) {
+        self.entries.truncate(len);
+    }
+
+    /// Finds the most specific route for the method and path.
+    ///
+    /// `HEAD` requests fall back to the `GET` route when no `HEAD` route is registered.
+    pub fn find(&self, method: &str, path: &str) -> RouteMatch {
+        let route_match = self.find_exact(method, path);
+        if method == "HEAD" {
+            if let RouteMatch::MethodNotAllowed(_) = route_match {
+                if let found @ RouteMatch::Found(..) = self.find_exact("GET", path) {
+                    return found;
+                }
+            }
+        }
+        route_match
+    }
+
+    fn find_exact(&self, method: &str, path: &str) -> RouteMatch {
+        let mut best: Option<(&RouteEntry, HashMap<String, String>)> = None;
+        let mut allowed: Vec<String> = Vec::new();
+
+        for entry in &self.entries {
+            let params = match entry.pattern.matches(path) {
+                Some(params) => params,
+                None => continue,
+            };
+
+            if entry.route.method != method {
+                if !allowed.contains(&entry.route.method) {
+                    allowed.push(entry.route.method.clone());
+                }
+                continue;
+            }
+
+            let is_better = best
+                .as_ref()
+                .is_none_or(|(current, _)| entry.pattern.specificity() < current.pattern.specificity());
+            if is_better {
+                best = Some((entry, params));
+            }
+        }
+
+        match best {
+            Some((entry, params)) => RouteMatch::Found(entry.handler.clone(), params),
+            None if !allowed.is_empty() => RouteMatch::MethodNotAllowed(allowed),
+            None => RouteMatch::NotFound,
+        }
+    }
+}
+
+fn split_path(path: &str) -> impl Iterator<Item = &str> {
+    path.split('/').filter(|part| !part.is_empty())
+}
+
+fn invalid_pattern(pattern: &str, reason: &str) -> Error {
+    Error::new(ErrorKind::InvalidInput, format!("Invalid route pattern '{}': {}", pattern, reason))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use super::*;
+    use crate::server::plugin::plugin_base::{Plugin, PluginError};
+    use crate::server::structs::structs_mime::Mime;
+    use crate::server::structs::structs_request::Request;
+    use crate::server::structs::structs_response::Response;
+
+    /// Answers with its own name, so tests can tell which route matched.
+    struct Named(&'static str);
+
+    impl Plugin for Named {
+        fn init(&mut self) -> Result<()> {
+            Ok(())
+        }
+
+        fn serve(&self, _request: &Request) -> std::result::Result<Response, PluginError> {
+            Ok(Response::response_ok(self.0.as_bytes().to_vec(), Mime::TextPlain))
+        }
+    }
+
+    fn router(routes: &[(&str, &str, &'static str)]) -> Router {
+        let mut router = Router::new();
+        for (method, pattern, name) in routes {
+            router
+                .insert(Route::new(method, pattern), PluginHandle::Blocking(Arc::new(Named(name))))
+                .unwrap();
+        }
+        router
+    }
+
+    /// Returns the name of the matched plugin and the captured parameters.
+    fn found(router: &Router, method: &str, path: &str) -> Option<(String, HashMap<String, String>)> {
+        match router.find(method, path) {
+            RouteMatch::Found(PluginHandle::Blocking(plugin), params) => {
+                let request = Request::from_head(b"GET / HTTP/1.1\r\n").unwrap();
+                let body = plugin.serve(&request).unwrap().body;
+                Some((String::from_utf8(body).unwrap(), params))
+            }
+            _ => None,
+        }
+    }
+
+    #[test]
+    fn captures_params_and_wildcards() {
+        let router = router(&[("GET", "/items/:id", "item"), ("GET", "/assets/*rest", "asset")]);
+
+        let (name, params) = found(&router, "GET", "/items/42").unwrap();
+        assert_eq!(name, "item");
+        assert_eq!(params["id"], "42");
+
+        let (name, params) = found(&router, "GET", "/assets/css/site.css").unwrap();
+        assert_eq!(name, "asset");
+        assert_eq!(params["rest"], "css/site.css");
+
+        let (_, params) = found(&router, "GET", "/assets").unwrap();
+        assert_eq!(params["rest"], "");
+
+        assert!(found(&router, "GET", "/items").is_none());
+        assert!(found(&router, "GET", "/items/42/extra").is_none());
+    }
+
+    #[test]
+    fn ignores_empty_segments() {
+        let router = router(&[("GET", "/a/b", "ab")]);
+        assert!(found(&router, "GET", "/a/b/").is_some());
+        assert!(found(&router, "GET", "//a//b").is_some());
+    }
+
+    #[test]
+    fn prefers_the_most_specific_route() {
+        let router = router(&[
+            ("GET", "/*path", "wildcard"),
+            ("GET", "/items/:id", "param"),
+            ("GET", "/items/new", "static"),
+        ]);
+        assert_eq!(found(&router, "GET", "/items/new").unwrap().0, "static");
+        assert_eq!(found(&router, "GET", "/items/7").unwrap().0, "param");
+        assert_eq!(found(&router, "GET", "/other").unwrap().0, "wildcard");
+    }
+
+    #[test]
+    fn reports_allowed_methods() {
+        let router = router(&[("GET", "/items", "get"), ("POST", "/items", "post")]);
+        match router.find("DELETE", "/items") {
+            RouteMatch::MethodNotAllowed(allowed) => assert_eq!(allowed, vec!["GET", "POST"]),
+            _ => panic!("expected 405"),
+        }
+        assert!(matches!(router.find("GET", "/missing"), RouteMatch::NotFound));
+    }
+
+    #[test]
+    fn head_falls_back_to_get() {
+        let router = router(&[("GET", "/page", "get")]);
+        assert_eq!(found(&router, "HEAD", "/page").unwrap().0, "get");
+    }
+
+    #[test]
+    fn rejects_conflicting_routes() {
+        let mut router = router(&[("GET", "/items/:id", "item")]);
+        let handler = || PluginHandle::Blocking(Arc::new(Named("other")));
+
+        let err = router.insert(Route::new("GET", "/items/:name"), handler()).unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::AlreadyExists);
+        assert!(router.insert(Route::new("get", "/items/:other"), handler()).is_err());
+
+        assert!(router.insert(Route::new("POST", "/items/:id"), handler()).is_ok());
+        assert!(router.insert(Route::new("GET", "/items/:id/edit"), handler()).is_ok());
+        assert!(router.insert(Route::new("GET", "/items/*rest"), handler()).is_ok());
+        assert_eq!(router.len(), 4);
+    }
+
+    #[test]
+    fn rejects_invalid_patterns() {
+        let mut router = Router::new();
+        let handler = || PluginHandle::Blocking(Arc::new(Named("invalid")));
+        for pattern in ["items", "/*rest/more", "/items/:", "/files/*"] {
+            let err = router.insert(Route::new("GET", pattern), handler()).unwrap_err();
+            assert_eq!(err.kind(), ErrorKind::InvalidInput, "{}", pattern);
+        }
+    }
+}
@@ -0,0 +1,265 @@
+// src/server/server_chunked.rs
+
+use std::fmt;
+
+use crate::server::structs::structs_request::RequestError;
+
+/// The terminating zero-length chunk with an empty trailer section.
+pub const LAST_CHUNK: &[u8] = b"0\r\n\r\n";
+
+/// A chunked body that cannot be accepted.
+#[derive(Debug)]
+pub enum ChunkedError {
+    /// The framing is malformed.
+    Invalid(String),
+    /// A chunk size line or the trailer section exceeds the given number of bytes.
+    LineTooLong(usize),
+    /// The chunks announce a body of this many bytes, more than allowed.
+    BodyTooLarge(usize),
+}
+
+impl fmt::Display for ChunkedError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ChunkedError::Invalid(msg) => write!(f, "Invalid chunked body: {}", msg),
+            ChunkedError::LineTooLong(limit) => write!(f, "Chunk size line or trailers exceed {} bytes", limit),
+            ChunkedError::BodyTooLarge(length) => write!(f, "Chunked body of {} bytes exceeds the limit", length),
+        }
+    }
+}
+
+impl std::error::Error for ChunkedError {}
+
+impl From<ChunkedError> for RequestError {
+    fn from(err: ChunkedError) -> Self {
+        RequestError::InvalidRequest(err.to_string())
+    }
+}
+
+/// Where the decoder is within the chunked framing.
+enum ChunkState {
+    /// Reading a chunk size line, with its extensions.
+    Size,
+    /// Reading chunk data, with this many bytes left in the chunk.
+    Data(usize),
+    /// Expecting the CRLF that ends a chunk's data.
+    DataEnd,
+    /// Reading trailer fields up to the closing blank line.
+    Trailers,
+    Done,
+}
+
+/// Decodes a `Transfer-Encoding: chunked` body incrementally, as its bytes arrive.
+///
+/// Each call to `decode` picks up where the previous one stopped, so the caller can drop
+/// the consumed bytes and every byte is looked at a bounded number of times.
+pub struct ChunkedDecoder {
+    state: ChunkState,
+    body: Vec<u8>,
+    max_line: usize,
+    max_body: usize,
+    /// Bytes of the current partial line already searched for its CRLF.
+    scanned: usize,
+    /// Bytes of trailer section read so far.
+    trailer_len: usize,
+}
+
+impl ChunkedDecoder {
+    /// Creates a decoder limiting each chunk size line, and the trailer section as a whole,
+    /// to `max_line` bytes and the decoded body to `max_body` bytes.
+    pub fn new(max_line: usize, max_body: usize) -> Self {
+        Self {
+            state: ChunkState::Size,
+            body: Vec::new(),
+            max_line,
+            max_body,
+            scanned: 0,
+            trailer_len: 0,
+        }
+    }
+
+    /// Decodes as much of `data` as possible.
+    ///
+    /// `data` must start with the first byte not consumed by the previous call.
+    ///
+    /// # Returns
+    /// * `Result<usize, ChunkedError>` - The number of bytes consumed from the front of `data`.
+    pub fn decode(&mut self, data: &[u8]) -> Result<usize, ChunkedError> {
+        let mut pos = 0;
+
+        loop {
+            match self.state {
+                ChunkState::Size => {
+                    let Some(line) = self.next_line(&data[pos..], 0)? else { break };
+                    let size = parse_chunk_size(&data[pos..pos + line])?;
+                    pos += line + 2;
+                    if size == 0 {
+                        self.state = ChunkState::Trailers;
+                    } else {
+                        if self.body.len().saturating_add(size) > self.max_body {
+                            return Err(ChunkedError::BodyTooLarge(self.body.len().saturating_add(size)));
+                        }
+                        self.state = ChunkState::Data(size);
+                    }
+                }
+                ChunkState::Data(remaining) => {
+                    let take = remaining.min(data.len() - pos);
+                    if take == 0 {
+                        break;
+                    }
+                    self.body.extend_from_slice(&data[pos..pos + take]);
+                    pos += take;
+                    self.state = if take == remaining { ChunkState::DataEnd } else { ChunkState::Data(remaining - take) };
+                }
+                ChunkState::DataEnd => {
+                    if data.len() - pos < 2 {
+                        break;
+                    }
+                    if &data[pos..pos + 2] != b"\r\n" {
+                        return Err(ChunkedError::Invalid("Chunk data not followed by CRLF".to_string()));
+                    }
+                    pos += 2;
+                    self.state = ChunkState::Size;
+                }
+                ChunkState::Trailers => {
+                    let Some(line) = self.next_line(&data[pos..], self.trailer_len)? else { break };
+                    pos += line + 2;
+                    self.trailer_len += line + 2;
+                    if line == 0 {
+                        self.state = ChunkState::Done;
+                    }
+                }
+                ChunkState::Done => break,
+            }
+        }
+
+        Ok(pos)
+    }
+
+    /// Returns `true` once the terminating chunk and trailers have been decoded.
+    pub fn is_complete(&self) -> bool {
+        matches!(self.state, ChunkState::Done)
+    }
+
+    /// Returns the decoded body.
+    pub fn into_body(self) -> Vec<u8> {
+        self.body
+    }
+
+    /// Finds the length of the line at the start of `data`, excluding its CRLF.
+    ///
+    /// Returns `Ok(None)` if the line is not complete yet, remembering how far it was
+    /// searched. `used` counts bytes already spent against `max_line`.
+    fn next_line(&mut self, data: &[u8], used: usize) -> Result<Option<usize>, ChunkedError> {
+        // Back up one byte in case the CR arrived at the end of the previous search
+        let from = self.scanned.saturating_sub(1).min(data.len());
+        let found = find_crlf(&data[from..]).map(|end| from + end);
+        let length = found.unwrap_or(data.len());
+        if used.saturating_add(length) > self.max_line {
+            return Err(ChunkedError::LineTooLong(self.max_line));
+        }
+        self.scanned = if found.is_some() { 0 } else { data.len() };
+        Ok(found)
+    }
+}
+
+/// Encodes `data` as a single chunk of a `Transfer-Encoding: chunked` body.
+pub fn encode_chunk(data: &[u8]) -> Vec<u8> {
+    let mut chunk = format!("{:x}\r\n", data.len()).into_bytes();
+    chunk.extend_from_slice(data);
+    chunk.extend_from_slice(b"\r\n");
+    chunk
+}
+
+fn find_crlf(data: &[u8]) -> Option<usize> {
+    data.windows(2).position(|window| window == b"\r\n")
+}
+
+fn parse_chunk_size(line: &[u8]) -> Result<usize, ChunkedError> {
+    let line = String::from_utf8_lossy(line);
+    let size = line.split(';').next().unwrap_or("").trim();
+    usize::from_str_radix(size, 16).map_err(|_| ChunkedError::Invalid(format!("Invalid chunk size: {}", size)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Feeds `data` to a decoder in pieces of `step` bytes, dropping consumed bytes as a reader does.
+    fn feed(decoder: &mut ChunkedDecoder, data: &[u8], step: usize) -> Result<Vec<u8>, ChunkedError> {
+        let mut buf = Vec::new();
+        for piece in data.chunks(step) {
+            buf.extend_from_slice(piece);
+            let consumed = decoder.decode(&buf)?;
+            buf.drain(..consumed);
+        }
+        Ok(buf)
+    }
+
+    #[test]
+    fn decodes_incrementally() {
+        let data = b"5;ext=1\r\nhello\r\n7\r\n, world\r\n0\r\nX-Trailer: 1\r\n\r\nNEXT";
+        for step in [1, 2, 3, 7, data.len()] {
+            let mut decoder = ChunkedDecoder::new(64, 64);
+            let rest = feed(&mut decoder, data, step).unwrap();
+            assert!(decoder.is_complete(), "step {}", step);
+            assert_eq!(rest, b"NEXT");
+            assert_eq!(decoder.into_body(), b"hello, world");
+        }
+    }
+
+    #[test]
+    fn skips_extensions_and_trailers() {
+        let mut decoder = ChunkedDecoder::new(64, 64);
+        let data = b"A;name=value\r\n0123456789\r\n0\r\nX-Trailer: 1\r\n\r\n";
+        assert_eq!(decoder.decode(data).unwrap(), data.len());
+        assert!(decoder.is_complete());
+        assert_eq!(decoder.into_body(), b"0123456789");
+    }
+
+    #[test]
+    fn round_trips_encode_chunk() {
+        let mut data = encode_chunk(b"abc");
+        data.extend_from_slice(&encode_chunk(&[b'x'; 300]));
+        data.extend_from_slice(LAST_CHUNK);
+        let mut decoder = ChunkedDecoder::new(64, 1024);
+        assert_eq!(decoder.decode(&data).unwrap(), data.len());
+        assert!(decoder.is_complete());
+        assert_eq!(decoder.into_body().len(), 303);
+    }
+
+    #[test]
+    fn rejects_malformed_chunks() {
+        for data in [&b"zz\r\nhello\r\n"[..], b"\r\n", b"3\r\nabcd\r\n"] {
+            assert!(matches!(ChunkedDecoder::new(64, 64).decode(data), Err(ChunkedError::Invalid(_))));
+        }
+    }
+
+    #[test]
+    fn limits_size_lines() {
+        let mut decoder = ChunkedDecoder::new(16, 1024);
+        let err = feed(&mut decoder, &[b'0'; 17], 4).unwrap_err();
+        assert!(matches!(err, ChunkedError::LineTooLong(16)));
+
+        let mut decoder = ChunkedDecoder::new(16, 1024);
+        let line = format!("1;{}\r\n", "x".repeat(20));
+        assert!(matches!(feed(&mut decoder, line.as_bytes(), 1), Err(ChunkedError::LineTooLong(16))));
+    }
+
+    #[test]
+    fn limits_trailer_section() {
+        let mut decoder = ChunkedDecoder::new(32, 1024);
+        let mut data = b"0\r\n".to_vec();
+        for _ in 0..10 {
+            data.extend_from_slice(b"X-Trailer: 1\r\n");
+        }
+        assert!(matches!(feed(&mut decoder, &data, 5), Err(ChunkedError::LineTooLong(32))));
+    }
+
+    #[test]
+    fn limits_body_before_data_arrives() {
+        let mut decoder = ChunkedDecoder::new(64, 10);
+        let err = feed(&mut decoder, b"8\r\nabcdefgh\r\n8\r\n", 64).unwrap_err();
+        assert!(matches!(err, ChunkedError::BodyTooLarge(16)));
+    }
+}
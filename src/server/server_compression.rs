@@ -0,0 +1,162 @@
+// src/server/server_compression.rs
+
+use std::io::{self, Write};
+use std::sync::Arc;
+
+use flate2::{write::GzEncoder, Compression};
+
+use crate::server::structs::structs_response::Response;
+use crate::util::logging::logln;
+
+/// Bodies smaller than this are sent as they are, as compressing them saves next to nothing.
+pub const COMPRESSION_MIN_SIZE: usize = 1024;
+
+/// Returns `true` if an `Accept-Encoding` value lets the response be gzip-compressed.
+///
+/// An explicit `gzip` entry takes precedence over `*`, and a `q` of zero refuses the coding.
+pub fn accepts_gzip(accept_encoding: &str) -> bool {
+    let mut wildcard = None;
+    for entry in accept_encoding.split(',') {
+        let mut parts = entry.split(';');
+        let coding = parts.next().unwrap_or("").trim();
+        let quality = parts
+            .filter_map(|param| param.trim().split_once('='))
+            .find(|(key, _)| key.trim().eq_ignore_ascii_case("q"))
+            .map_or(1.0, |(_, q)| q.trim().parse::<f32>().unwrap_or(0.0));
+        if coding.eq_ignore_ascii_case("gzip") || coding.eq_ignore_ascii_case("x-gzip") {
+            return quality > 0.0;
+        }
+        if coding == "*" {
+            wildcard = Some(quality > 0.0);
+        }
+    }
+    wildcard.unwrap_or(false)
+}
+
+/// Returns `true` for complete textual bodies of at least `COMPRESSION_MIN_SIZE` bytes
+/// that the plugin has not encoded itself.
+pub fn is_compressible(response: &Response) -> bool {
+    response.allows_body()
+        && !response.is_streamed()
+        && response.body.len() >= COMPRESSION_MIN_SIZE
+        && !response.header_fields.contains("content-encoding")
+        && response.header_fields.content_type().is_some_and(|mime| mime.is_text())
+}
+
+/// Compresses `data` with gzip.
+pub fn gzip(data: &[u8]) -> io::Result<Vec<u8>> {
+    let mut encoder = GzEncoder::new(Vec::new(), Compression::default());
+    encoder.write_all(data)?;
+    encoder.finish()
+}
+
+/// Gzip-compresses the body of a compressible response if the client accepts it.
+///
+/// The response gets `Vary: Accept-Encoding` either way, as its encoding depends on the request.
+/// Compression runs on tokio's blocking thread pool, since large bodies take a while.
+pub async fn compress_response(response: &mut Response, accepts_gzip: bool) {
+    if !is_compressible(response) {
+        return;
+    }
+    add_vary(response);
+    if !accepts_gzip {
+        return;
+    }
+
+    // Shared with the blocking task, so the body is kept even if the task fails
+    let body = Arc::new(std::mem::take(&mut response.body));
+    let input = Arc::clone(&body);
+    let compressed = tokio::task::spawn_blocking(move || gzip(&input))
+        .await
+        .unwrap_or_else(|e| Err(io::Error::other(e.to_string())));
+    let body = Arc::try_unwrap(body).unwrap_or_else(|body| body.to_vec());
+
+    match compressed {
+        // Incompressible bodies are sent as they are
+        Ok(compressed) if compressed.len() < body.len() => {
+            response.body = compressed;
+            response.set_header("Content-Encoding", "gzip").expect("Content-Encoding is a valid header");
+        }
+        Ok(_) => response.body = body,
+        Err(e) => {
+            logln(&format!("Failed to compress response: {}", e));
+            response.body = body;
+        }
+    }
+}
+
+fn add_vary(response: &mut Response) {
+    let vary = match response.header_fields.get_joined("vary") {
+        None => "Accept-Encoding".to_string(),
+        Some(vary) if vary.trim() == "*" || response.header_fields.has_token("vary", "accept-encoding") => return,
+        Some(vary) => format!("{}, Accept-Encoding", vary),
+    };
+    response.set_header("Vary", &vary).expect("Vary is a valid header");
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Read;
+
+    use flate2::read::GzDecoder;
+
+    use crate::server::structs::structs_mime::Mime;
+
+    fn text_response(length: usize) -> Response {
+        let mut body = "hello world ".repeat(length / 12 + 1).into_bytes();
+        body.truncate(length);
+        Response::response_ok(body, Mime::TextHtml)
+    }
+
+    #[test]
+    fn reads_accept_encoding() {
+        assert!(accepts_gzip("gzip"));
+        assert!(accepts_gzip("deflate, GZIP;q=0.5, br"));
+        assert!(accepts_gzip("*"));
+        assert!(!accepts_gzip(""));
+        assert!(!accepts_gzip("identity, br"));
+        assert!(!accepts_gzip("gzip;q=0"));
+        assert!(!accepts_gzip("gzip; q=0.0, *"));
+        assert!(!accepts_gzip("*;q=0"));
+    }
+
+    #[tokio::test]
+    async fn compresses_large_text_bodies() {
+        let mut response = text_response(4096);
+        let original = response.body.clone();
+        compress_response(&mut response, true).await;
+
+        assert_eq!(response.get_header("content-encoding"), Some("gzip"));
+        assert_eq!(response.get_header("vary"), Some("Accept-Encoding"));
+        assert!(response.body.len() < original.len());
+        let mut decoded = Vec::new();
+        GzDecoder::new(&response.body[..]).read_to_end(&mut decoded).unwrap();
+        assert_eq!(decoded, original);
+    }
+
+    #[tokio::test]
+    async fn only_varies_when_gzip_is_refused() {
+        let mut response = text_response(4096);
+        response.set_header("Vary", "Origin").unwrap();
+        compress_response(&mut response, false).await;
+        assert_eq!(response.get_header("content-encoding"), None);
+        assert_eq!(response.get_header("vary"), Some("Origin, Accept-Encoding"));
+        assert_eq!(response.body.len(), 4096);
+    }
+
+    #[tokio::test]
+    async fn leaves_other_bodies_alone() {
+        let mut small = text_response(COMPRESSION_MIN_SIZE - 1);
+        let mut binary = Response::response_ok(vec![0; 4096], Mime::ImagePng);
+        let mut encoded = text_response(4096);
+        encoded.set_header("Content-Encoding", "br").unwrap();
+        let (mut streamed, _writer) = Response::response_stream(Mime::TextPlain);
+
+        for response in [&mut small, &mut binary, &mut encoded, &mut streamed] {
+            compress_response(response, true).await;
+            assert_ne!(response.get_header("content-encoding"), Some("gzip"));
+            assert_eq!(response.get_header("vary"), None);
+        }
+    }
+}
@@ -1,20 +1,35 @@
 // src/server/server_core.rs
 
-use std::{net::SocketAddr, thread};
-use tokio::{net::TcpListener, io::{AsyncReadExt, AsyncWriteExt}, sync::oneshot};
-use crate::{server::structs::{structs_header::StatusCode, structs_request::Request, structs_response::Response}, util::logging::{logln, logln_color, Color}};
+use std::{io, net::{IpAddr, SocketAddr}, ops::RangeInclusive, thread, time::SystemTime};
+use std::time::Duration;
+use tokio::{net::{TcpListener, TcpStream}, io::AsyncWriteExt, sync::{oneshot, watch}, task::JoinSet};
+use crate::{server::structs::{structs_config::ServerConfig, structs_mime::Mime, structs_request::Request, structs_response::Response}, util::{logging::{logln, logln_color, Color}, time::http_date}};
 use std::sync::Arc;
 
 use super::plugin::plugin_manager::PluginManager;
+use super::server_compression::{accepts_gzip, compress_response};
+use super::server_lifecycle::{drain, InFlight, Lifecycle, ServerState, ShutdownReport};
+use super::server_reader::read_request;
+use super::websocket::websocket_core::run_websocket;
+
+/// Value of the `Server` header sent with every response.
+const SERVER_NAME: &str = concat!("smn_view/", env!("CARGO_PKG_VERSION"));
 
 /// Represents the server.
 pub struct Server {
-    pub ready_rx: oneshot::Receiver<()>,
+    /// Address the server is listening on, or `None` if it serves a `ProtocolHandler` instead.
+    pub local_addr: Option<SocketAddr>,
     pub handle: Option<thread::JoinHandle<()>>,
     pub _shutdown_tx: Option<oneshot::Sender<()>>,
+    pub(crate) lifecycle: Arc<Lifecycle>,
 }
 
 impl Server {
+    /// Returns the `http://` URL of the server root, if it is listening on a socket.
+    pub fn url(&self) -> Option<String> {
+        self.local_addr.map(|addr| format!("http://{}/", addr))
+    }
+
     /// Sends a shutdown signal to the server.
     pub fn shutdown(&mut self) {
         if let Some(tx) = self._shutdown_tx.take() {
@@ -25,7 +40,11 @@ impl Server {
     }
 
     /// Waits for the server to shut down.
-    pub fn await_shutdown(&mut self) {
+    ///
+    /// # Returns
+    /// * `Option<ShutdownReport>` - How many in-flight requests were drained or dropped and
+    ///   how many streams were ended, or `None` if the server never got to shut down gracefully.
+    pub fn await_shutdown(&mut self) -> Option<ShutdownReport> {
         if let Some(handle) = self.handle.take() {
             if let Err(err) = handle.join() {
                 logln(&format!("Server thread panicked: {:?}", err));
@@ -35,34 +54,117 @@ impl Server {
         } else {
             logln("Server already shut down or join called multiple times.");
         }
+        self.lifecycle.report()
     }
 
-    /// Waits until the server is ready to accept connections.
-    pub fn await_ready(&mut self) {
-        if let Ok(_) = self.ready_rx.try_recv() {
-            // Server is ready
-        }
+    /// Returns the current stage of the server's lifecycle.
+    pub fn state(&self) -> ServerState {
+        self.lifecycle.state()
+    }
+
+    /// Blocks until the server is ready to accept connections.
+    ///
+    /// # Returns
+    /// * `io::Result<()>` - `Ok` once the server is running, or why it never got there.
+    pub fn await_ready(&self) -> io::Result<()> {
+        self.lifecycle.wait_ready_blocking(None)
+    }
+
+    /// Blocks until the server is ready to accept connections, for at most `timeout`.
+    ///
+    /// # Returns
+    /// * `io::Result<()>` - `Ok` once the server is running, an `ErrorKind::TimedOut`
+    ///   error if it is still starting after `timeout`, or why it failed to start.
+    pub fn await_ready_timeout(&self, timeout: Duration) -> io::Result<()> {
+        self.lifecycle.wait_ready_blocking(Some(timeout))
+    }
+
+    /// Async counterpart of `await_ready`.
+    pub async fn ready(&self) -> io::Result<()> {
+        self.lifecycle.wait_ready().await
     }
 }
 
 /// Starts the server on the specified address with the provided `PluginManager`.
-pub fn start_server(addr: SocketAddr, plugin_manager: PluginManager) -> Server {
-    let (ready_tx, ready_rx) = oneshot::channel();
+///
+/// Use port 0 to let the system pick a free port, then read it from `Server::local_addr`.
+pub fn start_server(addr: SocketAddr, plugin_manager: PluginManager) -> io::Result<Server> {
+    start_server_with_config(addr, plugin_manager, ServerConfig::default())
+}
+
+/// Starts the server on the specified address with the provided `PluginManager` and `ServerConfig`.
+///
+/// # Returns
+/// * `io::Result<Server>` - The running server, or the error from binding the address.
+pub fn start_server_with_config(addr: SocketAddr, plugin_manager: PluginManager, config: ServerConfig) -> io::Result<Server> {
+    let listener = std::net::TcpListener::bind(addr)?;
+    spawn_server(listener, plugin_manager, config)
+}
+
+/// Starts the server on the first free port of `ports`, with the provided `PluginManager`.
+pub fn start_server_in_range(ip: IpAddr, ports: RangeInclusive<u16>, plugin_manager: PluginManager) -> io::Result<Server> {
+    start_server_in_range_with_config(ip, ports, plugin_manager, ServerConfig::default())
+}
+
+/// Starts the server on the first free port of `ports`, with the provided `PluginManager` and `ServerConfig`.
+///
+/// # Returns
+/// * `io::Result<Server>` - The running server, or the error from the last port tried.
+pub fn start_server_in_range_with_config(ip: IpAddr, ports: RangeInclusive<u16>, plugin_manager: PluginManager, config: ServerConfig) -> io::Result<Server> {
+    let mut last_error = io::Error::new(io::ErrorKind::InvalidInput, "Empty port range");
+    for port in ports {
+        match std::net::TcpListener::bind(SocketAddr::new(ip, port)) {
+            Ok(listener) => return spawn_server(listener, plugin_manager, config),
+            Err(e) => last_error = e,
+        }
+    }
+    Err(last_error)
+}
+
+/// Runs the server on its own thread and runtime, accepting connections from `listener`.
+fn spawn_server(listener: std::net::TcpListener, plugin_manager: PluginManager, config: ServerConfig) -> io::Result<Server> {
+    let addr = listener.local_addr()?;
+    // Tokio requires the listener to be non-blocking before taking it over
+    listener.set_nonblocking(true)?;
+
     let (shutdown_tx, mut shutdown_rx) = oneshot::channel::<()>();
+    let lifecycle = Arc::new(Lifecycle::new(&config));
+    let server_lifecycle = Arc::clone(&lifecycle);
 
     // Move `PluginManager` into the server thread
     let server_handle = thread::spawn(move || {
-        let rt = tokio::runtime::Runtime::new().expect("Failed to create Tokio runtime");
+        let lifecycle = server_lifecycle;
+        let rt = match tokio::runtime::Runtime::new() {
+            Ok(rt) => rt,
+            Err(e) => {
+                logln(&format!("Failed to create Tokio runtime: {}", e));
+                lifecycle.set(ServerState::Failed(e.to_string()));
+                return;
+            }
+        };
         rt.block_on(async move {
-            let listener = TcpListener::bind(addr).await.expect("Failed to bind");
+            let listener = match TcpListener::from_std(listener) {
+                Ok(listener) => listener,
+                Err(e) => {
+                    logln(&format!("Failed to start listener: {}", e));
+                    lifecycle.set(ServerState::Failed(e.to_string()));
+                    return;
+                }
+            };
 
             logln_color("[Started: Server]", Color::Green);
             // Print the server address and http:// URL
             logln(&format!("{} {}", Color::BrightBlack.paint("Server listening on:"), Color::Blue.paint(&format!("http://{}", addr))));
-            let _ = ready_tx.send(());
 
-            // Wrap `PluginManager` in an `Arc` for shared ownership across tasks
+            // Wrap `PluginManager` and `ServerConfig` in an `Arc` for shared ownership across tasks
             let plugin_manager = Arc::new(plugin_manager);
+            let config = Arc::new(config);
+
+            // Lets open connections notice the shutdown, so streamed responses can end cleanly
+            let (stopping_tx, stopping_rx) = watch::channel(false);
+            let mut connections = JoinSet::new();
+            let in_flight = InFlight::default();
+            lifecycle.set(ServerState::Running);
 
             loop {
                 tokio::select! {
@@ -72,63 +174,526 @@ pub fn start_server(addr: SocketAddr, plugin_manager: PluginManager) -> Server {
                     }
                     accept_result = listener.accept() => {
                         match accept_result {
-                            Ok((mut stream, _peer)) => {
-                                // Clone the `PluginManager` Arc for the task
+                            Ok((stream, _peer)) => {
+                                // Clone the shared state for the task
                                 let plugin_manager = Arc::clone(&plugin_manager);
-                                tokio::spawn(async move {
-                                    // Read the request
-                                    let mut buf = [0u8; 1024];
-                                    let n = stream.read(&mut buf).await.unwrap_or(0);
-                                    if n == 0 {
-                                        // Connection closed
-                                        return;
-                                    }
-                                    match Request::from_bytes(&buf[..n]) {
-                                        Ok(request) => {
-                                            // Find a plugin to handle the request
-                                            if let Some(plugin) = plugin_manager.find_plugin(&request) {
-                                                // Convert `tokio::net::TcpStream` to `std::net::TcpStream`
-                                                let std_stream = match stream.into_std() {
-                                                    Ok(s) => s,
-                                                    Err(e) => {
-                                                        logln(&format!("Failed to convert stream: {}", e));
-                                                        return;
-                                                    }
-                                                };
-                                                let mut std_stream = std_stream;
-                                                if let Err(e) = plugin.serve(&mut std_stream, &request) {
-                                                    logln(&format!("Plugin serve error: {}", e));
-                                                }
-                                            } else {
-                                                // No plugin found, respond with No Content
-                                                let response = Response::response_error("No Content".to_owned(), StatusCode::NotFound).to_bytes();
-                                                let _ = stream.write_all(&response).await;
-                                                let _ = stream.flush().await;
-                                            }
-                                        }
-                                        Err(e) => {
-                                            logln(&format!("Failed to parse request: {}", e));
-                                            // Invalid request, respond with Bad Request
-                                            let response = Response::response_error("Bad Request".to_owned(), StatusCode::BadRequest).to_bytes();
-                                            let _ = stream.write_all(&response).await;
-                                            let _ = stream.flush().await;
-                                        }
-                                    }
-                                });
+                                let config = Arc::clone(&config);
+                                connections.spawn(handle_connection(stream, plugin_manager, config, stopping_rx.clone(), in_flight.clone()));
                             }
                             Err(e) => {
                                 logln(&format!("Failed to accept connection: {}", e));
                             }
                         }
                     }
+                    Some(_) = connections.join_next(), if !connections.is_empty() => {
+                        // Reap finished connections
+                    }
                 }
             }
+
+            // Stop accepting, then let open connections finish their current request
+            drop(listener);
+            lifecycle.set(ServerState::Stopping);
+            let report = drain(&mut connections, &in_flight, &stopping_tx, config.shutdown_timeout).await;
+            lifecycle.finish(report);
         });
+
+        // Plugins still blocking after the deadline are left behind rather than waited for
+        rt.shutdown_background();
     });
 
-    Server {
-        ready_rx,
+    Ok(Server {
+        local_addr: Some(addr),
         handle: Some(server_handle),
         _shutdown_tx: Some(shutdown_tx),
+        lifecycle,
+    })
+}
+
+/// Serves requests on the connection until the client closes it, asks for it to be
+/// closed, it stays idle for longer than `ServerConfig::idle_timeout`, or the server stops.
+/// A request that stalls for longer than `ServerConfig::read_timeout` is answered with 408.
+///
+/// Pipelined requests are answered in the order they were received. Once the server stops,
+/// the request being handled is finished, event streams are ended and WebSockets are closed.
+async fn handle_connection(mut stream: TcpStream, plugin_manager: Arc<PluginManager>, config: Arc<ServerConfig>, mut stopping: watch::Receiver<bool>, in_flight: InFlight) {
+    let mut buf = Vec::new();
+
+    loop {
+        let read = tokio::select! {
+            read = read_request(&mut stream, &mut buf, &config) => read,
+            _ = stopping.wait_for(|&stopping| stopping) => return,
+        };
+        let request = match read {
+            Ok(Some(request)) => request,
+            Ok(None) => {
+                // Connection closed or idle for too long
+                return;
+            }
+            Err(e) => {
+                logln(&format!("Failed to read request: {}", e));
+                let Some(status) = e.status_code() else { return };
+                let mut response = Response::response_error(status.to_msg().to_owned(), status);
+                apply_default_headers(&mut response, false);
+                let _ = response.write_to_async(&mut stream).await;
+                return;
+            }
+        };
+
+        // Counted until the response is sent, or the stream it opened is closed
+        let request_guard = in_flight.start();
+        let keep_alive = request.keep_alive();
+        let method = request.method.clone();
+        let path = request.path.clone();
+
+        let http10 = request.version == "HTTP/1.0";
+        let accepts_gzip = request.header_fields.get_joined("accept-encoding").is_some_and(|value| accepts_gzip(&value));
+
+        let mut response = dispatch_request(&plugin_manager, request).await;
+
+        // HTTP/1.0 has no chunked coding, so a streamed body is sent as is and ends with the connection
+        if http10 && response.is_streamed() && response.allows_body() {
+            response.close_delimited = true;
+        }
+
+        if config.compression {
+            compress_response(&mut response, accepts_gzip).await;
+        }
+
+        // A shutdown that began while the request was handled closes the connection after it
+        let keep_alive = keep_alive && !response.close_delimited && !*stopping.borrow();
+        apply_default_headers(&mut response, keep_alive);
+        log_response(&method, &path, response.status_code);
+
+        // Event streams and WebSockets stay open until the server stops, so shutdown ends them instead of draining them
+        let is_event_stream = response.header_fields.content_type().is_some_and(|mime| *mime.essence() == Mime::EventStream);
+        let _request_guard = if response.status_code == 101 || is_event_stream {
+            in_flight.mark_stream(request_guard)
+        } else {
+            request_guard
+        };
+
+        // A WebSocket handshake hands the connection over once the response is sent
+        let upgrade = match response.status_code {
+            101 => response.upgrade.take(),
+            _ => None,
+        };
+
+        // Responses to HEAD carry the headers of the equivalent GET, but no body
+        let written = if method == "HEAD" {
+            stream.write_all(&response.head_to_bytes()).await
+        } else {
+            let mut stopping = stopping.clone();
+            let stopped = async move {
+                let _ = stopping.wait_for(|&stopping| stopping).await;
+            };
+            response.write_to_async_until(&mut stream, stopped).await
+        };
+        if written.is_err() {
+            return;
+        }
+
+        if let Some(upgrade) = upgrade {
+            let mut stopping = stopping.clone();
+            let stopped = async move {
+                let _ = stopping.wait_for(|&stopping| stopping).await;
+            };
+            run_websocket(&mut stream, std::mem::take(&mut buf), upgrade, config.max_message_size, stopped).await;
+            return;
+        }
+
+        if !keep_alive || *stopping.borrow() {
+            return;
+        }
+    }
+}
+
+/// Runs the request through the middleware chain and the matching plugin,
+/// answering plugin errors with the matching error response.
+pub(crate) async fn dispatch_request(plugin_manager: &PluginManager, request: Request) -> Response {
+    match plugin_manager.dispatch(request).await {
+        Ok(response) => response,
+        Err(e) => {
+            logln(&format!("Plugin error: {}", e));
+            let status = e.status_code();
+            Response::response_error(status.to_msg().to_owned(), status)
+        }
+    }
+}
+
+/// Adds the headers the server sets on every response, unless the plugin already set them.
+pub(crate) fn apply_default_headers(response: &mut Response, keep_alive: bool) {
+    if !response.header_fields.contains("date") {
+        response.set_header("Date", &http_date(SystemTime::now())).expect("Date is a valid header");
+    }
+    if !response.header_fields.contains("server") {
+        response.set_header("Server", SERVER_NAME).expect("Server is a valid header");
+    }
+    if !keep_alive {
+        response.set_header("Connection", "close").expect("Connection is a valid header");
+    }
+}
+
+/// Logs the request line and the status code it was answered with.
+pub(crate) fn log_response(method: &str, path: &str, status_code: u16) {
+    let color = match status_code {
+        200..=399 => Color::Green,
+        400..=499 => Color::Yellow,
+        _ => Color::Red,
+    };
+    logln(&format!("{} {}", Color::BrightBlack.paint(&format!("{} {}", method, path)), color.paint(&status_code.to_string())));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::server::structs::structs_mime::Mime;
+    use std::io::{Read, Write};
+    use std::net::TcpStream as StdTcpStream;
+
+    fn start(plugin_manager: PluginManager, config: ServerConfig) -> Server {
+        let server = start_server_with_config(([127, 0, 0, 1], 0).into(), plugin_manager, config).unwrap();
+        server.await_ready_timeout(Duration::from_secs(5)).unwrap();
+        server
+    }
+
+    fn connect(server: &Server) -> StdTcpStream {
+        let stream = StdTcpStream::connect(server.local_addr.unwrap()).unwrap();
+        stream.set_read_timeout(Some(Duration::from_secs(5))).unwrap();
+        stream
+    }
+
+    fn read_to_end(mut stream: StdTcpStream) -> String {
+        let mut response = Vec::new();
+        let _ = stream.read_to_end(&mut response);
+        String::from_utf8_lossy(&response).into_owned()
+    }
+
+    /// Reads one response with a `Content-Length` body, returning its head and body.
+    fn read_response(stream: &mut StdTcpStream) -> (String, String) {
+        let mut data = Vec::new();
+        let mut byte = [0u8; 1];
+        while !data.ends_with(b"\r\n\r\n") {
+            stream.read_exact(&mut byte).unwrap();
+            data.push(byte[0]);
+        }
+        let head = String::from_utf8(data).unwrap();
+        let length = head
+            .lines()
+            .find_map(|line| line.strip_prefix("Content-Length: "))
+            .map_or(0, |length| length.parse().unwrap());
+        let mut body = vec![0u8; length];
+        stream.read_exact(&mut body).unwrap();
+        (head, String::from_utf8(body).unwrap())
+    }
+
+    fn echo_server() -> Server {
+        let mut plugin_manager = PluginManager::new();
+        plugin_manager
+            .route("GET", "/items/:id", |request| {
+                let id: String = request.param("id")?;
+                Ok(Response::response_ok(id.into_bytes(), Mime::TextPlain))
+            })
+            .unwrap();
+        plugin_manager
+            .route("POST", "/echo", |request| Ok(Response::response_ok(request.body.clone(), Mime::TextPlain)))
+            .unwrap();
+        start(plugin_manager, ServerConfig::default())
+    }
+
+    #[test]
+    fn skips_ports_in_use() {
+        let taken = std::net::TcpListener::bind(("127.0.0.1", 0)).unwrap();
+        let port = taken.local_addr().unwrap().port();
+        let ip = IpAddr::from([127, 0, 0, 1]);
+
+        let mut server = start_server_in_range(ip, port..=port.saturating_add(20), PluginManager::new()).unwrap();
+        let bound = server.local_addr.unwrap().port();
+        assert!(bound > port && bound <= port.saturating_add(20), "bound {} after {}", bound, port);
+        server.shutdown();
+        server.await_shutdown();
+
+        // A range with no free port fails with the error of the last bind
+        let err = start_server_in_range(ip, port..=port, PluginManager::new()).err().unwrap();
+        assert_eq!(err.kind(), io::ErrorKind::AddrInUse);
+
+        // A fixed port that is taken is an error rather than a panic
+        assert!(start_server(SocketAddr::new(ip, port), PluginManager::new()).is_err());
+    }
+
+    #[test]
+    fn rejects_empty_port_ranges() {
+        let (first, last) = (9000, 8000);
+        let err = start_server_in_range([127, 0, 0, 1].into(), first..=last, PluginManager::new()).err().unwrap();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidInput);
+    }
+
+    #[tokio::test]
+    async fn answers_plugin_failures_with_500() {
+        let mut plugin_manager = PluginManager::new();
+        plugin_manager.route("GET", "/panic", |_| panic!("plugin bug")).unwrap();
+        plugin_manager
+            .route("GET", "/io", |_| Err(io::Error::new(io::ErrorKind::PermissionDenied, "denied").into()))
+            .unwrap();
+
+        for path in ["/panic", "/io"] {
+            let request = Request::from_string(&format!("GET {} HTTP/1.1\r\n\r\n", path)).unwrap();
+            let response = dispatch_request(&plugin_manager, request).await;
+            assert_eq!(response.status_code, 500, "{}", path);
+        }
+    }
+
+    #[test]
+    fn keeps_connections_alive() {
+        let mut server = echo_server();
+        let mut stream = connect(&server);
+
+        for id in ["1", "2", "3"] {
+            write!(stream, "GET /items/{} HTTP/1.1\r\nHost: test\r\n\r\n", id).unwrap();
+            let (head, body) = read_response(&mut stream);
+            assert!(head.starts_with("HTTP/1.1 200 OK\r\n"));
+            assert!(!head.contains("Connection: close"));
+            assert_eq!(body, id);
+        }
+
+        write!(stream, "GET /items/last HTTP/1.1\r\nConnection: close\r\n\r\n").unwrap();
+        let (head, body) = read_response(&mut stream);
+        assert!(head.contains("Connection: close\r\n"));
+        assert_eq!(body, "last");
+        assert_eq!(read_to_end(stream), "");
+
+        server.shutdown();
+        server.await_shutdown();
+    }
+
+    #[test]
+    fn closes_http10_connections_by_default() {
+        let mut server = echo_server();
+        let mut stream = connect(&server);
+        write!(stream, "GET /items/old HTTP/1.0\r\n\r\n").unwrap();
+        let response = read_to_end(stream);
+        assert!(response.contains("Connection: close\r\n"));
+        assert!(response.ends_with("old"));
+
+        server.shutdown();
+        server.await_shutdown();
+    }
+
+    #[test]
+    fn streams_to_http10_clients_without_chunking() {
+        let mut plugin_manager = PluginManager::new();
+        plugin_manager
+            .route("GET", "/stream", |_| {
+                let (response, writer) = Response::response_stream(Mime::TextPlain);
+                thread::spawn(move || {
+                    writer.blocking_send("ab").unwrap();
+                    writer.blocking_send("cd").unwrap();
+                });
+                Ok(response)
+            })
+            .unwrap();
+        let mut server = start(plugin_manager, ServerConfig::default());
+
+        // The connection is closed to end the body, even though the client asked to keep it open
+        let mut stream = connect(&server);
+        write!(stream, "GET /stream HTTP/1.0\r\nConnection: keep-alive\r\n\r\n").unwrap();
+        let response = read_to_end(stream);
+        assert!(!response.contains("Transfer-Encoding"), "{}", response);
+        assert!(!response.contains("Content-Length"), "{}", response);
+        assert!(response.contains("Connection: close\r\n"), "{}", response);
+        assert!(response.ends_with("\r\n\r\nabcd"), "{}", response);
+
+        let mut stream = connect(&server);
+        write!(stream, "GET /stream HTTP/1.1\r\nConnection: close\r\n\r\n").unwrap();
+        let response = read_to_end(stream);
+        assert!(response.contains("Transfer-Encoding: chunked\r\n"), "{}", response);
+        assert!(response.ends_with("2\r\nab\r\n2\r\ncd\r\n0\r\n\r\n"), "{}", response);
+
+        server.shutdown();
+        server.await_shutdown();
+    }
+
+    #[test]
+    fn compresses_bodies_for_clients_that_accept_gzip() {
+        let mut server = echo_server();
+        let body = "compressible ".repeat(200);
+
+        let mut stream = connect(&server);
+        write!(stream, "POST /echo HTTP/1.1\r\nAccept-Encoding: gzip, br\r\nContent-Length: {}\r\n\r\n{}", body.len(), body).unwrap();
+        let mut head = Vec::new();
+        let mut byte = [0u8; 1];
+        while !head.ends_with(b"\r\n\r\n") {
+            stream.read_exact(&mut byte).unwrap();
+            head.push(byte[0]);
+        }
+        let head = String::from_utf8(head).unwrap();
+        assert!(head.contains("Content-Encoding: gzip\r\n"), "{}", head);
+        assert!(head.contains("Vary: Accept-Encoding\r\n"), "{}", head);
+        let length: usize = head.lines().find_map(|line| line.strip_prefix("Content-Length: ")).unwrap().parse().unwrap();
+        assert!(length < body.len());
+
+        let mut stream = connect(&server);
+        write!(stream, "POST /echo HTTP/1.1\r\nContent-Length: {}\r\n\r\n{}", body.len(), body).unwrap();
+        let (head, echoed) = read_response(&mut stream);
+        assert!(!head.contains("Content-Encoding"), "{}", head);
+        assert_eq!(echoed, body);
+
+        server.shutdown();
+        server.await_shutdown();
+    }
+
+    #[test]
+    fn answers_pipelined_requests_in_order() {
+        let mut server = echo_server();
+        let mut stream = connect(&server);
+        stream
+            .write_all(
+                b"GET /items/a HTTP/1.1\r\n\r\n\
+                  POST /echo HTTP/1.1\r\nContent-Length: 5\r\n\r\nhello\
+                  POST /echo HTTP/1.1\r\nTransfer-Encoding: chunked\r\n\r\n3\r\nabc\r\n0\r\n\r\n\
+                  GET /missing HTTP/1.1\r\n\r\n\
+                  GET /items/z HTTP/1.1\r\nConnection: close\r\n\r\n",
+            )
+            .unwrap();
+
+        let responses: Vec<(String, String)> = (0..5).map(|_| read_response(&mut stream)).collect();
+        let bodies: Vec<&str> = responses.iter().map(|(_, body)| body.as_str()).collect();
+        assert_eq!(bodies[..3], ["a", "hello", "abc"]);
+        assert!(responses[3].0.starts_with("HTTP/1.1 404 Not Found\r\n"));
+        assert_eq!(bodies[4], "z");
+        assert_eq!(read_to_end(stream), "");
+
+        server.shutdown();
+        server.await_shutdown();
+    }
+
+    #[test]
+    fn drops_bodies_of_bodiless_statuses() {
+        let mut plugin_manager = PluginManager::new();
+        plugin_manager
+            .route("GET", "/empty", |_| {
+                let mut response = Response::response_no_content();
+                response.set_body_from_str("stray");
+                Ok(response)
+            })
+            .unwrap();
+        plugin_manager
+            .route("GET", "/next", |_| Ok(Response::response_ok(b"next".to_vec(), Mime::TextPlain)))
+            .unwrap();
+        let mut server = start(plugin_manager, ServerConfig::default());
+        let mut stream = connect(&server);
+
+        stream.write_all(b"GET /empty HTTP/1.1\r\n\r\nGET /next HTTP/1.1\r\nConnection: close\r\n\r\n").unwrap();
+        let response = read_to_end(stream);
+        let (first, second) = response.split_once("\r\n\r\n").unwrap();
+        assert!(first.starts_with("HTTP/1.1 204 No Content\r\n"));
+        assert!(!first.contains("Content-Length"));
+        assert!(second.starts_with("HTTP/1.1 200 OK\r\n"), "{}", second);
+        assert!(second.ends_with("next"));
+
+        server.shutdown();
+        server.await_shutdown();
+    }
+
+    #[test]
+    fn rejects_endless_chunk_size_line() {
+        let mut server = echo_server();
+        let mut stream = connect(&server);
+        stream.write_all(b"POST /echo HTTP/1.1\r\nTransfer-Encoding: chunked\r\n\r\n").unwrap();
+        stream.write_all(&[b'a'; 16 * 1024 + 1]).unwrap();
+        let (head, _) = read_response(&mut stream);
+        assert!(head.starts_with("HTTP/1.1 400 "), "{}", head);
+
+        server.shutdown();
+        server.await_shutdown();
+    }
+
+    #[test]
+    fn spools_large_multipart_bodies_to_disk() {
+        let mut plugin_manager = PluginManager::new();
+        plugin_manager
+            .route("POST", "/upload", |request| {
+                assert!(request.body.is_empty());
+                assert!(request.body_file.is_some());
+                let multipart = request.parse_multipart_with_threshold(4)?;
+                let file = multipart.file("upload").unwrap().bytes().unwrap();
+                let body = format!("{}:{}", multipart.field("title").unwrap(), String::from_utf8(file).unwrap());
+                Ok(Response::response_ok(body.into_bytes(), Mime::TextPlain))
+            })
+            .unwrap();
+        let mut server = start(plugin_manager, ServerConfig::default().set_multipart_spool_size(16));
+        let mut stream = connect(&server);
+
+        let body = "--XyZ\r\nContent-Disposition: form-data; name=\"title\"\r\n\r\nHi\r\n\
+                    --XyZ\r\nContent-Disposition: form-data; name=\"upload\"; filename=\"a.txt\"\r\n\r\n\
+                    file contents\r\n--XyZ--\r\n";
+        write!(
+            stream,
+            "POST /upload HTTP/1.1\r\nContent-Type: multipart/form-data; boundary=XyZ\r\nContent-Length: {}\r\n\r\n{}\
+             GET /missing HTTP/1.1\r\n\r\n",
+            body.len(),
+            body
+        )
+        .unwrap();
+
+        let (head, body) = read_response(&mut stream);
+        assert!(head.starts_with("HTTP/1.1 200 OK\r\n"), "{}", head);
+        assert_eq!(body, "Hi:file contents");
+        let (head, _) = read_response(&mut stream);
+        assert!(head.starts_with("HTTP/1.1 404 "));
+
+        server.shutdown();
+        server.await_shutdown();
+    }
+
+    #[test]
+    fn shutdown_drains_requests_and_ends_streams() {
+        let mut plugin_manager = PluginManager::new();
+        plugin_manager
+            .route("GET", "/slow", |_| {
+                thread::sleep(Duration::from_secs(3));
+                Ok(Response::response_ok(b"slow".to_vec(), Mime::TextPlain))
+            })
+            .unwrap();
+        plugin_manager
+            .route("GET", "/medium", |_| {
+                thread::sleep(Duration::from_millis(800));
+                Ok(Response::response_ok(b"medium".to_vec(), Mime::TextPlain))
+            })
+            .unwrap();
+        plugin_manager
+            .route("GET", "/events", |_| {
+                let (response, sender) = Response::response_sse();
+                thread::spawn(move || {
+                    while !sender.is_closed() {
+                        thread::sleep(Duration::from_millis(20));
+                    }
+                });
+                Ok(response)
+            })
+            .unwrap();
+        let mut server = start(plugin_manager, ServerConfig::default().set_shutdown_timeout(Duration::from_millis(1500)));
+
+        let clients: Vec<_> = ["/slow", "/medium", "/events"]
+            .iter()
+            .map(|path| {
+                let mut stream = connect(&server);
+                write!(stream, "GET {} HTTP/1.1\r\nHost: test\r\n\r\n", path).unwrap();
+                thread::spawn(move || read_to_end(stream))
+            })
+            .collect();
+        thread::sleep(Duration::from_millis(300));
+
+        server.shutdown();
+        let report = server.await_shutdown().unwrap();
+        assert_eq!(report, ShutdownReport { in_flight: 2, dropped: 1, streams: 1 });
+
+        let responses: Vec<String> = clients.into_iter().map(|client| client.join().unwrap()).collect();
+        assert_eq!(responses[0], "");
+        assert!(responses[1].starts_with("HTTP/1.1 200 OK\r\n"));
+        assert!(responses[1].contains("Connection: close\r\n"));
+        assert!(responses[1].ends_with("medium"));
+        assert!(responses[2].contains("Content-Type: text/event-stream"));
+        assert!(responses[2].ends_with("0\r\n\r\n"));
     }
 }
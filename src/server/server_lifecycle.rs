@@ -0,0 +1,338 @@
+// src/server/server_lifecycle.rs
+
+use std::io;
+use std::panic::{catch_unwind, AssertUnwindSafe};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Condvar, Mutex, MutexGuard};
+use std::time::Duration;
+use tokio::{sync::{watch, Notify}, task::JoinSet, time::timeout};
+
+use crate::server::structs::structs_config::{LifecycleCallback, ServerConfig};
+use crate::util::logging::{logln, logln_color, Color};
+
+/// The stages a server goes through, as reported by `Server::state`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ServerState {
+    /// The server thread is setting up and not accepting requests yet.
+    Starting,
+    /// The server is accepting requests.
+    Running,
+    /// Shutdown was requested and open connections are being finished.
+    Stopping,
+    /// The server has shut down.
+    Stopped,
+    /// The server could not start, with the reason.
+    Failed(String),
+}
+
+/// Outcome of a graceful shutdown, returned by `Server::await_shutdown`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ShutdownReport {
+    /// Requests still being handled when shutdown began.
+    pub in_flight: usize,
+    /// Requests cut off because they did not finish within `ServerConfig::shutdown_timeout`.
+    pub dropped: usize,
+    /// Event streams and WebSockets open when shutdown began; they are ended rather than drained.
+    pub streams: usize,
+}
+
+/// Shared state of a running server, waited on by `Server` and advanced by the server thread.
+pub(crate) struct Lifecycle {
+    state: Mutex<ServerState>,
+    changed: Condvar,
+    notify: Notify,
+    report: Mutex<Option<ShutdownReport>>,
+    on_started: Option<LifecycleCallback>,
+    on_stopping: Option<LifecycleCallback>,
+    on_stopped: Option<LifecycleCallback>,
+}
+
+impl Lifecycle {
+    pub(crate) fn new(config: &ServerConfig) -> Self {
+        Self {
+            state: Mutex::new(ServerState::Starting),
+            changed: Condvar::new(),
+            notify: Notify::new(),
+            report: Mutex::new(None),
+            on_started: config.on_started.clone(),
+            on_stopping: config.on_stopping.clone(),
+            on_stopped: config.on_stopped.clone(),
+        }
+    }
+
+    pub(crate) fn state(&self) -> ServerState {
+        self.lock().clone()
+    }
+
+    /// Returns the shutdown report, once the server has stopped.
+    pub(crate) fn report(&self) -> Option<ShutdownReport> {
+        *self.report.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    /// Records how the shutdown went and moves to `ServerState::Stopped`.
+    pub(crate) fn finish(&self, report: ShutdownReport) {
+        *self.report.lock().unwrap_or_else(|e| e.into_inner()) = Some(report);
+        self.set(ServerState::Stopped);
+    }
+
+    /// Moves to `state`, wakes every waiter and runs the matching callback.
+    pub(crate) fn set(&self, state: ServerState) {
+        let callback = match state {
+            ServerState::Running => self.on_started.as_ref(),
+            ServerState::Stopping => self.on_stopping.as_ref(),
+            ServerState::Stopped => self.on_stopped.as_ref(),
+            ServerState::Starting | ServerState::Failed(_) => None,
+        };
+
+        *self.lock() = state;
+        self.changed.notify_all();
+        self.notify.notify_waiters();
+
+        // A panicking callback must not take the server thread down with it
+        if let Some(callback) = callback {
+            if catch_unwind(AssertUnwindSafe(|| callback())).is_err() {
+                logln("Server lifecycle callback panicked");
+            }
+        }
+    }
+
+    /// Blocks until the server is running or has failed, for at most `timeout` if given.
+    pub(crate) fn wait_ready_blocking(&self, timeout: Option<Duration>) -> io::Result<()> {
+        let state = self.lock();
+        let state = match timeout {
+            Some(timeout) => {
+                let (state, _) = self
+                    .changed
+                    .wait_timeout_while(state, timeout, |state| *state == ServerState::Starting)
+                    .unwrap_or_else(|e| e.into_inner());
+                state
+            }
+            None => self
+                .changed
+                .wait_while(state, |state| *state == ServerState::Starting)
+                .unwrap_or_else(|e| e.into_inner()),
+        };
+        ready_result(&state).unwrap_or_else(|| {
+            Err(io::Error::new(io::ErrorKind::TimedOut, "Server did not start in time"))
+        })
+    }
+
+    /// Waits until the server is running or has failed.
+    pub(crate) async fn wait_ready(&self) -> io::Result<()> {
+        loop {
+            // Register for the wake-up before checking, so a change in between is not missed
+            let notified = self.notify.notified();
+            tokio::pin!(notified);
+            notified.as_mut().enable();
+
+            if let Some(result) = ready_result(&self.lock()) {
+                return result;
+            }
+            notified.await;
+        }
+    }
+
+    fn lock(&self) -> MutexGuard<'_, ServerState> {
+        self.state.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+/// Counts the requests being handled, so shutdown knows what it waits for and what it drops.
+///
+/// Requests that turned into event streams or WebSockets are counted apart, as they only
+/// end when the server stops and are not requests left unanswered.
+#[derive(Clone, Default)]
+pub(crate) struct InFlight {
+    requests: Arc<AtomicUsize>,
+    streams: Arc<AtomicUsize>,
+}
+
+impl InFlight {
+    /// Marks a request as started; it counts as in flight until the guard is dropped.
+    pub(crate) fn start(&self) -> InFlightGuard {
+        self.requests.fetch_add(1, Ordering::SeqCst);
+        InFlightGuard(Arc::clone(&self.requests))
+    }
+
+    /// Moves a request whose response opened a long-lived stream over to the stream count.
+    pub(crate) fn mark_stream(&self, guard: InFlightGuard) -> InFlightGuard {
+        self.streams.fetch_add(1, Ordering::SeqCst);
+        drop(guard);
+        InFlightGuard(Arc::clone(&self.streams))
+    }
+
+    pub(crate) fn count(&self) -> usize {
+        self.requests.load(Ordering::SeqCst)
+    }
+
+    pub(crate) fn streams(&self) -> usize {
+        self.streams.load(Ordering::SeqCst)
+    }
+}
+
+pub(crate) struct InFlightGuard(Arc<AtomicUsize>);
+
+impl Drop for InFlightGuard {
+    fn drop(&mut self) {
+        self.0.fetch_sub(1, Ordering::SeqCst);
+    }
+}
+
+/// Tells `tasks` to stop through `stopping`, waits up to `deadline` for them to finish,
+/// then aborts those still running.
+///
+/// The counts are taken before the stop is signalled, as idle connections and
+/// long-lived streams end on their own as soon as it is.
+pub(crate) async fn drain<T: 'static>(tasks: &mut JoinSet<T>, in_flight: &InFlight, stopping: &watch::Sender<bool>, deadline: Duration) -> ShutdownReport {
+    let mut report = ShutdownReport {
+        in_flight: in_flight.count(),
+        dropped: 0,
+        streams: in_flight.streams(),
+    };
+    let _ = stopping.send(true);
+
+    let drained = timeout(deadline, async {
+        while tasks.join_next().await.is_some() {}
+    })
+    .await
+    .is_ok();
+
+    if !drained {
+        // Counted before aborting, as the aborted tasks release their guards
+        report.dropped = in_flight.count();
+        tasks.shutdown().await;
+    }
+
+    if report.dropped > 0 {
+        logln_color(
+            &format!("Shutdown deadline reached, dropped {} of {} in-flight requests", report.dropped, report.in_flight),
+            Color::Yellow,
+        );
+    } else if report.in_flight > 0 {
+        logln(&format!("Drained {} in-flight requests", report.in_flight));
+    }
+    if report.streams > 0 {
+        logln(&format!("Ended {} event streams and WebSockets", report.streams));
+    }
+    report
+}
+
+/// Returns the outcome of waiting for readiness, or `None` while the server is still starting.
+fn ready_result(state: &ServerState) -> Option<io::Result<()>> {
+    match state {
+        ServerState::Starting => None,
+        ServerState::Running => Some(Ok(())),
+        ServerState::Stopping | ServerState::Stopped => Some(Err(io::Error::new(
+            io::ErrorKind::Interrupted,
+            "Server stopped before it was ready",
+        ))),
+        ServerState::Failed(reason) => Some(Err(io::Error::other(reason.clone()))),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::thread;
+
+    use crate::server::server_core::Server;
+
+    fn server(lifecycle: &Arc<Lifecycle>) -> Server {
+        Server {
+            local_addr: None,
+            handle: None,
+            _shutdown_tx: None,
+            lifecycle: Arc::clone(lifecycle),
+        }
+    }
+
+    /// Sets `state` on another thread after `delay`.
+    fn set_later(lifecycle: &Arc<Lifecycle>, state: ServerState, delay: Duration) -> thread::JoinHandle<()> {
+        let lifecycle = Arc::clone(lifecycle);
+        thread::spawn(move || {
+            thread::sleep(delay);
+            lifecycle.set(state);
+        })
+    }
+
+    #[test]
+    fn reports_the_current_state() {
+        let lifecycle = Arc::new(Lifecycle::new(&ServerConfig::default()));
+        let server = server(&lifecycle);
+        assert_eq!(server.state(), ServerState::Starting);
+        lifecycle.set(ServerState::Running);
+        assert_eq!(server.state(), ServerState::Running);
+        lifecycle.set(ServerState::Stopping);
+        assert_eq!(server.state(), ServerState::Stopping);
+        lifecycle.finish(ShutdownReport::default());
+        assert_eq!(server.state(), ServerState::Stopped);
+        assert_eq!(lifecycle.report(), Some(ShutdownReport::default()));
+    }
+
+    #[test]
+    fn await_ready_timeout_waits_for_the_server() {
+        let lifecycle = Arc::new(Lifecycle::new(&ServerConfig::default()));
+        let server = server(&lifecycle);
+
+        let err = server.await_ready_timeout(Duration::from_millis(50)).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::TimedOut);
+
+        let setter = set_later(&lifecycle, ServerState::Running, Duration::from_millis(50));
+        server.await_ready_timeout(Duration::from_secs(5)).unwrap();
+        setter.join().unwrap();
+        server.await_ready().unwrap();
+    }
+
+    #[test]
+    fn await_ready_reports_failures() {
+        let lifecycle = Arc::new(Lifecycle::new(&ServerConfig::default()));
+        lifecycle.set(ServerState::Failed("address in use".to_string()));
+        let err = server(&lifecycle).await_ready_timeout(Duration::from_secs(5)).unwrap_err();
+        assert_eq!(err.to_string(), "address in use");
+
+        let lifecycle = Arc::new(Lifecycle::new(&ServerConfig::default()));
+        lifecycle.set(ServerState::Stopped);
+        let err = server(&lifecycle).await_ready().unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::Interrupted);
+    }
+
+    #[tokio::test]
+    async fn ready_resolves_once_running() {
+        let lifecycle = Arc::new(Lifecycle::new(&ServerConfig::default()));
+        let server = server(&lifecycle);
+        let setter = set_later(&lifecycle, ServerState::Running, Duration::from_millis(50));
+        timeout(Duration::from_secs(5), server.ready()).await.unwrap().unwrap();
+        setter.join().unwrap();
+
+        // Already running, so it resolves at once
+        server.ready().await.unwrap();
+    }
+
+    #[test]
+    fn runs_each_callback_once_in_order() {
+        let calls = Arc::new(Mutex::new(Vec::new()));
+        let record = |name: &'static str| {
+            let calls = Arc::clone(&calls);
+            move || calls.lock().unwrap().push(name)
+        };
+        let config = ServerConfig::default()
+            .set_on_started(record("started"))
+            .set_on_stopping(record("stopping"))
+            .set_on_stopped(record("stopped"));
+        let lifecycle = Lifecycle::new(&config);
+
+        lifecycle.set(ServerState::Running);
+        lifecycle.set(ServerState::Stopping);
+        lifecycle.finish(ShutdownReport::default());
+        assert_eq!(*calls.lock().unwrap(), ["started", "stopping", "stopped"]);
+    }
+
+    #[test]
+    fn contains_panicking_callbacks() {
+        let config = ServerConfig::default().set_on_started(|| panic!("callback failed"));
+        let lifecycle = Lifecycle::new(&config);
+        lifecycle.set(ServerState::Running);
+        assert_eq!(lifecycle.state(), ServerState::Running);
+        assert!(lifecycle.wait_ready_blocking(Some(Duration::from_millis(10))).is_ok());
+    }
+}
@@ -0,0 +1,283 @@
+// src/server/server_protocol.rs
+
+use std::sync::{mpsc as std_mpsc, Arc};
+use std::thread;
+use tokio::{sync::{mpsc, oneshot, watch}, task::JoinSet};
+
+use crate::server::structs::{structs_config::ServerConfig, structs_header::StatusCode, structs_mime::Mime, structs_request::Request, structs_response::Response};
+use crate::util::logging::{logln, logln_color, Color};
+
+use super::plugin::plugin_manager::PluginManager;
+use super::server_core::{apply_default_headers, dispatch_request, log_response, Server};
+use super::server_lifecycle::{drain, InFlight, InFlightGuard, Lifecycle, ServerState};
+use super::server_reader::parse_request;
+
+/// A queued request, with the channel its response is returned on.
+type ProtocolJob = (Request, std_mpsc::SyncSender<Response>);
+
+/// Passes requests from an in-process transport, such as a webview custom protocol,
+/// to the plugins of a server started with `start_protocol_server`.
+///
+/// Cloning the handler is cheap; every clone feeds the same server.
+#[derive(Clone)]
+pub struct ProtocolHandler {
+    sender: mpsc::UnboundedSender<ProtocolJob>,
+    config: Arc<ServerConfig>,
+}
+
+impl ProtocolHandler {
+    /// Parses a raw request with the server's header and body limits, then dispatches it.
+    ///
+    /// Blocks like `handle`. A request that is malformed or over the limits is answered
+    /// with the same status the TCP server would send.
+    ///
+    /// # Arguments
+    /// * `raw` - The request head and body, framed as on the wire.
+    ///
+    /// # Returns
+    /// * `Response` - The plugin's response, or the error status for an unreadable request.
+    pub fn handle_bytes(&self, raw: &[u8]) -> Response {
+        match parse_request(raw, &self.config) {
+            Ok(request) => self.handle(request),
+            Err(e) => {
+                logln(&format!("Failed to read request: {}", e));
+                let status = e.status_code().unwrap_or(StatusCode::BadRequest);
+                Response::response_error(status.to_msg().to_owned(), status)
+            }
+        }
+    }
+
+    /// Dispatches a request and waits for its complete response.
+    ///
+    /// Blocks the calling thread for up to the config's `response_timeout`, so it must not
+    /// be called from an async task, where it would stall a runtime worker; use
+    /// `tokio::task::spawn_blocking` there instead.
+    /// A webview calls its custom protocol handler on the UI thread, so the window does
+    /// not respond while a request is answered, and a plugin that needs the event loop,
+    /// such as one opening a native dialog, waits for the timeout.
+    /// Streamed bodies are collected until they end, so long-lived streams are not
+    /// supported: WebSocket upgrades and event streams get `501 Not Implemented`.
+    ///
+    /// # Arguments
+    /// * `request` - The request to dispatch.
+    ///
+    /// # Returns
+    /// * `Response` - The plugin's response, `504 Gateway Timeout` if it does not arrive in
+    ///   time, or `503 Service Unavailable` once the server has stopped.
+    pub fn handle(&self, request: Request) -> Response {
+        let (reply_tx, reply_rx) = std_mpsc::sync_channel(1);
+        if self.sender.send((request, reply_tx)).is_err() {
+            return unavailable();
+        }
+        match reply_rx.recv_timeout(self.config.response_timeout) {
+            Ok(response) => response,
+            Err(std_mpsc::RecvTimeoutError::Timeout) => {
+                // The request keeps running on the server; its late response is discarded
+                let status = StatusCode::GatewayTimeout;
+                Response::response_error(status.to_msg().to_owned(), status)
+            }
+            Err(std_mpsc::RecvTimeoutError::Disconnected) => unavailable(),
+        }
+    }
+}
+
+/// Starts a server that runs the `PluginManager` without opening a socket.
+///
+/// Requests are fed in through the returned `ProtocolHandler`; the `Server` is used
+/// to wait for readiness and to shut down, as with `start_server`.
+pub fn start_protocol_server(plugin_manager: PluginManager) -> (Server, ProtocolHandler) {
+    start_protocol_server_with_config(plugin_manager, ServerConfig::default())
+}
+
+/// Starts a server that runs the `PluginManager` without opening a socket, with the provided `ServerConfig`.
+///
+/// The idle and read timeouts and `max_message_size` of the config do not apply, as requests do not come from a socket.
+pub fn start_protocol_server_with_config(plugin_manager: PluginManager, config: ServerConfig) -> (Server, ProtocolHandler) {
+    let config = Arc::new(config);
+    let (shutdown_tx, mut shutdown_rx) = oneshot::channel::<()>();
+    let (job_tx, mut job_rx) = mpsc::unbounded_channel::<ProtocolJob>();
+    let lifecycle = Arc::new(Lifecycle::new(&config));
+    let server_lifecycle = Arc::clone(&lifecycle);
+    let handler = ProtocolHandler { sender: job_tx, config: Arc::clone(&config) };
+
+    let server_handle = thread::spawn(move || {
+        let lifecycle = server_lifecycle;
+        let rt = match tokio::runtime::Runtime::new() {
+            Ok(rt) => rt,
+            Err(e) => {
+                logln(&format!("Failed to create Tokio runtime: {}", e));
+                lifecycle.set(ServerState::Failed(e.to_string()));
+                return;
+            }
+        };
+        rt.block_on(async move {
+            logln_color("[Started: Server]", Color::Green);
+            logln(&format!("{} {}", Color::BrightBlack.paint("Server listening on:"), Color::Blue.paint("in-process protocol handler")));
+
+            let plugin_manager = Arc::new(plugin_manager);
+
+            // Lets requests collecting a streamed body stop waiting for it on shutdown
+            let (stopping_tx, stopping_rx) = watch::channel(false);
+            let mut requests = JoinSet::new();
+            let in_flight = InFlight::default();
+            lifecycle.set(ServerState::Running);
+
+            loop {
+                tokio::select! {
+                    _ = &mut shutdown_rx => {
+                        break;
+                    }
+                    Some((request, reply)) = job_rx.recv() => {
+                        let plugin_manager = Arc::clone(&plugin_manager);
+                        let guard = in_flight.start();
+                        requests.spawn(handle_job(request, reply, plugin_manager, stopping_rx.clone(), guard));
+                    }
+                    Some(_) = requests.join_next(), if !requests.is_empty() => {
+                        // Reap finished requests
+                    }
+                }
+            }
+
+            // Dropping the receiver fails any request sent from now on
+            drop(job_rx);
+            lifecycle.set(ServerState::Stopping);
+            // Requests cut off at the deadline drop their reply channel and are answered with 503
+            let report = drain(&mut requests, &in_flight, &stopping_tx, config.shutdown_timeout).await;
+            lifecycle.finish(report);
+        });
+
+        // Plugins still blocking after the deadline are left behind rather than waited for
+        rt.shutdown_background();
+    });
+
+    let server = Server {
+        local_addr: None,
+        handle: Some(server_handle),
+        _shutdown_tx: Some(shutdown_tx),
+        lifecycle,
+    };
+    (server, handler)
+}
+
+/// Dispatches one request and sends back its response with the body fully collected.
+async fn handle_job(request: Request, reply: std_mpsc::SyncSender<Response>, plugin_manager: Arc<PluginManager>, mut stopping: watch::Receiver<bool>, _guard: InFlightGuard) {
+    let method = request.method.clone();
+    let path = request.path.clone();
+
+    let mut response = dispatch_request(&plugin_manager, request).await;
+
+    let is_event_stream = response.header_fields.content_type().is_some_and(|mime| *mime.essence() == Mime::EventStream);
+    if response.status_code == 101 || is_event_stream {
+        let status = StatusCode::NotImplemented;
+        response = Response::response_error("WebSockets and event streams need the TCP server".to_string(), status);
+    }
+
+    apply_default_headers(&mut response, true);
+    log_response(&method, &path, response.status_code);
+
+    if let Some(mut stream) = response.stream.take() {
+        loop {
+            let chunk = tokio::select! {
+                chunk = stream.next_chunk() => chunk,
+                _ = stopping.wait_for(|&stopping| stopping) => None,
+            };
+            let Some(chunk) = chunk else { break };
+            response.body.extend_from_slice(&chunk);
+        }
+    }
+    if method == "HEAD" || !response.allows_body() {
+        response.body.clear();
+    }
+
+    let _ = reply.send(response);
+}
+
+fn unavailable() -> Response {
+    let status = StatusCode::ServiceUnavailable;
+    Response::response_error(status.to_msg().to_owned(), status)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    fn start(plugin_manager: PluginManager, config: ServerConfig) -> (Server, ProtocolHandler) {
+        let (server, handler) = start_protocol_server_with_config(plugin_manager, config);
+        server.await_ready_timeout(Duration::from_secs(5)).unwrap();
+        (server, handler)
+    }
+
+    fn echo_server(config: ServerConfig) -> (Server, ProtocolHandler) {
+        let mut plugin_manager = PluginManager::new();
+        plugin_manager
+            .route("POST", "/echo", |request| Ok(Response::response_ok(request.body.clone(), Mime::TextPlain)))
+            .unwrap();
+        plugin_manager
+            .route("GET", "/slow", |_| {
+                thread::sleep(Duration::from_millis(500));
+                Ok(Response::response_ok(b"slow".to_vec(), Mime::TextPlain))
+            })
+            .unwrap();
+        plugin_manager.route("GET", "/upgrade", |_| Ok(Response::new(101, "Switching Protocols"))).unwrap();
+        plugin_manager
+            .route("GET", "/events", |_| {
+                let (response, _sender) = Response::response_sse();
+                Ok(response)
+            })
+            .unwrap();
+        start(plugin_manager, config)
+    }
+
+    #[test]
+    fn answers_raw_requests() {
+        let (mut server, handler) = echo_server(ServerConfig::default());
+        let response = handler.handle_bytes(b"POST /echo HTTP/1.1\r\nContent-Length: 5\r\n\r\nhello");
+        assert_eq!(response.status_code, 200);
+        assert_eq!(response.body, b"hello");
+        server.shutdown();
+    }
+
+    #[test]
+    fn applies_the_header_and_body_limits() {
+        let config = ServerConfig::default().set_max_header_size(256).set_max_body_size(8);
+        let (mut server, handler) = echo_server(config);
+
+        let response = handler.handle_bytes(b"POST /echo HTTP/1.1\r\nContent-Length: 9\r\n\r\n123456789");
+        assert_eq!(response.status_code, 413);
+
+        let raw = format!("POST /echo HTTP/1.1\r\nX-Filler: {}\r\nContent-Length: 0\r\n\r\n", "a".repeat(300));
+        let response = handler.handle_bytes(raw.as_bytes());
+        assert_eq!(response.status_code, 431);
+
+        server.shutdown();
+    }
+
+    #[test]
+    fn times_out_slow_plugins() {
+        let config = ServerConfig::default().set_response_timeout(Duration::from_millis(100));
+        let (mut server, handler) = echo_server(config);
+        let response = handler.handle_bytes(b"GET /slow HTTP/1.1\r\n\r\n");
+        assert_eq!(response.status_code, 504);
+        server.shutdown();
+    }
+
+    #[test]
+    fn refuses_upgrades_and_event_streams() {
+        let (mut server, handler) = echo_server(ServerConfig::default());
+        for path in ["/upgrade", "/events"] {
+            let response = handler.handle_bytes(format!("GET {} HTTP/1.1\r\n\r\n", path).as_bytes());
+            assert_eq!(response.status_code, 501, "{}", path);
+        }
+        server.shutdown();
+    }
+
+    #[test]
+    fn answers_503_after_shutdown() {
+        let (mut server, handler) = echo_server(ServerConfig::default());
+        server.shutdown();
+        server.await_shutdown();
+        let response = handler.handle_bytes(b"POST /echo HTTP/1.1\r\nContent-Length: 2\r\n\r\nhi");
+        assert_eq!(response.status_code, 503);
+    }
+}
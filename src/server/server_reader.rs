@@ -0,0 +1,278 @@
+// src/server/server_reader.rs
+
+use std::fmt;
+use std::io::{Error, ErrorKind};
+use std::time::Duration;
+
+use tokio::io::{AsyncRead, AsyncReadExt, AsyncWriteExt};
+use tokio::time::timeout;
+
+use crate::server::server_chunked::{ChunkedDecoder, ChunkedError};
+use crate::server::structs::structs_config::ServerConfig;
+use crate::server::structs::structs_form::TempFile;
+use crate::server::structs::structs_header::StatusCode;
+use crate::server::structs::structs_mime::Mime;
+use crate::server::structs::structs_request::{Request, RequestError};
+
+/// Error raised while reading a request from a connection.
+#[derive(Debug)]
+pub enum ReadError {
+    HeaderTooLarge(usize),
+    BodyTooLarge(usize),
+    InvalidRequest(RequestError),
+    Timeout(Duration),
+    Io(Error),
+}
+
+impl fmt::Display for ReadError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ReadError::HeaderTooLarge(limit) => write!(f, "Request head exceeds {} bytes", limit),
+            ReadError::BodyTooLarge(length) => write!(f, "Request body of {} bytes exceeds the limit", length),
+            ReadError::InvalidRequest(err) => write!(f, "{}", err),
+            ReadError::Timeout(limit) => write!(f, "No request data received for {:?}", limit),
+            ReadError::Io(err) => write!(f, "I/O error: {}", err),
+        }
+    }
+}
+
+impl std::error::Error for ReadError {}
+
+impl ReadError {
+    /// Returns the status the failed request is answered with, or `None` if the
+    /// connection is broken and cannot be answered.
+    pub fn status_code(&self) -> Option<StatusCode> {
+        match self {
+            ReadError::HeaderTooLarge(_) => Some(StatusCode::RequestHeaderFieldsTooLarge),
+            ReadError::BodyTooLarge(_) => Some(StatusCode::PayloadTooLarge),
+            ReadError::InvalidRequest(RequestError::UnsupportedTransferEncoding(_)) => Some(StatusCode::NotImplemented),
+            ReadError::InvalidRequest(_) => Some(StatusCode::BadRequest),
+            ReadError::Timeout(_) => Some(StatusCode::RequestTimeout),
+            ReadError::Io(_) => None,
+        }
+    }
+}
+
+impl From<Error> for ReadError {
+    fn from(err: Error) -> Self {
+        ReadError::Io(err)
+    }
+}
+
+impl From<ChunkedError> for ReadError {
+    fn from(err: ChunkedError) -> Self {
+        match err {
+            ChunkedError::BodyTooLarge(length) => ReadError::BodyTooLarge(length),
+            err => ReadError::InvalidRequest(err.into()),
+        }
+    }
+}
+
+impl From<RequestError> for ReadError {
+    fn from(err: RequestError) -> Self {
+        ReadError::InvalidRequest(err)
+    }
+}
+
+/// Reads one complete request (head and body) from the stream.
+///
+/// `buf` holds bytes already received from the connection and keeps any bytes read
+/// past the end of this request. Returns `Ok(None)` if the peer closed the connection,
+/// or sent nothing for `ServerConfig::idle_timeout`, before the request started. Once it
+/// has started, each read fails with `ReadError::Timeout` after `ServerConfig::read_timeout`.
+pub async fn read_request<R: AsyncRead + Unpin>(
+    stream: &mut R,
+    buf: &mut Vec<u8>,
+    config: &ServerConfig,
+) -> Result<Option<Request>, ReadError> {
+    // Read until the blank line that terminates the headers
+    let head_end = loop {
+        if let Some(end) = Request::find_head_end(buf) {
+            if end > config.max_header_size {
+                return Err(ReadError::HeaderTooLarge(config.max_header_size));
+            }
+            break end;
+        }
+        if buf.len() > config.max_header_size {
+            return Err(ReadError::HeaderTooLarge(config.max_header_size));
+        }
+        if buf.is_empty() {
+            // Waiting for the next request on an idle connection
+            match read_more(stream, buf, config.idle_timeout).await {
+                Ok(0) | Err(ReadError::Timeout(_)) => return Ok(None),
+                Ok(_) => continue,
+                Err(err) => return Err(err),
+            }
+        }
+        if read_more(stream, buf, config.read_timeout).await? == 0 {
+            return Err(unexpected_eof().into());
+        }
+    };
+
+    let mut request = Request::from_head(&buf[..head_end])?;
+
+    if request.is_chunked() {
+        // Decode the chunks as they arrive, dropping consumed bytes so only a partial line is kept
+        buf.drain(..head_end);
+        let mut decoder = ChunkedDecoder::new(config.max_header_size, config.max_body_size);
+        loop {
+            let consumed = decoder.decode(buf)?;
+            buf.drain(..consumed);
+            if decoder.is_complete() {
+                request.body = decoder.into_body();
+                return Ok(Some(request));
+            }
+            if read_more(stream, buf, config.read_timeout).await? == 0 {
+                return Err(unexpected_eof().into());
+            }
+        }
+    }
+
+    // Read exactly `Content-Length` bytes of body
+    let content_length = request.content_length()?.unwrap_or(0);
+    if content_length > config.max_body_size {
+        return Err(ReadError::BodyTooLarge(content_length));
+    }
+
+    let is_multipart = request.content_type().is_some_and(|mime| *mime.essence() == Mime::MultipartFormData);
+    if is_multipart && content_length > config.multipart_spool_size {
+        buf.drain(..head_end);
+        request.body_file = Some(spool_body(stream, buf, content_length, config.read_timeout).await?);
+        return Ok(Some(request));
+    }
+
+    let request_end = head_end + content_length;
+    while buf.len() < request_end {
+        if read_more(stream, buf, config.read_timeout).await? == 0 {
+            return Err(unexpected_eof().into());
+        }
+    }
+
+    request.body = buf[head_end..request_end].to_vec();
+    buf.drain(..request_end);
+
+    Ok(Some(request))
+}
+
+/// Parses one complete request already held in memory, such as one handed over by a webview.
+///
+/// Applies the same framing checks and size limits as `read_request`; bytes after the
+/// end of the request are ignored.
+pub fn parse_request(raw: &[u8], config: &ServerConfig) -> Result<Request, ReadError> {
+    let head_end = match Request::find_head_end(raw) {
+        Some(end) if end > config.max_header_size => return Err(ReadError::HeaderTooLarge(config.max_header_size)),
+        Some(end) => end,
+        None if raw.len() > config.max_header_size => return Err(ReadError::HeaderTooLarge(config.max_header_size)),
+        None => return Err(RequestError::InvalidRequest("Incomplete request head".to_string()).into()),
+    };
+
+    let mut request = Request::from_head(&raw[..head_end])?;
+    let body = &raw[head_end..];
+
+    if request.is_chunked() {
+        let mut decoder = ChunkedDecoder::new(config.max_header_size, config.max_body_size);
+        decoder.decode(body)?;
+        if !decoder.is_complete() {
+            return Err(RequestError::InvalidRequest("Incomplete chunked body".to_string()).into());
+        }
+        request.body = decoder.into_body();
+        return Ok(request);
+    }
+
+    let content_length = request.content_length()?.unwrap_or(0);
+    if content_length > config.max_body_size {
+        return Err(ReadError::BodyTooLarge(content_length));
+    }
+    if body.len() < content_length {
+        return Err(RequestError::InvalidRequest("Body shorter than Content-Length".to_string()).into());
+    }
+    request.body = body[..content_length].to_vec();
+
+    Ok(request)
+}
+
+/// Writes the next `length` bytes of the connection, starting with those already in `buf`,
+/// to a new temporary file, leaving any bytes past the body in `buf`.
+async fn spool_body<R: AsyncRead + Unpin>(stream: &mut R, buf: &mut Vec<u8>, length: usize, limit: Duration) -> Result<TempFile, ReadError> {
+    let (mut body_file, file) = TempFile::new()?;
+    let mut file = tokio::fs::File::from_std(file);
+
+    let mut remaining = length;
+    loop {
+        let take = remaining.min(buf.len());
+        file.write_all(&buf[..take]).await?;
+        buf.drain(..take);
+        body_file.add_len(take);
+        remaining -= take;
+        if remaining == 0 {
+            break;
+        }
+        if read_more(stream, buf, limit).await? == 0 {
+            return Err(unexpected_eof().into());
+        }
+    }
+    file.flush().await?;
+
+    Ok(body_file)
+}
+
+/// Appends whatever the stream has available to `buf`, returning the number of bytes read.
+///
+/// Fails with `ReadError::Timeout` if nothing arrives within `limit`.
+async fn read_more<R: AsyncRead + Unpin>(stream: &mut R, buf: &mut Vec<u8>, limit: Duration) -> Result<usize, ReadError> {
+    buf.reserve(8 * 1024);
+    match timeout(limit, stream.read_buf(buf)).await {
+        Ok(read) => Ok(read?),
+        Err(_) => Err(ReadError::Timeout(limit)),
+    }
+}
+
+fn unexpected_eof() -> Error {
+    Error::new(ErrorKind::UnexpectedEof, "Connection closed in the middle of a request")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(raw: &str, config: &ServerConfig) -> Result<Request, ReadError> {
+        parse_request(raw.as_bytes(), config)
+    }
+
+    #[test]
+    fn parses_requests_held_in_memory() {
+        let config = ServerConfig::default();
+        let request = parse("POST /echo HTTP/1.1\r\nContent-Length: 5\r\n\r\nhello", &config).unwrap();
+        assert_eq!(request.path, "/echo");
+        assert_eq!(request.body, b"hello");
+
+        let request = parse("POST /echo HTTP/1.1\r\nTransfer-Encoding: chunked\r\n\r\n5\r\nhello\r\n0\r\n\r\n", &config).unwrap();
+        assert_eq!(request.body, b"hello");
+    }
+
+    #[test]
+    fn applies_the_config_limits_to_requests_held_in_memory() {
+        let config = ServerConfig::default().set_max_header_size(64).set_max_body_size(4);
+
+        let err = parse("POST /echo HTTP/1.1\r\nContent-Length: 5\r\n\r\nhello", &config).unwrap_err();
+        assert!(matches!(err, ReadError::BodyTooLarge(5)));
+        let err = parse("POST /echo HTTP/1.1\r\nTransfer-Encoding: chunked\r\n\r\n5\r\nhello\r\n0\r\n\r\n", &config).unwrap_err();
+        assert!(matches!(err, ReadError::BodyTooLarge(_)));
+
+        let long_header = format!("GET / HTTP/1.1\r\nX-Long: {}\r\n\r\n", "a".repeat(64));
+        assert!(matches!(parse(&long_header, &config), Err(ReadError::HeaderTooLarge(64))));
+    }
+
+    #[test]
+    fn rejects_ambiguous_or_truncated_requests_held_in_memory() {
+        let config = ServerConfig::default();
+        let both = "POST / HTTP/1.1\r\nTransfer-Encoding: chunked\r\nContent-Length: 5\r\n\r\n5\r\nhello\r\n0\r\n\r\n";
+        assert_eq!(parse(both, &config).unwrap_err().status_code(), Some(StatusCode::BadRequest));
+        let short = "POST / HTTP/1.1\r\nContent-Length: 10\r\n\r\nhello";
+        assert_eq!(parse(short, &config).unwrap_err().status_code(), Some(StatusCode::BadRequest));
+        let long_size = format!("POST / HTTP/1.1\r\nTransfer-Encoding: chunked\r\n\r\n{}\r\n", "1".repeat(20 * 1024));
+        assert_eq!(parse(&long_size, &config).unwrap_err().status_code(), Some(StatusCode::BadRequest));
+        let gzip = "POST / HTTP/1.1\r\nTransfer-Encoding: gzip, chunked\r\n\r\n0\r\n\r\n";
+        assert_eq!(parse(gzip, &config).unwrap_err().status_code(), Some(StatusCode::NotImplemented));
+    }
+}
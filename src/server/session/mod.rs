@@ -0,0 +1,6 @@
+#[allow(unused)]
+pub mod session_core;
+#[allow(unused)]
+pub mod session_store;
+#[allow(unused)]
+pub mod session_middleware;
\ No newline at end of file
@@ -0,0 +1,220 @@
+// src/server/session/session_core.rs
+
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex, MutexGuard};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+
+use crate::server::plugin::plugin_base::PluginError;
+use crate::util::crypto::random_bytes;
+use crate::util::encoding::hex_encode;
+
+/// Number of random bytes in a session identifier.
+const SESSION_ID_BYTES: usize = 16;
+
+/// The values stored in a session, keyed by name.
+pub type SessionValues = HashMap<String, serde_json::Value>;
+
+/// A session as persisted by a `SessionStore`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SessionRecord {
+    pub values: SessionValues,
+    /// Expiry time in seconds since the Unix epoch.
+    pub expires_at: u64,
+}
+
+impl SessionRecord {
+    /// Creates a record for `values` that expires `max_age` from now.
+    pub fn new(values: SessionValues, max_age: Duration) -> Self {
+        Self {
+            values,
+            expires_at: unix_now().saturating_add(max_age.as_secs()),
+        }
+    }
+
+    /// Returns `true` once the record's expiry time has passed.
+    pub fn is_expired(&self) -> bool {
+        self.expires_at <= unix_now()
+    }
+}
+
+#[derive(Debug)]
+struct SessionState {
+    id: String,
+    values: SessionValues,
+    is_new: bool,
+    modified: bool,
+    destroyed: bool,
+    previous_id: Option<String>,
+}
+
+/// Per-client state that persists across requests, attached to the request by `SessionMiddleware`.
+///
+/// Values are stored as JSON, so anything implementing `Serialize` and `DeserializeOwned` can be
+/// kept in a session. The handle is cheap to clone and all clones share the same state, which
+/// lets plugins update it through the `&Request` they receive.
+///
+/// ```ignore
+/// fn serve(&self, request: &Request) -> Result<Response, PluginError> {
+///     let session = request.session().ok_or_else(|| PluginError::Internal("No session".to_owned()))?;
+///     let step: u32 = session.get("wizard_step").unwrap_or(0);
+///     session.insert("wizard_step", &(step + 1))?;
+///     ...
+/// }
+/// ```
+#[derive(Debug, Clone)]
+pub struct Session {
+    state: Arc<Mutex<SessionState>>,
+}
+
+impl Session {
+    /// Creates an empty session with a fresh identifier.
+    pub(crate) fn new() -> Self {
+        Self::from_state(generate_session_id(), SessionValues::new(), true)
+    }
+
+    /// Wraps a session loaded from a store.
+    pub(crate) fn existing(id: String, values: SessionValues) -> Self {
+        Self::from_state(id, values, false)
+    }
+
+    fn from_state(id: String, values: SessionValues, is_new: bool) -> Self {
+        Self {
+            state: Arc::new(Mutex::new(SessionState {
+                id,
+                values,
+                is_new,
+                modified: false,
+                destroyed: false,
+                previous_id: None,
+            })),
+        }
+    }
+
+    fn lock(&self) -> MutexGuard<'_, SessionState> {
+        self.state.lock().unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+
+    /// Returns the session identifier.
+    pub fn id(&self) -> String {
+        self.lock().id.clone()
+    }
+
+    /// Returns `true` if the session was created for this request rather than loaded from the store.
+    pub fn is_new(&self) -> bool {
+        self.lock().is_new
+    }
+
+    /// Retrieves a value, or `None` if it is missing or does not deserialize as `T`.
+    pub fn get<T: DeserializeOwned>(&self, key: &str) -> Option<T> {
+        let state = self.lock();
+        let value = state.values.get(key)?.clone();
+        serde_json::from_value(value).ok()
+    }
+
+    /// Returns `true` if the session holds a value for `key`.
+    pub fn contains(&self, key: &str) -> bool {
+        self.lock().values.contains_key(key)
+    }
+
+    /// Stores a value, replacing any previous value for `key`.
+    pub fn insert<T: Serialize>(&self, key: &str, value: &T) -> Result<(), PluginError> {
+        let value = serde_json::to_value(value)
+            .map_err(|e| PluginError::Internal(format!("Cannot store session value {}: {}", key, e)))?;
+        let mut state = self.lock();
+        state.values.insert(key.to_string(), value);
+        state.modified = true;
+        Ok(())
+    }
+
+    /// Removes a value, returning `true` if it was present.
+    pub fn remove(&self, key: &str) -> bool {
+        let mut state = self.lock();
+        let removed = state.values.remove(key).is_some();
+        state.modified |= removed;
+        removed
+    }
+
+    /// Removes all values but keeps the session.
+    pub fn clear(&self) {
+        let mut state = self.lock();
+        state.modified |= !state.values.is_empty();
+        state.values.clear();
+    }
+
+    /// Deletes the session from the store and tells the client to drop its cookie.
+    pub fn destroy(&self) {
+        let mut state = self.lock();
+        state.values.clear();
+        state.destroyed = true;
+    }
+
+    /// Moves the session to a new identifier, keeping its values.
+    ///
+    /// Call this when the privilege level changes, for example after a login, so an identifier
+    /// learned before that point becomes useless.
+    pub fn renew(&self) {
+        let mut state = self.lock();
+        let old_id = std::mem::replace(&mut state.id, generate_session_id());
+        if !state.is_new && state.previous_id.is_none() {
+            state.previous_id = Some(old_id);
+        }
+        state.modified = true;
+    }
+
+    /// Returns what must happen to the session once the response has been produced.
+    pub(crate) fn outcome(&self) -> SessionOutcome {
+        let state = self.lock();
+        if state.destroyed {
+            let mut stale_ids: Vec<String> = state.previous_id.iter().cloned().collect();
+            if !state.is_new {
+                stale_ids.push(state.id.clone());
+            }
+            SessionOutcome::Destroy { stale_ids }
+        } else if state.modified && (!state.is_new || !state.values.is_empty()) {
+            SessionOutcome::Save {
+                id: state.id.clone(),
+                values: state.values.clone(),
+                stale_id: state.previous_id.clone(),
+            }
+        } else {
+            SessionOutcome::Keep
+        }
+    }
+}
+
+/// What `SessionMiddleware` does with a session after the request.
+#[derive(Debug)]
+pub(crate) enum SessionOutcome {
+    /// Nothing changed, or the session is new and empty.
+    Keep,
+    /// Persist the values under `id`, removing `stale_id` if the session was renewed.
+    Save {
+        id: String,
+        values: SessionValues,
+        stale_id: Option<String>,
+    },
+    /// Remove every identifier the session was stored under.
+    Destroy { stale_ids: Vec<String> },
+}
+
+/// Generates a random session identifier.
+pub fn generate_session_id() -> String {
+    hex_encode(&random_bytes(SESSION_ID_BYTES))
+}
+
+/// Returns `true` if `id` has the shape of an identifier made by `generate_session_id`.
+///
+/// Stores use this to reject identifiers that could escape their storage location.
+pub fn is_valid_session_id(id: &str) -> bool {
+    id.len() == SESSION_ID_BYTES * 2 && id.bytes().all(|b| b.is_ascii_hexdigit())
+}
+
+fn unix_now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|duration| duration.as_secs())
+        .unwrap_or(0)
+}
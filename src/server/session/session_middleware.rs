@@ -0,0 +1,376 @@
+// src/server/session/session_middleware.rs
+
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use crate::server::plugin::plugin_base::{PluginError, PluginFuture};
+use crate::server::plugin::plugin_middleware::{Middleware, Next};
+use crate::server::structs::structs_cookie::{Cookie, SameSite};
+use crate::server::structs::structs_request::Request;
+use crate::server::structs::structs_response::Response;
+use crate::util::crypto::{constant_time_eq, hmac_sha1, random_bytes};
+use crate::util::encoding::{hex_decode, hex_encode};
+use crate::util::logging::logln;
+
+use super::session_core::{is_valid_session_id, Session, SessionOutcome, SessionRecord};
+use super::session_store::SessionStore;
+
+/// How often expired sessions are purged from the store.
+const PURGE_INTERVAL: Duration = Duration::from_secs(600);
+
+/// Middleware that attaches a `Session` to every request.
+///
+/// The session identifier travels in a cookie signed with HMAC-SHA1, so clients cannot forge
+/// or guess identifiers. New sessions are only stored, and the cookie only sent, once a plugin
+/// puts a value in them. A session expires `max_age` after it was last modified.
+///
+/// ```ignore
+/// let sessions = SessionMiddleware::new(FileSessionStore::new("sessions")?)
+///     .set_secret(b"a long secret kept outside the source tree");
+/// plugin_manager.apply_middleware(Box::new(sessions));
+/// ```
+pub struct SessionMiddleware {
+    store: Arc<dyn SessionStore>,
+    secret: Vec<u8>,
+    cookie_name: String,
+    cookie_path: String,
+    max_age: Duration,
+    secure: bool,
+    last_purge: Mutex<Instant>,
+}
+
+impl SessionMiddleware {
+    /// Creates the middleware with a random signing secret, so sessions end when the application restarts.
+    ///
+    /// Use `set_secret` with a persistent secret to keep sessions from a `FileSessionStore` across restarts.
+    pub fn new<S: SessionStore>(store: S) -> Self {
+        Self {
+            store: Arc::new(store),
+            secret: random_bytes(32),
+            cookie_name: "smn_session".to_string(),
+            cookie_path: "/".to_string(),
+            max_age: Duration::from_secs(24 * 60 * 60),
+            secure: false,
+            last_purge: Mutex::new(Instant::now()),
+        }
+    }
+
+    pub fn set_secret(mut self, secret: &[u8]) -> Self {
+        self.secret = secret.to_vec();
+        self
+    }
+
+    pub fn set_cookie_name(mut self, cookie_name: &str) -> Self {
+        self.cookie_name = cookie_name.to_string();
+        self
+    }
+
+    pub fn set_cookie_path(mut self, cookie_path: &str) -> Self {
+        self.cookie_path = cookie_path.to_string();
+        self
+    }
+
+    pub fn set_max_age(mut self, max_age: Duration) -> Self {
+        self.max_age = max_age;
+        self
+    }
+
+    /// Marks the cookie `Secure`, for applications served over HTTPS.
+    pub fn set_secure(mut self, secure: bool) -> Self {
+        self.secure = secure;
+        self
+    }
+
+    /// Returns the cookie value for `id`: the identifier followed by its signature.
+    fn sign(&self, id: &str) -> String {
+        format!("{}.{}", id, hex_encode(&hmac_sha1(&self.secret, id.as_bytes())))
+    }
+
+    /// Returns the session identifier from a cookie value if its signature is valid.
+    fn verify(&self, value: &str) -> Option<String> {
+        let (id, signature) = value.split_once('.')?;
+        let signature = hex_decode(signature)?;
+        let expected = hmac_sha1(&self.secret, id.as_bytes());
+        if is_valid_session_id(id) && constant_time_eq(&signature, &expected) {
+            Some(id.to_string())
+        } else {
+            None
+        }
+    }
+
+    fn session_cookie(&self, id: &str) -> Cookie {
+        Cookie::new(&self.cookie_name, &self.sign(id))
+            .set_path(&self.cookie_path)
+            .set_max_age(self.max_age)
+            .set_http_only(true)
+            .set_secure(self.secure)
+            .set_same_site(SameSite::Lax)
+    }
+
+    /// Runs a store operation on a blocking thread.
+    async fn with_store<T, F>(&self, operation: F) -> Result<T, PluginError>
+    where
+        T: Send + 'static,
+        F: FnOnce(&dyn SessionStore) -> std::io::Result<T> + Send + 'static,
+    {
+        let store = Arc::clone(&self.store);
+        tokio::task::spawn_blocking(move || operation(store.as_ref()))
+            .await
+            .map_err(|e| PluginError::Internal(format!("Session store task failed: {}", e)))?
+            .map_err(PluginError::from)
+    }
+
+    /// Purges expired sessions if `PURGE_INTERVAL` has passed since the last purge.
+    ///
+    /// A failed purge is only logged, since it does not concern the request that triggered it.
+    async fn purge_if_due(&self) {
+        {
+            let mut last_purge = self.last_purge.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+            if last_purge.elapsed() < PURGE_INTERVAL {
+                return;
+            }
+            *last_purge = Instant::now();
+        }
+        if let Err(e) = self.with_store(|store| store.purge_expired()).await {
+            logln(&format!("Failed to purge expired sessions: {}", e));
+        }
+    }
+
+    /// Loads the session named by the request cookie, or starts a new one.
+    async fn load(&self, request: &Request) -> Result<Session, PluginError> {
+        let id = match request.cookie(&self.cookie_name).and_then(|value| self.verify(value)) {
+            Some(id) => id,
+            None => return Ok(Session::new()),
+        };
+
+        let lookup_id = id.clone();
+        match self.with_store(move |store| store.load(&lookup_id)).await? {
+            Some(record) if !record.is_expired() => Ok(Session::existing(id, record.values)),
+            Some(_) => {
+                let stale_id = id.clone();
+                self.with_store(move |store| store.remove(&stale_id)).await?;
+                Ok(Session::new())
+            }
+            None => Ok(Session::new()),
+        }
+    }
+
+    /// Persists the session after the request and sets or clears the cookie on the response.
+    async fn commit(&self, session: &Session, had_cookie: bool, response: &mut Response) -> Result<(), PluginError> {
+        match session.outcome() {
+            SessionOutcome::Keep => {}
+            SessionOutcome::Save { id, values, stale_id } => {
+                let record = SessionRecord::new(values, self.max_age);
+                let save_id = id.clone();
+                self.with_store(move |store| {
+                    if let Some(stale_id) = stale_id {
+                        store.remove(&stale_id)?;
+                    }
+                    store.save(&save_id, &record)
+                })
+                .await?;
+                response.set_cookie(self.session_cookie(&id))?;
+            }
+            SessionOutcome::Destroy { stale_ids } => {
+                self.with_store(move |store| stale_ids.iter().try_for_each(|id| store.remove(id)))
+                    .await?;
+                if had_cookie {
+                    response.set_cookie(Cookie::removal(&self.cookie_name, &self.cookie_path))?;
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+impl Middleware for SessionMiddleware {
+    fn handle<'a>(&'a self, mut request: Request, next: Next<'a>) -> PluginFuture<'a> {
+        Box::pin(async move {
+            self.purge_if_due().await;
+
+            let had_cookie = request.cookie(&self.cookie_name).is_some();
+            let session = self.load(&request).await?;
+            request.session = Some(session.clone());
+
+            let mut response = next.run(request).await?;
+            self.commit(&session, had_cookie, &mut response).await?;
+            Ok(response)
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::server::plugin::plugin_manager::PluginManager;
+    use crate::server::session::session_core::SessionValues;
+    use crate::server::session::session_store::MemorySessionStore;
+    use crate::server::structs::structs_mime::Mime;
+
+    fn plugin_manager() -> PluginManager {
+        let mut plugin_manager = PluginManager::new();
+        let session = |request: &Request| request.session.clone().expect("SessionMiddleware sets the session");
+        plugin_manager
+            .route("GET", "/whoami", move |request| {
+                let user: String = session(request).get("user").unwrap_or_else(|| "nobody".to_string());
+                Ok(Response::response_ok(user.into_bytes(), Mime::TextPlain))
+            })
+            .unwrap();
+        plugin_manager
+            .route("GET", "/login", move |request| {
+                session(request).insert("user", &"ada")?;
+                Ok(Response::response_no_content())
+            })
+            .unwrap();
+        plugin_manager
+            .route("GET", "/renew", move |request| {
+                session(request).renew();
+                Ok(Response::response_no_content())
+            })
+            .unwrap();
+        plugin_manager
+            .route("GET", "/logout", move |request| {
+                session(request).destroy();
+                Ok(Response::response_no_content())
+            })
+            .unwrap();
+        plugin_manager
+    }
+
+    fn sessions() -> SessionMiddleware {
+        SessionMiddleware::new(MemorySessionStore::new()).set_secret(b"test secret")
+    }
+
+    async fn send(sessions: &SessionMiddleware, plugin_manager: &PluginManager, path: &str, cookie: Option<&str>) -> Response {
+        let cookie = cookie.map_or(String::new(), |value| format!("Cookie: smn_session={}\r\n", value));
+        let request = Request::from_string(&format!("GET {} HTTP/1.1\r\n{}\r\n", path, cookie)).unwrap();
+        sessions.handle(request, Next::new(&[], plugin_manager)).await.unwrap()
+    }
+
+    fn session_cookie(response: &Response) -> Option<&Cookie> {
+        response.cookies.iter().find(|cookie| cookie.name == "smn_session")
+    }
+
+    #[test]
+    fn verifies_signed_ids() {
+        let sessions = sessions();
+        let id = "0123456789abcdef".repeat(2);
+        let value = sessions.sign(&id);
+        assert_eq!(sessions.verify(&value), Some(id.clone()));
+
+        let other = SessionMiddleware::new(MemorySessionStore::new()).set_secret(b"other secret");
+        assert_eq!(other.verify(&value), None);
+    }
+
+    #[test]
+    fn rejects_tampered_cookies() {
+        let sessions = sessions();
+        let id = "0123456789abcdef".repeat(2);
+        let value = sessions.sign(&id);
+        let (_, signature) = value.split_once('.').unwrap();
+
+        let mut tampered = value.clone();
+        let last = if tampered.ends_with('0') { "1" } else { "0" };
+        tampered.replace_range(tampered.len() - 1.., last);
+        assert_eq!(sessions.verify(&tampered), None);
+
+        let other_id = "f".repeat(32);
+        assert_eq!(sessions.verify(&format!("{}.{}", other_id, signature)), None);
+        assert_eq!(sessions.verify(&id), None);
+        assert_eq!(sessions.verify(&format!("{}.zz", id)), None);
+
+        // A malformed id is rejected even with a valid signature for it
+        let malformed = sessions.sign("../../etc/passwd");
+        assert_eq!(sessions.verify(&malformed), None);
+    }
+
+    #[tokio::test]
+    async fn keeps_sessions_across_requests() {
+        let sessions = sessions();
+        let plugin_manager = plugin_manager();
+
+        let response = send(&sessions, &plugin_manager, "/login", None).await;
+        let cookie = session_cookie(&response).expect("session cookie is set").value.clone();
+
+        let response = send(&sessions, &plugin_manager, "/whoami", Some(&cookie)).await;
+        assert_eq!(response.body, b"ada");
+        assert!(session_cookie(&response).is_none());
+    }
+
+    #[tokio::test]
+    async fn does_not_set_a_cookie_for_empty_sessions() {
+        let sessions = sessions();
+        let plugin_manager = plugin_manager();
+
+        let response = send(&sessions, &plugin_manager, "/whoami", None).await;
+        assert_eq!(response.body, b"nobody");
+        assert!(response.cookies.is_empty());
+
+        // Renewing an empty session does not store it either
+        let response = send(&sessions, &plugin_manager, "/renew", None).await;
+        assert!(response.cookies.is_empty());
+    }
+
+    #[tokio::test]
+    async fn ignores_tampered_cookies() {
+        let sessions = sessions();
+        let plugin_manager = plugin_manager();
+
+        let response = send(&sessions, &plugin_manager, "/login", None).await;
+        let cookie = session_cookie(&response).unwrap().value.clone();
+        let forged = format!("{}.{}", "f".repeat(32), cookie.split_once('.').unwrap().1);
+
+        let response = send(&sessions, &plugin_manager, "/whoami", Some(&forged)).await;
+        assert_eq!(response.body, b"nobody");
+    }
+
+    #[tokio::test]
+    async fn renew_moves_the_session_to_a_new_id() {
+        let sessions = sessions();
+        let plugin_manager = plugin_manager();
+
+        let response = send(&sessions, &plugin_manager, "/login", None).await;
+        let old_cookie = session_cookie(&response).unwrap().value.clone();
+
+        let response = send(&sessions, &plugin_manager, "/renew", Some(&old_cookie)).await;
+        let new_cookie = session_cookie(&response).expect("renewed session cookie is set").value.clone();
+        assert_ne!(new_cookie.split_once('.').unwrap().0, old_cookie.split_once('.').unwrap().0);
+
+        let response = send(&sessions, &plugin_manager, "/whoami", Some(&new_cookie)).await;
+        assert_eq!(response.body, b"ada");
+        let response = send(&sessions, &plugin_manager, "/whoami", Some(&old_cookie)).await;
+        assert_eq!(response.body, b"nobody");
+    }
+
+    #[tokio::test]
+    async fn destroy_clears_the_cookie() {
+        let sessions = sessions();
+        let plugin_manager = plugin_manager();
+
+        let response = send(&sessions, &plugin_manager, "/login", None).await;
+        let cookie = session_cookie(&response).unwrap().value.clone();
+
+        let response = send(&sessions, &plugin_manager, "/logout", Some(&cookie)).await;
+        let removal = session_cookie(&response).expect("removal cookie is set");
+        assert_eq!(removal.value, "");
+        assert_eq!(removal.max_age, Some(Duration::ZERO));
+
+        let response = send(&sessions, &plugin_manager, "/whoami", Some(&cookie)).await;
+        assert_eq!(response.body, b"nobody");
+    }
+
+    #[tokio::test]
+    async fn treats_expired_sessions_as_new() {
+        let sessions = sessions();
+        let plugin_manager = plugin_manager();
+
+        let id = "a".repeat(32);
+        let mut values = SessionValues::new();
+        values.insert("user".to_string(), serde_json::json!("ada"));
+        sessions.store.save(&id, &SessionRecord { values, expires_at: 0 }).unwrap();
+
+        let response = send(&sessions, &plugin_manager, "/whoami", Some(&sessions.sign(&id))).await;
+        assert_eq!(response.body, b"nobody");
+        assert!(sessions.store.load(&id).unwrap().is_none());
+    }
+}
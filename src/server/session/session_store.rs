@@ -0,0 +1,193 @@
+// src/server/session/session_store.rs
+
+use std::collections::HashMap;
+use std::fs;
+use std::io::{Error, ErrorKind, Result};
+use std::path::PathBuf;
+use std::sync::Mutex;
+
+use super::session_core::{is_valid_session_id, SessionRecord};
+
+/// Backend that persists sessions between requests.
+///
+/// Methods are called from a blocking thread, so implementations may perform file or
+/// network I/O directly.
+pub trait SessionStore: Send + Sync + 'static {
+    /// Loads a session, returning `None` if the store has no record for `id`.
+    fn load(&self, id: &str) -> Result<Option<SessionRecord>>;
+
+    /// Stores a session, replacing any previous record for `id`.
+    fn save(&self, id: &str, record: &SessionRecord) -> Result<()>;
+
+    /// Removes a session. Removing an unknown `id` is not an error.
+    fn remove(&self, id: &str) -> Result<()>;
+
+    /// Removes every expired session.
+    fn purge_expired(&self) -> Result<()>;
+}
+
+/// Keeps sessions in memory; they are lost when the application exits.
+#[derive(Debug, Default)]
+pub struct MemorySessionStore {
+    sessions: Mutex<HashMap<String, SessionRecord>>,
+}
+
+impl MemorySessionStore {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    fn sessions(&self) -> std::sync::MutexGuard<'_, HashMap<String, SessionRecord>> {
+        self.sessions.lock().unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+}
+
+impl SessionStore for MemorySessionStore {
+    fn load(&self, id: &str) -> Result<Option<SessionRecord>> {
+        Ok(self.sessions().get(id).cloned())
+    }
+
+    fn save(&self, id: &str, record: &SessionRecord) -> Result<()> {
+        self.sessions().insert(id.to_string(), record.clone());
+        Ok(())
+    }
+
+    fn remove(&self, id: &str) -> Result<()> {
+        self.sessions().remove(id);
+        Ok(())
+    }
+
+    fn purge_expired(&self) -> Result<()> {
+        self.sessions().retain(|_, record| !record.is_expired());
+        Ok(())
+    }
+}
+
+/// Prefix of the session files in a `FileSessionStore` directory, so other files are left alone.
+const SESSION_FILE_PREFIX: &str = "smn_session-";
+
+/// Keeps each session as a JSON file in a directory, so sessions survive restarts.
+///
+/// Files are named `smn_session-{id}.json`; other files in the directory are never touched.
+#[derive(Debug)]
+pub struct FileSessionStore {
+    dir: PathBuf,
+}
+
+impl FileSessionStore {
+    /// Creates a store in `dir`, creating the directory if needed.
+    pub fn new<P: Into<PathBuf>>(dir: P) -> Result<Self> {
+        let dir = dir.into();
+        fs::create_dir_all(&dir)?;
+        Ok(Self { dir })
+    }
+
+    fn path_for(&self, id: &str) -> Result<PathBuf> {
+        if !is_valid_session_id(id) {
+            return Err(Error::new(ErrorKind::InvalidInput, format!("Invalid session id: {}", id)));
+        }
+        Ok(self.dir.join(format!("{}{}.json", SESSION_FILE_PREFIX, id)))
+    }
+}
+
+impl SessionStore for FileSessionStore {
+    fn load(&self, id: &str) -> Result<Option<SessionRecord>> {
+        let content = match fs::read(self.path_for(id)?) {
+            Ok(content) => content,
+            Err(e) if e.kind() == ErrorKind::NotFound => return Ok(None),
+            Err(e) => return Err(e),
+        };
+        // A corrupt file is treated as a missing session rather than failing every request
+        Ok(serde_json::from_slice(&content).ok())
+    }
+
+    fn save(&self, id: &str, record: &SessionRecord) -> Result<()> {
+        let path = self.path_for(id)?;
+        let content = serde_json::to_vec(record).map_err(|e| Error::new(ErrorKind::InvalidData, e))?;
+
+        // Write to a temporary file first so a crash never leaves a half-written session
+        let temp_path = path.with_extension("json.tmp");
+        fs::write(&temp_path, content)?;
+        fs::rename(&temp_path, &path)
+    }
+
+    fn remove(&self, id: &str) -> Result<()> {
+        match fs::remove_file(self.path_for(id)?) {
+            Err(e) if e.kind() != ErrorKind::NotFound => Err(e),
+            _ => Ok(()),
+        }
+    }
+
+    fn purge_expired(&self) -> Result<()> {
+        for entry in fs::read_dir(&self.dir)? {
+            let path = entry?.path();
+            let is_session_file = path
+                .file_name()
+                .and_then(|name| name.to_str())
+                .and_then(|name| name.strip_prefix(SESSION_FILE_PREFIX))
+                .and_then(|name| name.strip_suffix(".json"))
+                .is_some_and(is_valid_session_id);
+            if !is_session_file {
+                continue;
+            }
+            // Files that cannot be read as a record are kept, as they may not be ours
+            let expired = fs::read(&path)
+                .ok()
+                .and_then(|content| serde_json::from_slice::<SessionRecord>(&content).ok())
+                .is_some_and(|record| record.is_expired());
+            if expired {
+                let _ = fs::remove_file(&path);
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::*;
+    use crate::server::session::session_core::SessionValues;
+
+    #[test]
+    fn memory_store_purges_expired_sessions() {
+        let store = MemorySessionStore::new();
+        let live_id = "0".repeat(32);
+        let expired_id = "1".repeat(32);
+        store.save(&live_id, &SessionRecord::new(SessionValues::new(), Duration::from_secs(60))).unwrap();
+        store.save(&expired_id, &SessionRecord { values: SessionValues::new(), expires_at: 0 }).unwrap();
+        assert!(!store.load(&live_id).unwrap().unwrap().is_expired());
+        assert!(store.load(&expired_id).unwrap().unwrap().is_expired());
+
+        store.purge_expired().unwrap();
+
+        assert!(store.load(&live_id).unwrap().is_some());
+        assert!(store.load(&expired_id).unwrap().is_none());
+        store.remove(&live_id).unwrap();
+        assert!(store.load(&live_id).unwrap().is_none());
+    }
+
+    #[test]
+    fn purge_only_removes_expired_session_files() {
+        let dir = std::env::temp_dir().join(format!("smn_view-sessions-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        let store = FileSessionStore::new(&dir).unwrap();
+
+        let live_id = "0".repeat(32);
+        let expired_id = "1".repeat(32);
+        store.save(&live_id, &SessionRecord::new(SessionValues::new(), Duration::from_secs(60))).unwrap();
+        store.save(&expired_id, &SessionRecord { values: SessionValues::new(), expires_at: 0 }).unwrap();
+        let expired_record = serde_json::to_vec(&SessionRecord { values: SessionValues::new(), expires_at: 0 }).unwrap();
+        fs::write(dir.join("settings.json"), &expired_record).unwrap();
+        fs::write(dir.join(format!("smn_session-{}.json", "2".repeat(32))), b"not a record").unwrap();
+
+        store.purge_expired().unwrap();
+
+        assert!(store.load(&live_id).unwrap().is_some());
+        assert!(store.load(&expired_id).unwrap().is_none());
+        assert!(dir.join("settings.json").exists());
+        assert!(dir.join(format!("smn_session-{}.json", "2".repeat(32))).exists());
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
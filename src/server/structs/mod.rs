@@ -6,3 +6,15 @@ pub mod structs_response;
 pub mod structs_header;
 #[allow(unused)]
 pub mod structs_mime;
+#[allow(unused)]
+pub mod structs_config;
+#[allow(unused)]
+pub mod structs_body;
+#[allow(unused)]
+pub mod structs_form;
+#[allow(unused)]
+pub mod structs_cookie;
+#[allow(unused)]
+pub mod structs_headermap;
+#[allow(unused)]
+pub mod structs_event;
\ No newline at end of file
@@ -0,0 +1,85 @@
+// src/server/structs/structs_body.rs
+
+use std::fmt;
+use std::io::{Error, ErrorKind, Result, Write};
+
+use tokio::sync::mpsc;
+
+/// Number of chunks that may be queued before the writer has to wait for the connection.
+const BODY_CHANNEL_CAPACITY: usize = 16;
+
+/// Creates a connected writer/stream pair for an incrementally produced response body.
+pub fn body_channel() -> (BodyWriter, BodyStream) {
+    let (sender, receiver) = mpsc::channel(BODY_CHANNEL_CAPACITY);
+    (BodyWriter { sender }, BodyStream { receiver })
+}
+
+/// The receiving half of a streamed response body, drained by the server.
+pub struct BodyStream {
+    receiver: mpsc::Receiver<Vec<u8>>,
+}
+
+impl BodyStream {
+    /// Waits for the next chunk. Returns `None` once every `BodyWriter` has been dropped.
+    pub async fn next_chunk(&mut self) -> Option<Vec<u8>> {
+        self.receiver.recv().await
+    }
+
+    /// Blocking variant of `next_chunk`. Must not be called from an async context.
+    pub fn blocking_next_chunk(&mut self) -> Option<Vec<u8>> {
+        self.receiver.blocking_recv()
+    }
+}
+
+impl fmt::Debug for BodyStream {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("BodyStream").finish_non_exhaustive()
+    }
+}
+
+/// The sending half of a streamed response body. Each write is emitted as one chunk;
+/// dropping every clone of the writer ends the body.
+#[derive(Clone)]
+pub struct BodyWriter {
+    sender: mpsc::Sender<Vec<u8>>,
+}
+
+impl BodyWriter {
+    /// Queues `data` as the next chunk, waiting if the connection is behind.
+    pub async fn send(&self, data: impl Into<Vec<u8>>) -> Result<()> {
+        let data = data.into();
+        if data.is_empty() {
+            return Ok(());
+        }
+        self.sender.send(data).await.map_err(|_| closed_error())
+    }
+
+    /// Blocking variant of `send`. Must not be called from an async context.
+    pub fn blocking_send(&self, data: impl Into<Vec<u8>>) -> Result<()> {
+        let data = data.into();
+        if data.is_empty() {
+            return Ok(());
+        }
+        self.sender.blocking_send(data).map_err(|_| closed_error())
+    }
+
+    /// Returns `true` if the client has gone away and further writes will fail.
+    pub fn is_closed(&self) -> bool {
+        self.sender.is_closed()
+    }
+}
+
+impl Write for BodyWriter {
+    fn write(&mut self, buf: &[u8]) -> Result<usize> {
+        self.blocking_send(buf)?;
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> Result<()> {
+        Ok(())
+    }
+}
+
+fn closed_error() -> Error {
+    Error::new(ErrorKind::BrokenPipe, "Response body stream closed")
+}
@@ -0,0 +1,121 @@
+// src/server/structs/structs_config.rs
+
+use std::sync::Arc;
+use std::time::Duration;
+
+use super::structs_form::DEFAULT_FILE_THRESHOLD;
+
+/// Callback run on the server thread when the server changes state.
+pub type LifecycleCallback = Arc<dyn Fn() + Send + Sync>;
+
+/// Limits and tuning options for the HTTP server.
+pub struct ServerConfig {
+    /// Maximum size in bytes of the request line and headers.
+    pub max_header_size: usize,
+    /// Maximum size in bytes of a request body.
+    pub max_body_size: usize,
+    /// Multipart bodies with a larger `Content-Length` are written to a temporary file as
+    /// they arrive instead of being held in memory.
+    pub multipart_spool_size: usize,
+    /// How long a connection may wait for the first byte of its next request before it is closed.
+    pub idle_timeout: Duration,
+    /// How long each read may wait once a request has started arriving, before the
+    /// request is answered with `408 Request Timeout`.
+    pub read_timeout: Duration,
+    /// Maximum size in bytes of a WebSocket message, after reassembling its fragments.
+    pub max_message_size: usize,
+    /// How long shutdown waits for in-flight requests before cutting them off.
+    pub shutdown_timeout: Duration,
+    /// How long a `ProtocolHandler` waits for a response before answering `504 Gateway Timeout`.
+    pub response_timeout: Duration,
+    /// Gzip-compresses textual bodies of at least `server_compression::COMPRESSION_MIN_SIZE` bytes for clients
+    /// that accept it. Only applies to the TCP server.
+    pub compression: bool,
+    /// Run once the server accepts requests.
+    pub on_started: Option<LifecycleCallback>,
+    /// Run when shutdown begins, before open connections are finished.
+    pub on_stopping: Option<LifecycleCallback>,
+    /// Run once the server has shut down.
+    pub on_stopped: Option<LifecycleCallback>,
+}
+
+impl Default for ServerConfig {
+    fn default() -> Self {
+        Self {
+            max_header_size: 16 * 1024,
+            max_body_size: 16 * 1024 * 1024,
+            multipart_spool_size: DEFAULT_FILE_THRESHOLD,
+            idle_timeout: Duration::from_secs(30),
+            read_timeout: Duration::from_secs(10),
+            max_message_size: 16 * 1024 * 1024,
+            shutdown_timeout: Duration::from_secs(10),
+            response_timeout: Duration::from_secs(30),
+            compression: true,
+            on_started: None,
+            on_stopping: None,
+            on_stopped: None,
+        }
+    }
+}
+
+impl ServerConfig {
+    pub fn set_max_header_size(mut self, max_header_size: usize) -> Self {
+        self.max_header_size = max_header_size;
+        self
+    }
+
+    pub fn set_max_body_size(mut self, max_body_size: usize) -> Self {
+        self.max_body_size = max_body_size;
+        self
+    }
+
+    pub fn set_multipart_spool_size(mut self, multipart_spool_size: usize) -> Self {
+        self.multipart_spool_size = multipart_spool_size;
+        self
+    }
+
+    pub fn set_idle_timeout(mut self, idle_timeout: Duration) -> Self {
+        self.idle_timeout = idle_timeout;
+        self
+    }
+
+    pub fn set_read_timeout(mut self, read_timeout: Duration) -> Self {
+        self.read_timeout = read_timeout;
+        self
+    }
+
+    pub fn set_max_message_size(mut self, max_message_size: usize) -> Self {
+        self.max_message_size = max_message_size;
+        self
+    }
+
+    pub fn set_shutdown_timeout(mut self, shutdown_timeout: Duration) -> Self {
+        self.shutdown_timeout = shutdown_timeout;
+        self
+    }
+
+    pub fn set_response_timeout(mut self, response_timeout: Duration) -> Self {
+        self.response_timeout = response_timeout;
+        self
+    }
+
+    pub fn set_compression(mut self, compression: bool) -> Self {
+        self.compression = compression;
+        self
+    }
+
+    pub fn set_on_started<F: Fn() + Send + Sync + 'static>(mut self, callback: F) -> Self {
+        self.on_started = Some(Arc::new(callback));
+        self
+    }
+
+    pub fn set_on_stopping<F: Fn() + Send + Sync + 'static>(mut self, callback: F) -> Self {
+        self.on_stopping = Some(Arc::new(callback));
+        self
+    }
+
+    pub fn set_on_stopped<F: Fn() + Send + Sync + 'static>(mut self, callback: F) -> Self {
+        self.on_stopped = Some(Arc::new(callback));
+        self
+    }
+}
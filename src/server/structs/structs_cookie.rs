@@ -0,0 +1,290 @@
+// src/server/structs/structs_cookie.rs
+
+use std::collections::HashMap;
+use std::time::{Duration, SystemTime};
+
+use crate::util::time::http_date;
+
+use super::structs_headermap::is_token;
+use super::structs_response::ResponseError;
+
+/// The `SameSite` attribute of a cookie.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SameSite {
+    Strict,
+    Lax,
+    None,
+}
+
+impl SameSite {
+    pub fn to_string(&self) -> &str {
+        match self {
+            SameSite::Strict => "Strict",
+            SameSite::Lax => "Lax",
+            SameSite::None => "None",
+        }
+    }
+}
+
+/// A cookie to send to the client with `Set-Cookie`.
+///
+/// ```ignore
+/// let cookie = Cookie::new("theme", "dark")
+///     .set_path("/")
+///     .set_max_age(Duration::from_secs(3600))
+///     .set_http_only(true)
+///     .set_same_site(SameSite::Lax);
+/// response.set_cookie(cookie)?;
+/// ```
+///
+/// The value is sent as is, so it must consist of cookie-octets (RFC 6265 §4.1.1); encode
+/// anything else, for example as base64 or percent-escapes, before building the cookie.
+#[derive(Debug, Clone)]
+pub struct Cookie {
+    pub name: String,
+    pub value: String,
+    pub path: Option<String>,
+    pub domain: Option<String>,
+    pub max_age: Option<Duration>,
+    pub expires: Option<SystemTime>,
+    pub http_only: bool,
+    pub secure: bool,
+    pub same_site: Option<SameSite>,
+}
+
+impl Cookie {
+    /// Creates a session cookie with the given name and value and no attributes.
+    pub fn new(name: &str, value: &str) -> Self {
+        Self {
+            name: name.to_string(),
+            value: value.to_string(),
+            path: None,
+            domain: None,
+            max_age: None,
+            expires: None,
+            http_only: false,
+            secure: false,
+            same_site: None,
+        }
+    }
+
+    /// Creates a cookie that tells the client to delete `name` on the given path.
+    pub fn removal(name: &str, path: &str) -> Self {
+        Self::new(name, "")
+            .set_path(path)
+            .set_max_age(Duration::ZERO)
+            .set_expires(SystemTime::UNIX_EPOCH)
+    }
+
+    pub fn set_path(mut self, path: &str) -> Self {
+        self.path = Some(path.to_string());
+        self
+    }
+
+    pub fn set_domain(mut self, domain: &str) -> Self {
+        self.domain = Some(domain.to_string());
+        self
+    }
+
+    pub fn set_max_age(mut self, max_age: Duration) -> Self {
+        self.max_age = Some(max_age);
+        self
+    }
+
+    pub fn set_expires(mut self, expires: SystemTime) -> Self {
+        self.expires = Some(expires);
+        self
+    }
+
+    pub fn set_http_only(mut self, http_only: bool) -> Self {
+        self.http_only = http_only;
+        self
+    }
+
+    pub fn set_secure(mut self, secure: bool) -> Self {
+        self.secure = secure;
+        self
+    }
+
+    pub fn set_same_site(mut self, same_site: SameSite) -> Self {
+        self.same_site = Some(same_site);
+        self
+    }
+
+    /// Checks that the cookie can be sent without altering the `Set-Cookie` header around it.
+    ///
+    /// # Returns
+    /// * `Result<(), ResponseError>` - `ResponseError::InvalidCookie` if the name is not a
+    ///   token, the value holds characters outside cookie-octets, the path or domain
+    ///   contains `;` or control characters, or `SameSite=None` is set without `Secure`,
+    ///   which browsers reject.
+    pub fn validate(&self) -> Result<(), ResponseError> {
+        if !is_token(&self.name) {
+            return Err(ResponseError::InvalidCookie(format!("Invalid cookie name: {:?}", self.name)));
+        }
+        let value = self
+            .value
+            .strip_prefix('"')
+            .and_then(|value| value.strip_suffix('"'))
+            .unwrap_or(&self.value);
+        if !value.bytes().all(is_cookie_octet) {
+            return Err(ResponseError::InvalidCookie(format!("Invalid value for cookie '{}'", self.name)));
+        }
+        if self.path.as_deref().is_some_and(|path| !is_attribute_value(path)) {
+            return Err(ResponseError::InvalidCookie(format!("Invalid path for cookie '{}'", self.name)));
+        }
+        if self.domain.as_deref().is_some_and(|domain| !is_domain(domain)) {
+            return Err(ResponseError::InvalidCookie(format!("Invalid domain for cookie '{}'", self.name)));
+        }
+        if self.same_site == Some(SameSite::None) && !self.secure {
+            return Err(ResponseError::InvalidCookie(format!("Cookie '{}' has SameSite=None without Secure", self.name)));
+        }
+        Ok(())
+    }
+
+    /// Formats the cookie as the value of a `Set-Cookie` header.
+    ///
+    /// # Returns
+    /// * `Result<String, ResponseError>` - The header value, or the error from `validate`.
+    pub fn to_header_value(&self) -> Result<String, ResponseError> {
+        self.validate()?;
+        let mut value = format!("{}={}", self.name, self.value);
+
+        if let Some(path) = &self.path {
+            value.push_str(&format!("; Path={}", path));
+        }
+        if let Some(domain) = &self.domain {
+            value.push_str(&format!("; Domain={}", domain));
+        }
+        if let Some(max_age) = self.max_age {
+            value.push_str(&format!("; Max-Age={}", max_age.as_secs()));
+        }
+        if let Some(expires) = self.expires {
+            value.push_str(&format!("; Expires={}", http_date(expires)));
+        }
+        if self.http_only {
+            value.push_str("; HttpOnly");
+        }
+        if self.secure {
+            value.push_str("; Secure");
+        }
+        if let Some(same_site) = self.same_site {
+            value.push_str(&format!("; SameSite={}", same_site.to_string()));
+        }
+
+        Ok(value)
+    }
+
+    /// Parses the `name=value` pairs of a request `Cookie` header.
+    ///
+    /// Pairs without `=` are skipped, surrounding quotes are removed from values, and for a
+    /// repeated name the first occurrence wins, as it is the most specific one.
+    pub fn parse_header(header: &str) -> HashMap<String, String> {
+        let mut cookies = HashMap::new();
+        for pair in header.split(';') {
+            if let Some((name, value)) = pair.split_once('=') {
+                let name = name.trim();
+                if name.is_empty() {
+                    continue;
+                }
+                let value = value.trim();
+                let value = value
+                    .strip_prefix('"')
+                    .and_then(|value| value.strip_suffix('"'))
+                    .unwrap_or(value);
+                cookies.entry(name.to_string()).or_insert_with(|| value.to_string());
+            }
+        }
+        cookies
+    }
+}
+
+/// Matches the cookie-octet rule: printable ASCII except whitespace, `"`, `,`, `;` and `\`.
+fn is_cookie_octet(b: u8) -> bool {
+    matches!(b, 0x21 | 0x23..=0x2B | 0x2D..=0x3A | 0x3C..=0x5B | 0x5D..=0x7E)
+}
+
+/// Matches an attribute value such as `Path`: any character except `;` and controls.
+fn is_attribute_value(value: &str) -> bool {
+    !value.chars().any(|c| c == ';' || c.is_ascii_control())
+}
+
+/// Matches a host name, optionally with the leading dot older clients expect.
+fn is_domain(domain: &str) -> bool {
+    let host = domain.strip_prefix('.').unwrap_or(domain);
+    !host.is_empty() && host.bytes().all(|b| b.is_ascii_alphanumeric() || b == b'-' || b == b'.')
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn formats_all_attributes() {
+        let cookie = Cookie::new("theme", "dark")
+            .set_path("/app")
+            .set_domain("example.com")
+            .set_max_age(Duration::from_secs(3600))
+            .set_expires(SystemTime::UNIX_EPOCH)
+            .set_http_only(true)
+            .set_secure(true)
+            .set_same_site(SameSite::Lax);
+        assert_eq!(
+            cookie.to_header_value().unwrap(),
+            "theme=dark; Path=/app; Domain=example.com; Max-Age=3600; \
+             Expires=Thu, 01 Jan 1970 00:00:00 GMT; HttpOnly; Secure; SameSite=Lax"
+        );
+    }
+
+    #[test]
+    fn accepts_empty_and_quoted_values() {
+        assert!(Cookie::removal("sid", "/").validate().is_ok());
+        assert!(Cookie::new("sid", "\"abc\"").validate().is_ok());
+        assert!(Cookie::new("sid", "a.b-c_d~e=").validate().is_ok());
+    }
+
+    #[test]
+    fn rejects_invalid_names() {
+        for name in ["", "a b", "a=b", "a;b", "a\r\nb"] {
+            assert!(Cookie::new(name, "v").to_header_value().is_err(), "{:?}", name);
+        }
+    }
+
+    #[test]
+    fn rejects_values_outside_cookie_octets() {
+        for value in ["a b", "a;b", "a,b", "a\\b", "a\"b", "a\r\nSet-Cookie: x=y", "é"] {
+            assert!(Cookie::new("sid", value).validate().is_err(), "{:?}", value);
+        }
+    }
+
+    #[test]
+    fn rejects_invalid_path_and_domain() {
+        assert!(Cookie::new("sid", "v").set_path("/a; Secure").validate().is_err());
+        assert!(Cookie::new("sid", "v").set_path("/a\nb").validate().is_err());
+        assert!(Cookie::new("sid", "v").set_domain("evil.com; Path=/").validate().is_err());
+        assert!(Cookie::new("sid", "v").set_domain("").validate().is_err());
+        assert!(Cookie::new("sid", "v").set_domain(".example.com").validate().is_ok());
+    }
+
+    #[test]
+    fn same_site_none_requires_secure() {
+        let cookie = Cookie::new("sid", "v").set_same_site(SameSite::None);
+        assert!(cookie.validate().is_err());
+        assert_eq!(cookie.set_secure(true).to_header_value().unwrap(), "sid=v; Secure; SameSite=None");
+    }
+
+    #[test]
+    fn parses_request_header() {
+        let cookies = Cookie::parse_header(" a=1; b=\"two\" ;flag; =x; c=; a=shadowed");
+        assert_eq!(cookies.len(), 3);
+        assert_eq!(cookies["a"], "1");
+        assert_eq!(cookies["b"], "two");
+        assert_eq!(cookies["c"], "");
+    }
+
+    #[test]
+    fn value_may_contain_equals_sign() {
+        let cookies = Cookie::parse_header("token=abc==");
+        assert_eq!(cookies["token"], "abc==");
+    }
+}
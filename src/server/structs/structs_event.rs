@@ -0,0 +1,165 @@
+// src/server/structs/structs_event.rs
+
+use std::io::Result;
+use std::time::Duration;
+
+use super::structs_body::BodyWriter;
+
+/// A Server-Sent Event, written to a `text/event-stream` response by an `EventSender`.
+///
+/// ```ignore
+/// let event = Event::new("42%")
+///     .set_event("progress")
+///     .set_id("17");
+/// events.send(event).await?;
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct Event {
+    pub id: Option<String>,
+    pub event: Option<String>,
+    pub data: Option<String>,
+    pub retry: Option<Duration>,
+    pub comment: Option<String>,
+}
+
+impl Event {
+    /// Creates a message event carrying `data`; multi-line data is sent as several `data:` fields.
+    pub fn new(data: &str) -> Self {
+        Self {
+            data: Some(data.to_string()),
+            ..Self::default()
+        }
+    }
+
+    /// Creates a comment, ignored by the browser; useful to keep idle connections open.
+    pub fn comment(text: &str) -> Self {
+        Self {
+            comment: Some(text.to_string()),
+            ..Self::default()
+        }
+    }
+
+    /// Sets the event id, which the browser sends back as `Last-Event-ID` when it reconnects.
+    pub fn set_id(mut self, id: &str) -> Self {
+        self.id = Some(id.to_string());
+        self
+    }
+
+    /// Sets the event type, dispatched to `addEventListener(type)` instead of `onmessage`.
+    pub fn set_event(mut self, event: &str) -> Self {
+        self.event = Some(event.to_string());
+        self
+    }
+
+    /// Sets how long the browser waits before reconnecting after the connection drops.
+    pub fn set_retry(mut self, retry: Duration) -> Self {
+        self.retry = Some(retry);
+        self
+    }
+
+    /// Serializes the event in the `text/event-stream` format, including the blank line that ends it.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut out = String::new();
+
+        if let Some(comment) = &self.comment {
+            for line in split_lines(comment) {
+                out.push_str(&format!(": {}\n", line));
+            }
+        }
+        if let Some(event) = &self.event {
+            out.push_str(&format!("event: {}\n", single_line(event).replace('\0', "")));
+        }
+        if let Some(id) = &self.id {
+            // Ids containing NUL are ignored by browsers
+            out.push_str(&format!("id: {}\n", single_line(id).replace('\0', "")));
+        }
+        if let Some(retry) = self.retry {
+            out.push_str(&format!("retry: {}\n", retry.as_millis()));
+        }
+        if let Some(data) = &self.data {
+            for line in split_lines(data) {
+                out.push_str(&format!("data: {}\n", line));
+            }
+        }
+
+        out.push('\n');
+        out.into_bytes()
+    }
+}
+
+/// Sends events on a `text/event-stream` response created by `Response::response_sse`.
+///
+/// The stream ends when every clone of the sender is dropped, and sends start failing once the
+/// client disconnects or the server shuts down, so producers can stop with `?`.
+#[derive(Clone)]
+pub struct EventSender {
+    writer: BodyWriter,
+}
+
+impl EventSender {
+    pub(crate) fn new(writer: BodyWriter) -> Self {
+        Self { writer }
+    }
+
+    /// Sends an event, waiting if the connection is behind.
+    pub async fn send(&self, event: Event) -> Result<()> {
+        self.writer.send(event.to_bytes()).await
+    }
+
+    /// Blocking variant of `send`. Must not be called from an async context.
+    pub fn blocking_send(&self, event: Event) -> Result<()> {
+        self.writer.blocking_send(event.to_bytes())
+    }
+
+    /// Returns `true` once the client has disconnected or the server is shutting down.
+    pub fn is_closed(&self) -> bool {
+        self.writer.is_closed()
+    }
+}
+
+/// Splits on CRLF, CR and LF alike, as the event stream format ends a line at any of them.
+fn split_lines(value: &str) -> impl Iterator<Item = &str> {
+    value.split("\r\n").flat_map(|line| line.split(['\r', '\n']))
+}
+
+/// Keeps only the first line of a field value, as `event` and `id` cannot span lines.
+fn single_line(value: &str) -> &str {
+    value.split(['\r', '\n']).next().unwrap_or("")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn text(event: Event) -> String {
+        String::from_utf8(event.to_bytes()).unwrap()
+    }
+
+    #[test]
+    fn sends_each_data_line_as_a_field() {
+        assert_eq!(text(Event::new("hello")), "data: hello\n\n");
+        assert_eq!(text(Event::new("a\nb\n")), "data: a\ndata: b\ndata: \n\n");
+        assert_eq!(text(Event::new("")), "data: \n\n");
+    }
+
+    #[test]
+    fn treats_cr_and_crlf_as_line_breaks() {
+        assert_eq!(text(Event::new("a\r\nb")), "data: a\ndata: b\n\n");
+        assert_eq!(text(Event::new("x\r\revent: evil")), "data: x\ndata: \ndata: event: evil\n\n");
+        assert_eq!(text(Event::new("a\rdata: injected")), "data: a\ndata: data: injected\n\n");
+        assert_eq!(text(Event::comment("one\rtwo")), ": one\n: two\n\n");
+    }
+
+    #[test]
+    fn keeps_id_and_event_on_one_line() {
+        let event = Event::new("x").set_event("tick\rdata: evil").set_id("4\u{0}2\nid: 9");
+        assert_eq!(text(event), "event: tick\nid: 42\ndata: x\n\n");
+        assert_eq!(text(Event::new("x").set_event("a\0b")), "event: ab\ndata: x\n\n");
+    }
+
+    #[test]
+    fn sends_retry_in_milliseconds() {
+        let event = Event::comment("ping").set_retry(Duration::from_secs(3));
+        assert_eq!(text(event), ": ping\nretry: 3000\n\n");
+    }
+}
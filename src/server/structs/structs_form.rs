@@ -0,0 +1,461 @@
+// src/server/structs/structs_form.rs
+
+use std::fs::{self, File};
+use std::io::{BufWriter, Read, Result as IoResult, Write};
+use std::path::{Path, PathBuf};
+
+use crate::util::crypto::random_bytes;
+use crate::util::encoding::hex_encode;
+
+use super::structs_request::RequestError;
+
+/// Multipart parts larger than this many bytes are copied to a temporary file by default.
+pub const DEFAULT_FILE_THRESHOLD: usize = 1024 * 1024;
+
+/// Longest header block a multipart part may have.
+const MAX_PART_HEAD: usize = 16 * 1024;
+
+/// Size of the reads made when parsing a body from a file.
+const READ_CHUNK: usize = 64 * 1024;
+
+/// A `multipart/form-data` body split into its parts.
+#[derive(Debug)]
+pub struct Multipart {
+    pub parts: Vec<MultipartPart>,
+}
+
+/// One part of a `multipart/form-data` body.
+#[derive(Debug)]
+pub struct MultipartPart {
+    pub name: String,
+    pub filename: Option<String>,
+    pub content_type: Option<String>,
+    pub data: PartData,
+}
+
+/// Where the contents of a multipart part are kept once the body has been parsed.
+#[derive(Debug)]
+pub enum PartData {
+    Memory(Vec<u8>),
+    File(TempFile),
+}
+
+/// A file in the system temp directory that is deleted when dropped, unless persisted.
+#[derive(Debug)]
+pub struct TempFile {
+    path: PathBuf,
+    len: usize,
+}
+
+impl Multipart {
+    /// Returns the text value of the first non-file field with the given name.
+    pub fn field(&self, name: &str) -> Option<&str> {
+        self.parts
+            .iter()
+            .filter(|part| part.name == name && part.filename.is_none())
+            .find_map(|part| part.text())
+    }
+
+    /// Returns the first uploaded file with the given field name.
+    pub fn file(&self, name: &str) -> Option<&MultipartPart> {
+        self.parts
+            .iter()
+            .find(|part| part.name == name && part.filename.is_some())
+    }
+
+    /// Returns every uploaded file with the given field name, in order.
+    pub fn files(&self, name: &str) -> Vec<&MultipartPart> {
+        self.parts
+            .iter()
+            .filter(|part| part.name == name && part.filename.is_some())
+            .collect()
+    }
+}
+
+impl MultipartPart {
+    /// Returns the contents as text, if they are held in memory and valid UTF-8.
+    pub fn text(&self) -> Option<&str> {
+        match &self.data {
+            PartData::Memory(bytes) => std::str::from_utf8(bytes).ok(),
+            PartData::File(_) => None,
+        }
+    }
+
+    /// Returns the contents, reading them back from disk if they were copied to a temp file.
+    pub fn bytes(&self) -> IoResult<Vec<u8>> {
+        match &self.data {
+            PartData::Memory(bytes) => Ok(bytes.clone()),
+            PartData::File(file) => fs::read(file.path()),
+        }
+    }
+
+    /// Returns the size of the contents in bytes.
+    pub fn len(&self) -> usize {
+        match &self.data {
+            PartData::Memory(bytes) => bytes.len(),
+            PartData::File(file) => file.len,
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+impl TempFile {
+    /// Creates an empty file with an unpredictable name in the system temp directory,
+    /// readable only by the current user, and returns it opened for writing.
+    pub(crate) fn new() -> IoResult<(Self, File)> {
+        let name = format!("smn_view-upload-{}", hex_encode(&random_bytes(16)));
+        let path = std::env::temp_dir().join(name);
+
+        let mut options = File::options();
+        options.write(true).create_new(true);
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::OpenOptionsExt;
+            options.mode(0o600);
+        }
+        let file = options.open(&path)?;
+
+        Ok((Self { path, len: 0 }, file))
+    }
+
+    /// Records that `len` more bytes were written through the file returned by `new`.
+    pub(crate) fn add_len(&mut self, len: usize) {
+        self.len += len;
+    }
+
+    /// Returns the location of the temporary file.
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Moves the file to `destination` so it survives being dropped.
+    pub fn persist(self, destination: &Path) -> IoResult<()> {
+        if fs::rename(&self.path, destination).is_err() {
+            // Renaming fails across filesystems, so fall back to copying
+            fs::copy(&self.path, destination)?;
+        }
+        Ok(())
+    }
+}
+
+impl Drop for TempFile {
+    fn drop(&mut self) {
+        let _ = fs::remove_file(&self.path);
+    }
+}
+
+/// Parses a `multipart/form-data` body delimited by `boundary` that is held in memory.
+///
+/// Parts larger than `file_threshold` bytes are copied to temporary files, which lets
+/// the request body be freed while large uploads are kept.
+pub fn parse_multipart(body: &[u8], boundary: &str, file_threshold: usize) -> Result<Multipart, RequestError> {
+    let mut parser = MultipartParser::new(boundary, file_threshold);
+    parser.feed(body)?;
+    parser.finish()
+}
+
+/// Parses a `multipart/form-data` body delimited by `boundary`, reading it from `reader`
+/// a block at a time so only parts up to `file_threshold` bytes are held in memory.
+pub fn parse_multipart_from<R: Read>(mut reader: R, boundary: &str, file_threshold: usize) -> Result<Multipart, RequestError> {
+    let mut parser = MultipartParser::new(boundary, file_threshold);
+    let mut chunk = vec![0u8; READ_CHUNK];
+    loop {
+        let read = reader.read(&mut chunk).map_err(|e| invalid(&format!("Failed to read body: {}", e)))?;
+        if read == 0 {
+            return parser.finish();
+        }
+        parser.feed(&chunk[..read])?;
+    }
+}
+
+/// Where a `MultipartParser` is within the body.
+enum MultipartState {
+    /// Skipping anything before the first delimiter.
+    Preamble,
+    /// Just past a delimiter, which is followed by CRLF or, for the last one, `--`.
+    Delimiter,
+    /// Reading the header block of a part.
+    Head,
+    /// Copying the contents of a part until the next delimiter.
+    Data,
+    /// Past the closing delimiter; the epilogue is ignored.
+    Done,
+}
+
+/// Incremental `multipart/form-data` parser that copies large parts to disk as they arrive.
+///
+/// Only a partial delimiter or header block is buffered between calls to `feed`.
+pub struct MultipartParser {
+    state: MultipartState,
+    delimiter: Vec<u8>,
+    next_delimiter: Vec<u8>,
+    file_threshold: usize,
+    buf: Vec<u8>,
+    parts: Vec<MultipartPart>,
+    /// Open handle of the current part once it has moved to a temp file.
+    file: Option<BufWriter<File>>,
+}
+
+impl MultipartParser {
+    pub fn new(boundary: &str, file_threshold: usize) -> Self {
+        Self {
+            state: MultipartState::Preamble,
+            delimiter: format!("--{}", boundary).into_bytes(),
+            next_delimiter: format!("\r\n--{}", boundary).into_bytes(),
+            file_threshold,
+            buf: Vec::new(),
+            parts: Vec::new(),
+            file: None,
+        }
+    }
+
+    /// Parses the next block of the body.
+    pub fn feed(&mut self, data: &[u8]) -> Result<(), RequestError> {
+        self.buf.extend_from_slice(data);
+
+        loop {
+            match self.state {
+                MultipartState::Preamble => match find(&self.buf, &self.delimiter, 0) {
+                    Some(start) => {
+                        self.buf.drain(..start + self.delimiter.len());
+                        self.state = MultipartState::Delimiter;
+                    }
+                    None => {
+                        // Keep what could be the start of a delimiter cut off by the block
+                        let keep = self.delimiter.len() - 1;
+                        let drop = self.buf.len().saturating_sub(keep);
+                        self.buf.drain(..drop);
+                        return Ok(());
+                    }
+                },
+                MultipartState::Delimiter => {
+                    if self.buf.len() < 2 {
+                        return Ok(());
+                    }
+                    if self.buf.starts_with(b"--") {
+                        self.buf.clear();
+                        self.state = MultipartState::Done;
+                    } else if self.buf.starts_with(b"\r\n") {
+                        self.buf.drain(..2);
+                        self.state = MultipartState::Head;
+                    } else {
+                        return Err(invalid("Boundary not followed by CRLF"));
+                    }
+                }
+                MultipartState::Head => match find(&self.buf, b"\r\n\r\n", 0) {
+                    Some(end) => {
+                        let part = parse_part_head(&String::from_utf8_lossy(&self.buf[..end]))?;
+                        self.parts.push(part);
+                        self.buf.drain(..end + 4);
+                        self.state = MultipartState::Data;
+                    }
+                    None if self.buf.len() > MAX_PART_HEAD => return Err(invalid("Part headers too long")),
+                    None => return Ok(()),
+                },
+                MultipartState::Data => match find(&self.buf, &self.next_delimiter, 0) {
+                    Some(end) => {
+                        let data: Vec<u8> = self.buf.drain(..end + self.next_delimiter.len()).take(end).collect();
+                        self.write_part(&data)?;
+                        self.finish_part()?;
+                        self.state = MultipartState::Delimiter;
+                    }
+                    None => {
+                        let keep = self.next_delimiter.len() - 1;
+                        let ready = self.buf.len().saturating_sub(keep);
+                        let data: Vec<u8> = self.buf.drain(..ready).collect();
+                        self.write_part(&data)?;
+                        return Ok(());
+                    }
+                },
+                MultipartState::Done => {
+                    self.buf.clear();
+                    return Ok(());
+                }
+            }
+        }
+    }
+
+    /// Returns the parsed parts, or an error if the body ended before its closing delimiter.
+    pub fn finish(self) -> Result<Multipart, RequestError> {
+        match self.state {
+            MultipartState::Done => Ok(Multipart { parts: self.parts }),
+            MultipartState::Preamble => Err(invalid("Missing opening boundary")),
+            MultipartState::Head => Err(invalid("Part without header terminator")),
+            _ => Err(invalid("Missing closing boundary")),
+        }
+    }
+
+    /// Appends `data` to the current part, moving it to a temp file once it passes the threshold.
+    fn write_part(&mut self, data: &[u8]) -> Result<(), RequestError> {
+        let Some(part) = self.parts.last_mut() else {
+            return Ok(());
+        };
+        match &mut part.data {
+            PartData::Memory(bytes) if bytes.len() + data.len() > self.file_threshold => {
+                let (mut temp, file) = TempFile::new().map_err(disk_error)?;
+                let mut file = BufWriter::new(file);
+                file.write_all(bytes).map_err(disk_error)?;
+                file.write_all(data).map_err(disk_error)?;
+                temp.add_len(bytes.len() + data.len());
+                part.data = PartData::File(temp);
+                self.file = Some(file);
+            }
+            PartData::Memory(bytes) => bytes.extend_from_slice(data),
+            PartData::File(temp) => {
+                if let Some(file) = self.file.as_mut() {
+                    file.write_all(data).map_err(disk_error)?;
+                }
+                temp.add_len(data.len());
+            }
+        }
+        Ok(())
+    }
+
+    /// Flushes and closes the temp file of the current part, if it has one.
+    fn finish_part(&mut self) -> Result<(), RequestError> {
+        if let Some(mut file) = self.file.take() {
+            file.flush().map_err(disk_error)?;
+        }
+        Ok(())
+    }
+}
+
+/// Reads the field name, file name and content type from the header block of a part.
+fn parse_part_head(head: &str) -> Result<MultipartPart, RequestError> {
+    let mut name = None;
+    let mut filename = None;
+    let mut content_type = None;
+
+    for line in head.split("\r\n") {
+        let (key, value) = match line.split_once(':') {
+            Some(header) => header,
+            None => continue,
+        };
+        match key.trim().to_lowercase().as_str() {
+            "content-disposition" => {
+                for param in value.split(';').skip(1) {
+                    if let Some((param_key, param_value)) = param.split_once('=') {
+                        let param_value = param_value.trim().trim_matches('"').to_string();
+                        match param_key.trim().to_lowercase().as_str() {
+                            "name" => name = Some(param_value),
+                            "filename" => filename = Some(param_value),
+                            _ => {}
+                        }
+                    }
+                }
+            }
+            "content-type" => content_type = Some(value.trim().to_string()),
+            _ => {}
+        }
+    }
+
+    let name = name.ok_or_else(|| invalid("Part without a field name"))?;
+    Ok(MultipartPart {
+        name,
+        filename,
+        content_type,
+        data: PartData::Memory(Vec::new()),
+    })
+}
+
+fn find(haystack: &[u8], needle: &[u8], from: usize) -> Option<usize> {
+    haystack
+        .get(from..)?
+        .windows(needle.len())
+        .position(|window| window == needle)
+        .map(|pos| pos + from)
+}
+
+fn disk_error(err: std::io::Error) -> RequestError {
+    invalid(&format!("Failed to copy part to disk: {}", err))
+}
+
+fn invalid(msg: &str) -> RequestError {
+    RequestError::InvalidRequest(format!("Invalid multipart body: {}", msg))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const BODY: &[u8] = b"preamble\r\n--XyZ\r\n\
+Content-Disposition: form-data; name=\"title\"\r\n\r\n\
+Hello\r\n--XyZ\r\n\
+Content-Disposition: form-data; name=\"upload\"; filename=\"a.txt\"\r\n\
+Content-Type: text/plain\r\n\r\n\
+line one\r\nline two\r\n--XyZ--\r\n";
+
+    #[test]
+    fn splits_fields_and_files() {
+        let multipart = parse_multipart(BODY, "XyZ", DEFAULT_FILE_THRESHOLD).unwrap();
+        assert_eq!(multipart.parts.len(), 2);
+        assert_eq!(multipart.field("title"), Some("Hello"));
+        assert_eq!(multipart.field("upload"), None);
+
+        let file = multipart.file("upload").unwrap();
+        assert_eq!(file.filename.as_deref(), Some("a.txt"));
+        assert_eq!(file.content_type.as_deref(), Some("text/plain"));
+        assert_eq!(file.bytes().unwrap(), b"line one\r\nline two");
+    }
+
+    #[test]
+    fn copies_large_parts_to_temp_files() {
+        let multipart = parse_multipart(BODY, "XyZ", 8).unwrap();
+        let file = multipart.file("upload").unwrap();
+        let path = match &file.data {
+            PartData::File(temp) => temp.path().to_path_buf(),
+            PartData::Memory(_) => panic!("part was not copied to disk"),
+        };
+        assert_eq!(file.len(), 18);
+        assert_eq!(file.bytes().unwrap(), b"line one\r\nline two");
+        assert!(matches!(multipart.parts[0].data, PartData::Memory(_)));
+
+        drop(multipart);
+        assert!(!path.exists());
+    }
+
+    #[test]
+    fn parses_bodies_fed_in_small_blocks() {
+        let mut parser = MultipartParser::new("XyZ", 8);
+        for block in BODY.chunks(3) {
+            parser.feed(block).unwrap();
+        }
+        let multipart = parser.finish().unwrap();
+        assert_eq!(multipart.field("title"), Some("Hello"));
+        let file = multipart.file("upload").unwrap();
+        assert!(matches!(file.data, PartData::File(_)));
+        assert_eq!(file.bytes().unwrap(), b"line one\r\nline two");
+
+        let multipart = parse_multipart_from(BODY, "XyZ", DEFAULT_FILE_THRESHOLD).unwrap();
+        assert_eq!(multipart.parts.len(), 2);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn temp_files_are_private() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let (first, _) = TempFile::new().unwrap();
+        let (second, _) = TempFile::new().unwrap();
+        assert_ne!(first.path(), second.path());
+        let mode = fs::metadata(first.path()).unwrap().permissions().mode();
+        assert_eq!(mode & 0o777, 0o600);
+    }
+
+    #[test]
+    fn rejects_malformed_bodies() {
+        assert!(parse_multipart(b"no boundary here", "XyZ", 1024).is_err());
+        assert!(parse_multipart(b"--XyZ\r\nContent-Disposition: form-data; name=\"a\"\r\n\r\nx", "XyZ", 1024).is_err());
+        assert!(parse_multipart(b"--XyZ\r\nContent-Type: text/plain\r\n\r\nx\r\n--XyZ--", "XyZ", 1024).is_err());
+        assert!(parse_multipart(b"--XyZ\r\nno header end\r\n--XyZ--", "XyZ", 1024).is_err());
+    }
+
+    #[test]
+    fn empty_form_has_no_parts() {
+        let multipart = parse_multipart(b"--XyZ--\r\n", "XyZ", 1024).unwrap();
+        assert!(multipart.parts.is_empty());
+    }
+}
@@ -0,0 +1,254 @@
+// src/server/structs/structs_headermap.rs
+
+use std::str::FromStr;
+
+use super::structs_mime::Mime;
+use super::structs_response::ResponseError;
+
+/// Header fields of a request or response.
+///
+/// Names are matched case-insensitively but keep the casing they were added with. A name may
+/// appear several times, and fields are serialized in the order they were added.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct HeaderMap {
+    entries: Vec<(String, String)>,
+}
+
+impl HeaderMap {
+    /// Creates an empty `HeaderMap`.
+    pub fn new() -> Self {
+        Self { entries: Vec::new() }
+    }
+
+    /// Creates a `HeaderMap` from fields that are not validated.
+    ///
+    /// Only for parsing requests, which check their field names themselves; anything written
+    /// to the wire must go through `insert` or `append`.
+    pub(crate) fn from_unchecked(entries: Vec<(String, String)>) -> Self {
+        Self { entries }
+    }
+
+    /// Returns the number of header lines, counting repeated names separately.
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Returns `true` if at least one field with this name is present.
+    pub fn contains(&self, name: &str) -> bool {
+        self.entries.iter().any(|(key, _)| key.eq_ignore_ascii_case(name))
+    }
+
+    /// Returns the first value of the field with this name.
+    pub fn get(&self, name: &str) -> Option<&str> {
+        self.entries
+            .iter()
+            .find(|(key, _)| key.eq_ignore_ascii_case(name))
+            .map(|(_, value)| value.as_str())
+    }
+
+    /// Returns every value of the field with this name, in the order they were added.
+    pub fn get_all(&self, name: &str) -> Vec<&str> {
+        self.entries
+            .iter()
+            .filter(|(key, _)| key.eq_ignore_ascii_case(name))
+            .map(|(_, value)| value.as_str())
+            .collect()
+    }
+
+    /// Returns all values of a list-valued field joined with `", "`, as if sent on a single line.
+    pub fn get_joined(&self, name: &str) -> Option<String> {
+        let values = self.get_all(name);
+        if values.is_empty() {
+            None
+        } else {
+            Some(values.join(", "))
+        }
+    }
+
+    /// Parses the first value of the field with this name.
+    ///
+    /// Returns `None` if the field is absent or its value does not parse as `T`.
+    pub fn get_as<T: FromStr>(&self, name: &str) -> Option<T> {
+        self.get(name).and_then(|value| value.trim().parse().ok())
+    }
+
+    /// Returns `true` if any value of a comma-separated field contains `token`, ignoring case.
+    pub fn has_token(&self, name: &str, token: &str) -> bool {
+        self.get_all(name)
+            .iter()
+            .flat_map(|value| value.split(','))
+            .any(|item| item.trim().eq_ignore_ascii_case(token))
+    }
+
+    /// Sets a field, replacing every existing value with the same name.
+    ///
+    /// The field keeps the position of its first occurrence, or is added at the end if it is new.
+    /// Fails with `ResponseError::InvalidHeader` if the name is not a token or the value
+    /// contains control characters such as CR or LF.
+    pub fn insert(&mut self, name: &str, value: &str) -> Result<(), ResponseError> {
+        validate_field(name, value)?;
+        match self.entries.iter().position(|(key, _)| key.eq_ignore_ascii_case(name)) {
+            Some(index) => {
+                self.entries[index] = (name.to_string(), value.to_string());
+                let mut current = 0;
+                self.entries.retain(|(key, _)| {
+                    let keep = current <= index || !key.eq_ignore_ascii_case(name);
+                    current += 1;
+                    keep
+                });
+                Ok(())
+            }
+            None => self.append(name, value),
+        }
+    }
+
+    /// Adds a field, keeping any existing values with the same name as separate lines.
+    ///
+    /// Validated like `insert`.
+    pub fn append(&mut self, name: &str, value: &str) -> Result<(), ResponseError> {
+        validate_field(name, value)?;
+        self.entries.push((name.to_string(), value.to_string()));
+        Ok(())
+    }
+
+    /// Removes every field with this name and returns their values.
+    pub fn remove(&mut self, name: &str) -> Vec<String> {
+        let mut removed = Vec::new();
+        self.entries.retain(|(key, value)| {
+            if key.eq_ignore_ascii_case(name) {
+                removed.push(value.clone());
+                false
+            } else {
+                true
+            }
+        });
+        removed
+    }
+
+    /// Iterates over all fields as `(name, value)` pairs, in the order they were added.
+    pub fn iter(&self) -> impl Iterator<Item = (&str, &str)> {
+        self.entries.iter().map(|(key, value)| (key.as_str(), value.as_str()))
+    }
+
+    /// Returns the parsed `Content-Type`, if present.
+    pub fn content_type(&self) -> Option<Mime> {
+        self.get("content-type").map(Mime::from_string)
+    }
+
+    /// Sets `Content-Type`, adding a UTF-8 charset for text types.
+    pub fn set_content_type(&mut self, mime: &Mime) -> Result<(), ResponseError> {
+        self.insert("Content-Type", &mime.to_header_value())
+    }
+
+    /// Returns the `Content-Length`, if present and valid.
+    pub fn content_length(&self) -> Option<usize> {
+        self.get_as("content-length")
+    }
+}
+
+/// Returns `true` if `input` is a non-empty RFC 7230 token, as used for header and cookie names.
+pub(crate) fn is_token(input: &str) -> bool {
+    !input.is_empty()
+        && input
+            .bytes()
+            .all(|b| b.is_ascii_alphanumeric() || b"!#$%&'*+-.^_`|~".contains(&b))
+}
+
+/// Checks that a field can be written to the wire without changing the message framing.
+fn validate_field(name: &str, value: &str) -> Result<(), ResponseError> {
+    if !is_token(name) {
+        return Err(ResponseError::InvalidHeader(format!("Invalid header name: {:?}", name)));
+    }
+    // Horizontal tab is the only control character allowed in a field value
+    if value.chars().any(|c| c.is_ascii_control() && c != '\t') {
+        return Err(ResponseError::InvalidHeader(format!("Invalid value for header '{}'", name)));
+    }
+    Ok(())
+}
+
+impl<'a> IntoIterator for &'a HeaderMap {
+    type Item = (&'a str, &'a str);
+    type IntoIter = Box<dyn Iterator<Item = (&'a str, &'a str)> + 'a>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        Box::new(self.iter())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn map(fields: &[(&str, &str)]) -> HeaderMap {
+        let mut headers = HeaderMap::new();
+        for (name, value) in fields {
+            headers.append(name, value).unwrap();
+        }
+        headers
+    }
+
+    #[test]
+    fn lookups_ignore_case() {
+        let headers = map(&[("Content-Type", "text/html"), ("X-Tag", "a")]);
+        assert_eq!(headers.get("content-type"), Some("text/html"));
+        assert!(headers.contains("x-tag"));
+        assert_eq!(headers.get("missing"), None);
+    }
+
+    #[test]
+    fn multiple_values_are_kept_in_order() {
+        let headers = map(&[("Vary", "Accept"), ("Other", "x"), ("vary", "Cookie")]);
+        assert_eq!(headers.get_all("VARY"), vec!["Accept", "Cookie"]);
+        assert_eq!(headers.get_joined("vary").as_deref(), Some("Accept, Cookie"));
+        assert!(headers.has_token("vary", "cookie"));
+        assert!(!headers.has_token("vary", "encoding"));
+    }
+
+    #[test]
+    fn insert_replaces_all_values_at_first_position() {
+        let mut headers = map(&[("A", "1"), ("Vary", "x"), ("B", "2"), ("vary", "y")]);
+        headers.insert("VARY", "z").unwrap();
+        let fields: Vec<_> = headers.iter().collect();
+        assert_eq!(fields, vec![("A", "1"), ("VARY", "z"), ("B", "2")]);
+    }
+
+    #[test]
+    fn append_and_remove() {
+        let mut headers = HeaderMap::new();
+        headers.append("Set-Thing", "1").unwrap();
+        headers.append("set-thing", "2").unwrap();
+        assert_eq!(headers.len(), 2);
+        assert_eq!(headers.remove("SET-THING"), vec!["1", "2"]);
+        assert!(headers.is_empty());
+    }
+
+    #[test]
+    fn typed_accessors() {
+        let mut headers = map(&[("Content-Length", " 42 ")]);
+        assert_eq!(headers.content_length(), Some(42));
+        headers.set_content_type(&Mime::ApplicationJson).unwrap();
+        assert_eq!(headers.content_type().as_ref().map(Mime::essence), Some(&Mime::ApplicationJson));
+    }
+
+    #[test]
+    fn rejects_invalid_names() {
+        let mut headers = HeaderMap::new();
+        for name in ["", "Bad Name", "Bad:Name", "Bad\r\nName", "Bäd"] {
+            assert!(headers.insert(name, "value").is_err(), "{:?}", name);
+        }
+        assert!(headers.is_empty());
+    }
+
+    #[test]
+    fn rejects_control_characters_in_values() {
+        let mut headers = HeaderMap::new();
+        for value in ["a\r\nSet-Cookie: x=y", "a\nb", "a\rb", "a\0b"] {
+            assert!(headers.append("X-Test", value).is_err(), "{:?}", value);
+        }
+        assert!(headers.append("X-Test", "tab\tand unicode ✓").is_ok());
+    }
+}
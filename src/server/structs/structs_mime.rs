@@ -1,12 +1,128 @@
+use std::collections::HashMap;
+use std::sync::{OnceLock, RwLock};
+
+/// A media type, as sent in `Content-Type`.
+///
+/// Well-known types have their own variants; anything else is carried as `Custom`.
+/// Parameters such as `charset` are attached with `with_param`.
+#[derive(Debug, Clone, PartialEq)]
 pub enum Mime {
     TextPlain,
     TextHtml,
     ApplicationJson,
     CSS,
     JavaScript,
+    TextCsv,
+    TextXml,
+    EventStream,
+    ImagePng,
+    ImageJpeg,
+    ImageGif,
+    ImageWebp,
+    ImageSvg,
+    ImageIcon,
+    ImageAvif,
+    ImageBmp,
+    FontWoff,
+    FontWoff2,
+    FontTtf,
+    FontOtf,
+    AudioMpeg,
+    AudioOgg,
+    AudioWav,
+    VideoMp4,
+    VideoWebm,
+    Wasm,
+    Pdf,
+    Zip,
+    FormUrlEncoded,
+    MultipartFormData,
+    OctetStream,
+    Custom(String),
+    WithParams(Box<Mime>, Vec<(String, String)>),
+}
+
+/// Built-in extension table, used when no override is registered.
+const EXTENSIONS: &[(&str, &str)] = &[
+    // Text and documents
+    ("txt", "text/plain"),
+    ("text", "text/plain"),
+    ("log", "text/plain"),
+    ("html", "text/html"),
+    ("htm", "text/html"),
+    ("css", "text/css"),
+    ("csv", "text/csv"),
+    ("md", "text/markdown"),
+    ("xml", "text/xml"),
+    ("ics", "text/calendar"),
+    ("pdf", "application/pdf"),
+    ("rtf", "application/rtf"),
+    // Scripts, data and manifests
+    ("js", "application/javascript"),
+    ("mjs", "application/javascript"),
+    ("cjs", "application/javascript"),
+    ("json", "application/json"),
+    ("map", "application/json"),
+    ("webmanifest", "application/manifest+json"),
+    ("yaml", "application/yaml"),
+    ("yml", "application/yaml"),
+    ("toml", "application/toml"),
+    ("wasm", "application/wasm"),
+    // Images
+    ("png", "image/png"),
+    ("apng", "image/apng"),
+    ("jpg", "image/jpeg"),
+    ("jpeg", "image/jpeg"),
+    ("jfif", "image/jpeg"),
+    ("gif", "image/gif"),
+    ("webp", "image/webp"),
+    ("svg", "image/svg+xml"),
+    ("ico", "image/x-icon"),
+    ("avif", "image/avif"),
+    ("bmp", "image/bmp"),
+    ("tif", "image/tiff"),
+    ("tiff", "image/tiff"),
+    // Fonts
+    ("woff", "font/woff"),
+    ("woff2", "font/woff2"),
+    ("ttf", "font/ttf"),
+    ("otf", "font/otf"),
+    ("eot", "application/vnd.ms-fontobject"),
+    // Audio
+    ("mp3", "audio/mpeg"),
+    ("ogg", "audio/ogg"),
+    ("oga", "audio/ogg"),
+    ("opus", "audio/opus"),
+    ("wav", "audio/wav"),
+    ("flac", "audio/flac"),
+    ("aac", "audio/aac"),
+    ("m4a", "audio/mp4"),
+    ("mid", "audio/midi"),
+    ("midi", "audio/midi"),
+    // Video
+    ("mp4", "video/mp4"),
+    ("m4v", "video/mp4"),
+    ("webm", "video/webm"),
+    ("ogv", "video/ogg"),
+    ("mov", "video/quicktime"),
+    ("avi", "video/x-msvideo"),
+    // Archives and binaries
+    ("zip", "application/zip"),
+    ("gz", "application/gzip"),
+    ("tar", "application/x-tar"),
+    ("7z", "application/x-7z-compressed"),
+    ("bin", "application/octet-stream"),
+    ("exe", "application/octet-stream"),
+];
+
+/// Extension mappings registered at runtime, consulted before the built-in table.
+fn overrides() -> &'static RwLock<HashMap<String, Mime>> {
+    static OVERRIDES: OnceLock<RwLock<HashMap<String, Mime>>> = OnceLock::new();
+    OVERRIDES.get_or_init(|| RwLock::new(HashMap::new()))
 }
 
 impl Mime {
+    /// Returns the `type/subtype` essence, without parameters.
     pub fn to_string(&self) -> &str {
         match self {
             Mime::TextPlain => "text/plain",
@@ -14,28 +130,336 @@ impl Mime {
             Mime::ApplicationJson => "application/json",
             Mime::CSS => "text/css",
             Mime::JavaScript => "application/javascript",
+            Mime::TextCsv => "text/csv",
+            Mime::TextXml => "text/xml",
+            Mime::EventStream => "text/event-stream",
+            Mime::ImagePng => "image/png",
+            Mime::ImageJpeg => "image/jpeg",
+            Mime::ImageGif => "image/gif",
+            Mime::ImageWebp => "image/webp",
+            Mime::ImageSvg => "image/svg+xml",
+            Mime::ImageIcon => "image/x-icon",
+            Mime::ImageAvif => "image/avif",
+            Mime::ImageBmp => "image/bmp",
+            Mime::FontWoff => "font/woff",
+            Mime::FontWoff2 => "font/woff2",
+            Mime::FontTtf => "font/ttf",
+            Mime::FontOtf => "font/otf",
+            Mime::AudioMpeg => "audio/mpeg",
+            Mime::AudioOgg => "audio/ogg",
+            Mime::AudioWav => "audio/wav",
+            Mime::VideoMp4 => "video/mp4",
+            Mime::VideoWebm => "video/webm",
+            Mime::Wasm => "application/wasm",
+            Mime::Pdf => "application/pdf",
+            Mime::Zip => "application/zip",
+            Mime::FormUrlEncoded => "application/x-www-form-urlencoded",
+            Mime::MultipartFormData => "multipart/form-data",
+            Mime::OctetStream => "application/octet-stream",
+            Mime::Custom(mime) => mime,
+            Mime::WithParams(mime, _) => mime.to_string(),
         }
     }
 
+    /// Parses a `Content-Type` value, keeping any parameters. Unknown types become `Custom`.
     pub fn from_string(mime: &str) -> Self {
-        match mime {
+        let mut parts = mime.split(';');
+        let essence = parts.next().unwrap_or("").trim().to_lowercase();
+
+        let base = match essence.as_str() {
             "text/plain" => Mime::TextPlain,
             "text/html" => Mime::TextHtml,
             "application/json" => Mime::ApplicationJson,
             "text/css" => Mime::CSS,
-            "application/javascript" => Mime::JavaScript,
-            _ => Mime::TextPlain,
-        }
+            "application/javascript" | "text/javascript" => Mime::JavaScript,
+            "text/csv" => Mime::TextCsv,
+            "text/xml" => Mime::TextXml,
+            "text/event-stream" => Mime::EventStream,
+            "image/png" => Mime::ImagePng,
+            "image/jpeg" => Mime::ImageJpeg,
+            "image/gif" => Mime::ImageGif,
+            "image/webp" => Mime::ImageWebp,
+            "image/svg+xml" => Mime::ImageSvg,
+            "image/x-icon" | "image/vnd.microsoft.icon" => Mime::ImageIcon,
+            "image/avif" => Mime::ImageAvif,
+            "image/bmp" => Mime::ImageBmp,
+            "font/woff" => Mime::FontWoff,
+            "font/woff2" => Mime::FontWoff2,
+            "font/ttf" => Mime::FontTtf,
+            "font/otf" => Mime::FontOtf,
+            "audio/mpeg" => Mime::AudioMpeg,
+            "audio/ogg" => Mime::AudioOgg,
+            "audio/wav" => Mime::AudioWav,
+            "video/mp4" => Mime::VideoMp4,
+            "video/webm" => Mime::VideoWebm,
+            "application/wasm" => Mime::Wasm,
+            "application/pdf" => Mime::Pdf,
+            "application/zip" => Mime::Zip,
+            "application/x-www-form-urlencoded" => Mime::FormUrlEncoded,
+            "multipart/form-data" => Mime::MultipartFormData,
+            "application/octet-stream" => Mime::OctetStream,
+            "" => Mime::TextPlain,
+            _ => Mime::Custom(essence),
+        };
+
+        parts.fold(base, |mime, param| match param.split_once('=') {
+            Some((key, value)) => mime.with_param(key.trim(), value.trim().trim_matches('"')),
+            None => mime,
+        })
     }
 
+    /// Looks up the type for a file extension, falling back to `text/plain` when it is unknown.
     pub fn from_extension(extension: &str) -> Self {
-        match extension {
-            "txt" => Mime::TextPlain,
-            "html" => Mime::TextHtml,
-            "json" => Mime::ApplicationJson,
-            "css" => Mime::CSS,
-            "js" => Mime::JavaScript,
-            _ => Mime::TextPlain,
+        Self::lookup_extension(extension).unwrap_or(Mime::TextPlain)
+    }
+
+    /// Looks up the type for a file extension in the registered overrides and the built-in table.
+    pub fn lookup_extension(extension: &str) -> Option<Self> {
+        let extension = extension.trim_start_matches('.').to_lowercase();
+
+        if let Some(mime) = overrides().read().ok().and_then(|map| map.get(&extension).cloned()) {
+            return Some(mime);
+        }
+
+        EXTENSIONS
+            .iter()
+            .find(|(ext, _)| *ext == extension)
+            .map(|(_, mime)| Mime::from_string(mime))
+    }
+
+    /// Looks up the type for a file extension, sniffing `content` when the extension is unknown.
+    ///
+    /// Unrecognised content is reported as `text/plain` if it is valid UTF-8, and as
+    /// `application/octet-stream` otherwise.
+    pub fn from_extension_or_sniff(extension: &str, content: &[u8]) -> Self {
+        Self::lookup_extension(extension)
+            .or_else(|| Self::sniff(content))
+            .unwrap_or_else(|| match std::str::from_utf8(content) {
+                Ok(_) => Mime::TextPlain,
+                Err(_) => Mime::OctetStream,
+            })
+    }
+
+    /// Registers the type served for an extension, replacing the built-in mapping.
+    pub fn register_extension(extension: &str, mime: Mime) {
+        let extension = extension.trim_start_matches('.').to_lowercase();
+        if let Ok(mut map) = overrides().write() {
+            map.insert(extension, mime);
+        }
+    }
+
+    /// Guesses the type from the leading bytes of `content`.
+    pub fn sniff(content: &[u8]) -> Option<Self> {
+        let starts = |magic: &[u8]| content.starts_with(magic);
+        let riff_kind = content.get(8..12);
+        // "BM" alone is too common in text, so also require a known DIB header size
+        let bmp_dib_size = content.get(14..18).map(|size| u32::from_le_bytes([size[0], size[1], size[2], size[3]]));
+
+        let mime = if starts(b"\x89PNG\r\n\x1a\n") {
+            Mime::ImagePng
+        } else if starts(b"\xff\xd8\xff") {
+            Mime::ImageJpeg
+        } else if starts(b"GIF87a") || starts(b"GIF89a") {
+            Mime::ImageGif
+        } else if starts(b"RIFF") && riff_kind == Some(b"WEBP") {
+            Mime::ImageWebp
+        } else if starts(b"RIFF") && riff_kind == Some(b"WAVE") {
+            Mime::AudioWav
+        } else if starts(b"\x00\x00\x01\x00") {
+            Mime::ImageIcon
+        } else if starts(b"BM") && matches!(bmp_dib_size, Some(12 | 40 | 56 | 108 | 124)) {
+            Mime::ImageBmp
+        } else if content.get(4..12) == Some(b"ftypavif") {
+            Mime::ImageAvif
+        } else if content.get(4..8) == Some(b"ftyp") {
+            Mime::VideoMp4
+        } else if starts(b"wOFF") {
+            Mime::FontWoff
+        } else if starts(b"wOF2") {
+            Mime::FontWoff2
+        } else if starts(b"\x00\x01\x00\x00") {
+            Mime::FontTtf
+        } else if starts(b"OTTO") {
+            Mime::FontOtf
+        } else if starts(b"ID3") || starts(b"\xff\xfb") || starts(b"\xff\xf3") {
+            Mime::AudioMpeg
+        } else if starts(b"OggS") {
+            Mime::AudioOgg
+        } else if starts(b"\x1a\x45\xdf\xa3") {
+            Mime::VideoWebm
+        } else if starts(b"\x00asm") {
+            Mime::Wasm
+        } else if starts(b"%PDF-") {
+            Mime::Pdf
+        } else if starts(b"PK\x03\x04") {
+            Mime::Zip
+        } else {
+            return Self::sniff_text(content);
+        };
+
+        Some(mime)
+    }
+
+    /// Recognises markup by its opening tag, ignoring leading whitespace and case.
+    fn sniff_text(content: &[u8]) -> Option<Self> {
+        let head = &content[..content.len().min(512)];
+        let head = String::from_utf8_lossy(head).trim_start().to_lowercase();
+
+        if head.starts_with("<!doctype html") || head.starts_with("<html") {
+            Some(Mime::TextHtml)
+        } else if head.starts_with("<svg") || (head.starts_with("<?xml") && head.contains("<svg")) {
+            Some(Mime::ImageSvg)
+        } else if head.starts_with("<?xml") {
+            Some(Mime::TextXml)
+        } else {
+            None
+        }
+    }
+
+    /// Returns a copy of the type with a parameter added, e.g. `("charset", "utf-8")`.
+    pub fn with_param(self, key: &str, value: &str) -> Self {
+        let param = (key.to_lowercase(), value.to_string());
+        match self {
+            Mime::WithParams(mime, mut params) => {
+                params.retain(|(existing, _)| *existing != param.0);
+                params.push(param);
+                Mime::WithParams(mime, params)
+            }
+            mime => Mime::WithParams(Box::new(mime), vec![param]),
+        }
+    }
+
+    /// Returns the value of a parameter, if it was set.
+    pub fn param(&self, key: &str) -> Option<&str> {
+        match self {
+            Mime::WithParams(_, params) => params
+                .iter()
+                .find(|(name, _)| name.eq_ignore_ascii_case(key))
+                .map(|(_, value)| value.as_str()),
+            _ => None,
         }
     }
-}
\ No newline at end of file
+
+    /// Returns the type without its parameters.
+    pub fn essence(&self) -> &Mime {
+        match self {
+            Mime::WithParams(mime, _) => mime.essence(),
+            mime => mime,
+        }
+    }
+
+    /// Returns `true` for textual types, which are served with `charset=utf-8` by default.
+    pub fn is_text(&self) -> bool {
+        let essence = self.to_string();
+        essence.starts_with("text/")
+            || essence.ends_with("+json")
+            || essence.ends_with("+xml")
+            || matches!(
+                self.essence(),
+                Mime::ApplicationJson | Mime::JavaScript | Mime::FormUrlEncoded
+            )
+            || matches!(essence, "application/xml" | "application/yaml" | "application/toml")
+    }
+
+    /// Formats the type for a `Content-Type` header, including its parameters.
+    ///
+    /// Textual types without an explicit charset get `charset=utf-8`.
+    pub fn to_header_value(&self) -> String {
+        let mut value = self.to_string().to_string();
+        if let Mime::WithParams(_, params) = self {
+            for (key, param) in params {
+                value.push_str(&format!("; {}={}", key, param));
+            }
+        }
+        if self.is_text() && self.param("charset").is_none() {
+            value.push_str("; charset=utf-8");
+        }
+        value
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn bmp(dib_size: u32) -> Vec<u8> {
+        let mut content = b"BM".to_vec();
+        content.extend_from_slice(&[0; 12]);
+        content.extend_from_slice(&dib_size.to_le_bytes());
+        content.extend_from_slice(&[0; 16]);
+        content
+    }
+
+    #[test]
+    fn sniffs_bmp_only_with_known_dib_header() {
+        for size in [12, 40, 56, 108, 124] {
+            assert_eq!(Mime::sniff(&bmp(size)), Some(Mime::ImageBmp));
+        }
+        assert_ne!(Mime::sniff(&bmp(7)), Some(Mime::ImageBmp));
+        assert_ne!(Mime::sniff(b"BMW owners club meeting notes"), Some(Mime::ImageBmp));
+    }
+
+    #[test]
+    fn sniffs_magic_numbers() {
+        assert_eq!(Mime::sniff(b"\x89PNG\r\n\x1a\n...."), Some(Mime::ImagePng));
+        assert_eq!(Mime::sniff(b"GIF89a..."), Some(Mime::ImageGif));
+        assert_eq!(Mime::sniff(b"RIFF\0\0\0\0WEBPVP8 "), Some(Mime::ImageWebp));
+        assert_eq!(Mime::sniff(b"%PDF-1.7"), Some(Mime::Pdf));
+    }
+
+    #[test]
+    fn looks_up_extensions_case_insensitively() {
+        assert_eq!(Mime::lookup_extension("html"), Some(Mime::TextHtml));
+        assert_eq!(Mime::lookup_extension("HTML"), Some(Mime::TextHtml));
+        assert_eq!(Mime::lookup_extension(".Js"), Some(Mime::JavaScript));
+        assert_eq!(Mime::lookup_extension("webmanifest"), Some(Mime::Custom("application/manifest+json".to_string())));
+        assert_eq!(Mime::lookup_extension("unknown"), None);
+        assert_eq!(Mime::from_extension("unknown"), Mime::TextPlain);
+    }
+
+    #[test]
+    fn registered_extensions_override_built_ins() {
+        assert_eq!(Mime::lookup_extension("rtf"), Some(Mime::Custom("application/rtf".to_string())));
+        Mime::register_extension(".RTF", Mime::TextPlain);
+        assert_eq!(Mime::lookup_extension("rtf"), Some(Mime::TextPlain));
+        assert_eq!(Mime::lookup_extension("Rtf"), Some(Mime::TextPlain));
+    }
+
+    #[test]
+    fn parses_header_values() {
+        assert_eq!(Mime::from_string("Application/JSON"), Mime::ApplicationJson);
+        assert_eq!(Mime::from_string("application/x-custom"), Mime::Custom("application/x-custom".to_string()));
+
+        let mime = Mime::from_string("text/html; Charset=\"ISO-8859-1\"; level=1");
+        assert_eq!(*mime.essence(), Mime::TextHtml);
+        assert_eq!(mime.to_string(), "text/html");
+        assert_eq!(mime.param("charset"), Some("ISO-8859-1"));
+        assert_eq!(mime.param("LEVEL"), Some("1"));
+        assert_eq!(mime.param("boundary"), None);
+
+        let mime = Mime::from_string("multipart/form-data; boundary=abc");
+        assert_eq!(*mime.essence(), Mime::MultipartFormData);
+        assert_eq!(mime.param("boundary"), Some("abc"));
+
+        let mime = Mime::from_string("application/x-custom;version=2");
+        assert_eq!(*mime.essence(), Mime::Custom("application/x-custom".to_string()));
+        assert_eq!(mime.param("version"), Some("2"));
+    }
+
+    #[test]
+    fn adds_charset_to_text_types_only() {
+        assert_eq!(Mime::TextHtml.to_header_value(), "text/html; charset=utf-8");
+        assert_eq!(Mime::ApplicationJson.to_header_value(), "application/json; charset=utf-8");
+        assert_eq!(Mime::from_string("application/ld+json").to_header_value(), "application/ld+json; charset=utf-8");
+        assert_eq!(Mime::ImagePng.to_header_value(), "image/png");
+        assert_eq!(Mime::OctetStream.to_header_value(), "application/octet-stream");
+        assert_eq!(
+            Mime::MultipartFormData.with_param("boundary", "x").to_header_value(),
+            "multipart/form-data; boundary=x"
+        );
+        assert_eq!(
+            Mime::TextPlain.with_param("charset", "latin1").to_header_value(),
+            "text/plain; charset=latin1"
+        );
+    }
+}
@@ -1,21 +1,42 @@
 // src/server/structs/structs_http.rs
 
-use crate::util::logging::logln;
 use std::collections::HashMap;
+use std::str::FromStr;
+
+use serde::de::DeserializeOwned;
+
+use crate::server::server_chunked::ChunkedDecoder;
+use crate::server::session::session_core::Session;
+use crate::util::encoding::{parse_urlencoded, percent_decode};
+
+use super::structs_cookie::Cookie;
+use super::structs_headermap::{is_token, HeaderMap};
+use super::structs_form::{parse_multipart, parse_multipart_from, Multipart, TempFile, DEFAULT_FILE_THRESHOLD};
+use super::structs_mime::Mime;
 
 /// Represents an HTTP request with minimal parsing.
 #[derive(Default)]
 pub struct Request {
     pub method: String,
-    pub path: String,
-    pub header_fields: HashMap<String, String>, // Explicit type annotations
+    pub target: String, // Raw request target as sent by the client
+    pub path: String, // Percent-decoded path, without the query string
+    pub query_fields: HashMap<String, Vec<String>>, // Decoded query parameters, values in order of appearance
+    pub version: String,
+    pub header_fields: HeaderMap, // Header fields in the order they were received
+    pub cookie_fields: HashMap<String, String>, // Cookies parsed from the Cookie header
     pub body: Vec<u8>, // New field to store the request body
+    pub body_file: Option<TempFile>, // Large multipart body written to disk by the server instead of `body`
+    pub params: HashMap<String, String>, // Path parameters captured by the matched route
+    pub session: Option<Session>, // Set by SessionMiddleware
 }
 
 /// Custom error type for Request operations.
 #[derive(Debug)]
 pub enum RequestError {
     HeaderNotFound(String),
+    ParamNotFound(String),
+    ContentTypeMismatch { expected: String, found: String },
+    UnsupportedTransferEncoding(String),
     InvalidRequest(String),
 }
 
@@ -23,6 +44,11 @@ impl std::fmt::Display for RequestError {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
             RequestError::HeaderNotFound(header) => write!(f, "Header '{}' not found", header),
+            RequestError::ParamNotFound(param) => write!(f, "Path parameter '{}' not found", param),
+            RequestError::ContentTypeMismatch { expected, found } => {
+                write!(f, "Expected a {} body, got '{}'", expected, found)
+            }
+            RequestError::UnsupportedTransferEncoding(codings) => write!(f, "Unsupported Transfer-Encoding: {}", codings),
             RequestError::InvalidRequest(msg) => write!(f, "Invalid request: {}", msg),
         }
     }
@@ -38,12 +64,49 @@ impl Request {
     }
 
     /// Parses a raw HTTP request from bytes and returns a `Request` instance.
+    ///
+    /// The body is taken from the bytes following the header terminator, either decoded
+    /// from `Transfer-Encoding: chunked` or limited to the length announced by `Content-Length`.
     pub fn from_bytes(request: &[u8]) -> Result<Self, RequestError> {
-        let mut lines = request.split(|&b| b == b'\r' || b == b'\n');
+        let head_end = Self::find_head_end(request).unwrap_or(request.len());
+        let mut parsed = Self::from_head(&request[..head_end])?;
+
+        if parsed.is_chunked() {
+            let body = request.get(head_end..).unwrap_or_default();
+            let mut decoder = ChunkedDecoder::new(usize::MAX, usize::MAX);
+            decoder.decode(body)?;
+            if !decoder.is_complete() {
+                return Err(RequestError::InvalidRequest("Incomplete chunked body".to_string()));
+            }
+            parsed.body = decoder.into_body();
+        } else if let Some(content_length) = parsed.content_length()? {
+            let body = request.get(head_end..).unwrap_or_default();
+            if body.len() < content_length {
+                return Err(RequestError::InvalidRequest(format!(
+                    "Body shorter than Content-Length: {} of {} bytes",
+                    body.len(),
+                    content_length
+                )));
+            }
+            parsed.body = body[..content_length].to_vec();
+        }
+
+        Ok(parsed)
+    }
+
+    /// Parses the request line and headers of a raw HTTP request.
+    ///
+    /// The `head` slice may or may not include the trailing blank line; any bytes
+    /// after the end of the headers are ignored and the body is left empty.
+    pub fn from_head(head: &[u8]) -> Result<Self, RequestError> {
+        let mut lines = head
+            .split(|&b| b == b'\n')
+            .map(|line| line.strip_suffix(b"\r").unwrap_or(line));
 
         // Parse request line
         let request_line = lines
             .next()
+            .filter(|line| !line.is_empty())
             .ok_or_else(|| RequestError::InvalidRequest("Empty request".to_string()))?;
         let request_line_str = String::from_utf8_lossy(request_line);
         let mut parts = request_line_str.split_whitespace();
@@ -52,15 +115,17 @@ impl Request {
             .next()
             .ok_or_else(|| RequestError::InvalidRequest("Missing HTTP method".to_string()))?
             .to_string();
-        let path = parts
+        let target = parts
             .next()
             .ok_or_else(|| RequestError::InvalidRequest("Missing request path".to_string()))?
             .to_string();
-        // Optionally, parse HTTP version if needed
-        // let _version = parts.next().ok_or_else(|| RequestError::InvalidRequest("Missing HTTP version".to_string()))?.to_string();
+        let (path, query_fields) = Self::parse_target(&target)?;
+        let version = parts.next().unwrap_or("HTTP/1.0").to_string();
+        if !version.starts_with("HTTP/") {
+            return Err(RequestError::InvalidRequest(format!("Invalid HTTP version: {}", version)));
+        }
 
-        let mut header_fields: HashMap<String, String> = HashMap::new();
-        let mut current_header = None;
+        let mut headers: Vec<(String, String)> = Vec::new();
 
         // Parse headers
         for line in &mut lines {
@@ -71,18 +136,17 @@ impl Request {
             let line_str = String::from_utf8_lossy(line);
             if line_str.starts_with(' ') || line_str.starts_with('\t') {
                 // Continuation of the previous header
-                if let Some(ref header_name) = current_header {
-                    if let Some(header_value) = header_fields.get_mut(header_name) {
-                        header_value.push(' ');
-                        header_value.push_str(line_str.trim());
-                    }
+                if let Some((_, header_value)) = headers.last_mut() {
+                    header_value.push(' ');
+                    header_value.push_str(line_str.trim());
                 }
             } else {
                 if let Some((key, value)) = line_str.split_once(':') {
-                    let key = key.trim().to_lowercase(); // Normalize to lowercase for case-insensitive matching
-                    let value = value.trim().to_string();
-                    header_fields.insert(key.clone(), value);
-                    current_header = Some(key);
+                    // Whitespace before the colon is not allowed either, as proxies disagree on it
+                    if !is_token(key) {
+                        return Err(RequestError::InvalidRequest(format!("Invalid header name: {:?}", key)));
+                    }
+                    headers.push((key.to_string(), value.trim().to_string()));
                 } else {
                     return Err(RequestError::InvalidRequest(format!(
                         "Invalid header line: {}",
@@ -92,61 +156,142 @@ impl Request {
             }
         }
 
-        // Parse body based on Content-Length
-        let body = if let Some(content_length_str) = header_fields.get("content-length") {
-            let content_length: usize = content_length_str.parse().map_err(|_| {
-                RequestError::InvalidRequest("Invalid Content-Length value".to_string())
-            })?;
-
-            logln(&format!("Content-Length: {}", content_length));
-
-            let mut body = Vec::with_capacity(content_length);
-            for _ in 0..content_length {
-                if let Some(&byte) = request.get(lines.clone().count()) {
-                    body.push(byte);
-                }
-            }
-
-            // Since we've already split the request into lines, we'll reconstruct the body.
-            // Alternatively, you can handle the body differently based on your server's implementation.
-            // For simplicity, we'll assume the body starts after the first empty line.
-
-            let body_start = request
-                .windows(4)
-                .position(|window| window == b"\r\n\r\n")
-                .map(|pos| pos + 4)
-                .unwrap_or(request.len());
-
-            request[body_start..].to_vec()
-        } else {
-            Vec::new()
-        };
+        let header_fields = HeaderMap::from_unchecked(headers);
+        Self::check_framing(&header_fields)?;
+        let cookie_fields = Cookie::parse_header(&header_fields.get_all("cookie").join("; "));
 
         Ok(Self {
             method,
+            target,
             path,
+            query_fields,
+            version,
             header_fields,
-            body,
+            cookie_fields,
+            body: Vec::new(),
+            body_file: None,
+            params: HashMap::new(),
+            session: None,
         })
     }
 
+    /// Rejects headers that leave the body length ambiguous, so a proxy in front of the
+    /// server cannot split the stream into different requests than the server does.
+    fn check_framing(header_fields: &HeaderMap) -> Result<(), RequestError> {
+        let Some(codings) = header_fields.get_joined("transfer-encoding") else {
+            return Ok(());
+        };
+        if header_fields.contains("content-length") {
+            return Err(RequestError::InvalidRequest(
+                "Both Transfer-Encoding and Content-Length are present".to_string(),
+            ));
+        }
+        // Only chunked framing is decoded, so any other coding would reach handlers still applied
+        if !codings.trim().eq_ignore_ascii_case("chunked") {
+            return Err(RequestError::UnsupportedTransferEncoding(codings));
+        }
+        Ok(())
+    }
+
+    /// Splits a request target into its decoded path and query parameters.
+    fn parse_target(target: &str) -> Result<(String, HashMap<String, Vec<String>>), RequestError> {
+        let target = target.split('#').next().unwrap_or("");
+        let (raw_path, raw_query) = target.split_once('?').unwrap_or((target, ""));
+
+        let path = percent_decode(raw_path).ok_or_else(|| {
+            RequestError::InvalidRequest(format!("Invalid percent-encoding in path: {}", raw_path))
+        })?;
+
+        let pairs = parse_urlencoded(raw_query).ok_or_else(|| {
+            RequestError::InvalidRequest(format!("Invalid percent-encoding in query: {}", raw_query))
+        })?;
+        let mut query_fields: HashMap<String, Vec<String>> = HashMap::new();
+        for (key, value) in pairs {
+            query_fields.entry(key).or_default().push(value);
+        }
+
+        Ok((path, query_fields))
+    }
+
+    /// Returns the offset just past the blank line that ends the request head, if present.
+    pub fn find_head_end(request: &[u8]) -> Option<usize> {
+        request
+            .windows(4)
+            .position(|window| window == b"\r\n\r\n")
+            .map(|pos| pos + 4)
+    }
+
+    /// Returns the parsed `Content-Length` header, if the request has one.
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(Some(usize))` with the announced body length.
+    /// * `Ok(None)` if the header is absent.
+    /// * `Err(RequestError)` if the header is not a valid length, or is repeated with different values.
+    pub fn content_length(&self) -> Result<Option<usize>, RequestError> {
+        let mut content_length = None;
+        for value in self.header_fields.get_all("content-length") {
+            let length: usize = value.trim().parse().map_err(|_| {
+                RequestError::InvalidRequest("Invalid Content-Length value".to_string())
+            })?;
+            if content_length.is_some_and(|existing| existing != length) {
+                return Err(RequestError::InvalidRequest("Conflicting Content-Length values".to_string()));
+            }
+            content_length = Some(length);
+        }
+        Ok(content_length)
+    }
+
+    /// Returns `true` if the body is sent with `Transfer-Encoding: chunked`.
+    ///
+    /// Requests with any other transfer coding are rejected when they are parsed.
+    pub fn is_chunked(&self) -> bool {
+        self.header_fields
+            .get_joined("transfer-encoding")
+            .is_some_and(|codings| codings.trim().eq_ignore_ascii_case("chunked"))
+    }
+
+    /// Returns `true` if the client expects the connection to stay open after this request.
+    ///
+    /// HTTP/1.1 connections are persistent unless `Connection: close` is sent, while
+    /// HTTP/1.0 connections close unless `Connection: keep-alive` is sent.
+    pub fn keep_alive(&self) -> bool {
+        if self.version == "HTTP/1.0" {
+            self.header_fields.has_token("connection", "keep-alive")
+        } else {
+            !self.header_fields.has_token("connection", "close")
+        }
+    }
+
     /// Retrieves the value of a specific header.
     ///
     /// # Arguments
     ///
-    /// * `header_name` - The name of the header to retrieve.
+    /// * `header_name` - The name of the header to retrieve, matched case-insensitively.
     ///
     /// # Returns
     ///
-    /// * `Ok(&str)` containing the header value if found.
+    /// * `Ok(&str)` containing the first value of the header if found.
     /// * `Err(RequestError)` with an error message if the header is not found.
     pub fn get_header_value<'a>(&'a self, header_name: &str) -> Result<&'a str, RequestError> {
         self.header_fields
-            .get(&header_name.to_lowercase())
-            .map(|value| value.as_str())
+            .get(header_name)
             .ok_or_else(|| RequestError::HeaderNotFound(header_name.to_string()))
     }
 
+    /// Retrieves every value of a header that was sent more than once.
+    ///
+    /// # Arguments
+    ///
+    /// * `header_name` - The name of the header to retrieve, matched case-insensitively.
+    ///
+    /// # Returns
+    ///
+    /// * `Vec<&str>` with the values in the order they were received, empty if the header is absent.
+    pub fn get_header_values(&self, header_name: &str) -> Vec<&str> {
+        self.header_fields.get_all(header_name)
+    }
+
     /// Retrieves the value of a specific header or returns a default value if not found.
     ///
     /// # Arguments
@@ -162,14 +307,100 @@ impl Request {
         header_name: &str,
         default: &'a str,
     ) -> &'a str {
-        self.header_fields
-            .get(&header_name.to_lowercase())
+        self.header_fields.get(header_name).unwrap_or(default)
+    }
+
+    /// Retrieves the id of the last Server-Sent Event the client received before reconnecting.
+    ///
+    /// # Returns
+    ///
+    /// * `Some(&str)` with the `Last-Event-ID` header if the client is resuming a stream.
+    /// * `None` for a new stream.
+    pub fn last_event_id(&self) -> Option<&str> {
+        self.header_fields.get("last-event-id")
+    }
+
+    /// Retrieves the value of a cookie sent by the client.
+    ///
+    /// # Arguments
+    ///
+    /// * `name` - The cookie name, matched case-sensitively.
+    ///
+    /// # Returns
+    ///
+    /// * `Some(&str)` containing the cookie value if the client sent it.
+    /// * `None` if there is no such cookie.
+    pub fn cookie(&self, name: &str) -> Option<&str> {
+        self.cookie_fields.get(name).map(|value| value.as_str())
+    }
+
+    /// Retrieves the session of the client that sent the request.
+    ///
+    /// # Returns
+    ///
+    /// * `Some(&Session)` if a `SessionMiddleware` is applied.
+    /// * `None` if sessions are not enabled.
+    pub fn session(&self) -> Option<&Session> {
+        self.session.as_ref()
+    }
+
+    /// Retrieves the first value of a query parameter.
+    ///
+    /// # Arguments
+    ///
+    /// * `name` - The decoded parameter name.
+    ///
+    /// # Returns
+    ///
+    /// * `Some(&str)` containing the first value if the parameter is present.
+    /// * `None` if the query string does not contain the parameter.
+    pub fn query(&self, name: &str) -> Option<&str> {
+        self.query_fields
+            .get(name)
+            .and_then(|values| values.first())
             .map(|value| value.as_str())
-            .unwrap_or(default)
+    }
+
+    /// Retrieves every value of a repeated query parameter, such as `?tag=a&tag=b`.
+    ///
+    /// # Arguments
+    ///
+    /// * `name` - The decoded parameter name.
+    ///
+    /// # Returns
+    ///
+    /// * `Vec<&str>` with the values in the order they appear, empty if the parameter is absent.
+    pub fn query_all(&self, name: &str) -> Vec<&str> {
+        self.query_fields
+            .get(name)
+            .map(|values| values.iter().map(|value| value.as_str()).collect())
+            .unwrap_or_default()
+    }
+
+    /// Retrieves a path parameter captured by the matched route, parsed into `T`.
+    ///
+    /// # Arguments
+    ///
+    /// * `name` - The parameter name as written in the route pattern, without the `:` or `*`.
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(T)` containing the parsed parameter.
+    /// * `Err(RequestError)` if the parameter is missing or cannot be parsed.
+    pub fn param<T: FromStr>(&self, name: &str) -> Result<T, RequestError> {
+        let value = self
+            .params
+            .get(name)
+            .ok_or_else(|| RequestError::ParamNotFound(name.to_string()))?;
+        value.parse().map_err(|_| {
+            RequestError::InvalidRequest(format!("Invalid value for path parameter '{}': {}", name, value))
+        })
     }
 
     /// Retrieves the request body as a byte slice.
     ///
+    /// Empty for a large multipart body that the server wrote to `body_file` instead.
+    ///
     /// # Returns
     ///
     /// * `&[u8]` containing the request body.
@@ -186,6 +417,100 @@ impl Request {
     pub fn get_body_as_str(&self) -> Result<&str, std::str::Utf8Error> {
         std::str::from_utf8(&self.body)
     }
+
+    /// Retrieves the parsed `Content-Type` of the body, if the header is present.
+    pub fn content_type(&self) -> Option<Mime> {
+        self.header_fields.content_type()
+    }
+
+    /// Parses an `application/x-www-form-urlencoded` body.
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(HashMap)` mapping each field name to its values in order of appearance.
+    /// * `Err(RequestError)` if the body has another content type or is not validly encoded.
+    pub fn parse_form(&self) -> Result<HashMap<String, Vec<String>>, RequestError> {
+        self.expect_content_type(Mime::FormUrlEncoded)?;
+
+        let body = self.get_body_as_str().map_err(|_| {
+            RequestError::InvalidRequest("Form body is not valid UTF-8".to_string())
+        })?;
+        let pairs = parse_urlencoded(body).ok_or_else(|| {
+            RequestError::InvalidRequest("Invalid percent-encoding in form body".to_string())
+        })?;
+
+        let mut fields: HashMap<String, Vec<String>> = HashMap::new();
+        for (key, value) in pairs {
+            fields.entry(key).or_default().push(value);
+        }
+        Ok(fields)
+    }
+
+    /// Parses a `multipart/form-data` body, copying parts over `DEFAULT_FILE_THRESHOLD` bytes to temporary files.
+    ///
+    /// Bodies over `ServerConfig::multipart_spool_size` are written to `body_file` while they
+    /// are received and parsed from there, so large uploads never sit in memory whole.
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(Multipart)` containing the parts in order.
+    /// * `Err(RequestError)` if the body has another content type or is malformed.
+    pub fn parse_multipart(&self) -> Result<Multipart, RequestError> {
+        self.parse_multipart_with_threshold(DEFAULT_FILE_THRESHOLD)
+    }
+
+    /// Parses the `multipart/form-data` body, copying parts larger than `file_threshold`
+    /// bytes to temporary files that are removed when the part is dropped.
+    pub fn parse_multipart_with_threshold(&self, file_threshold: usize) -> Result<Multipart, RequestError> {
+        let content_type = self.expect_content_type(Mime::MultipartFormData)?;
+        let boundary = content_type.param("boundary").ok_or_else(|| {
+            RequestError::InvalidRequest("Multipart Content-Type has no boundary".to_string())
+        })?;
+
+        match &self.body_file {
+            Some(body_file) => {
+                let file = std::fs::File::open(body_file.path()).map_err(|e| {
+                    RequestError::InvalidRequest(format!("Failed to open spooled body: {}", e))
+                })?;
+                parse_multipart_from(file, boundary, file_threshold)
+            }
+            None => parse_multipart(&self.body, boundary, file_threshold),
+        }
+    }
+
+    /// Deserializes a JSON body into `T`.
+    ///
+    /// Accepts `application/json` and any `+json` type.
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(T)` containing the deserialized value.
+    /// * `Err(RequestError)` if the body has another content type or does not match `T`.
+    pub fn parse_json<T: DeserializeOwned>(&self) -> Result<T, RequestError> {
+        let content_type = self.content_type().unwrap_or(Mime::OctetStream);
+        let essence = content_type.to_string();
+        if essence != Mime::ApplicationJson.to_string() && !essence.ends_with("+json") {
+            return Err(RequestError::ContentTypeMismatch {
+                expected: Mime::ApplicationJson.to_string().to_owned(),
+                found: essence.to_owned(),
+            });
+        }
+
+        serde_json::from_slice(&self.body)
+            .map_err(|e| RequestError::InvalidRequest(format!("Invalid JSON body: {}", e)))
+    }
+
+    /// Returns the body's content type if its essence is `expected`.
+    fn expect_content_type(&self, expected: Mime) -> Result<Mime, RequestError> {
+        let content_type = self.content_type().unwrap_or(Mime::OctetStream);
+        if content_type.essence() != &expected {
+            return Err(RequestError::ContentTypeMismatch {
+                expected: expected.to_string().to_owned(),
+                found: content_type.to_string().to_owned(),
+            });
+        }
+        Ok(content_type)
+    }
 }
 
 // Debug implementation for Request
@@ -193,9 +518,74 @@ impl std::fmt::Debug for Request {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         f.debug_struct("Request")
             .field("method", &self.method)
+            .field("target", &self.target)
             .field("path", &self.path)
+            .field("query_fields", &self.query_fields)
+            .field("version", &self.version)
             .field("header_fields", &self.header_fields)
+            .field("cookie_fields", &self.cookie_fields)
             .field("body", &format!("{:?}", self.body))
+            .field("body_file", &self.body_file)
+            .field("params", &self.params)
+            .field("session", &self.session.as_ref().map(|session| session.id()))
             .finish()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reads_body_by_content_length() {
+        let request = Request::from_bytes(b"POST /a?x=1 HTTP/1.1\r\nContent-Length: 3\r\n\r\nabcdef").unwrap();
+        assert_eq!(request.path, "/a");
+        assert_eq!(request.query("x"), Some("1"));
+        assert_eq!(request.body, b"abc");
+    }
+
+    #[test]
+    fn reads_chunked_body() {
+        let request =
+            Request::from_bytes(b"POST / HTTP/1.1\r\nTransfer-Encoding: chunked\r\n\r\n3\r\nabc\r\n0\r\n\r\n").unwrap();
+        assert_eq!(request.body, b"abc");
+    }
+
+    #[test]
+    fn rejects_header_names_that_are_not_tokens() {
+        assert!(Request::from_head(b"GET / HTTP/1.1\r\nX-Ok: 1\r\n\r\n").is_ok());
+        for head in [
+            &b"GET / HTTP/1.1\r\nBad Name: 1\r\n\r\n"[..],
+            b"GET / HTTP/1.1\r\nContent-Length : 5\r\n\r\n",
+            b"GET / HTTP/1.1\r\n: empty\r\n\r\n",
+        ] {
+            assert!(Request::from_head(head).is_err(), "{:?}", String::from_utf8_lossy(head));
+        }
+    }
+
+    #[test]
+    fn rejects_transfer_encoding_with_content_length() {
+        let head = b"POST / HTTP/1.1\r\nContent-Length: 5\r\nTransfer-Encoding: chunked\r\n\r\n";
+        assert!(Request::from_head(head).is_err());
+        let request = b"POST / HTTP/1.1\r\nTransfer-Encoding: chunked\r\nContent-Length: 3\r\n\r\n0\r\n\r\n";
+        assert!(Request::from_bytes(request).is_err());
+    }
+
+    #[test]
+    fn rejects_transfer_codings_other_than_chunked() {
+        for codings in ["gzip", "gzip, chunked", "chunked, chunked", "identity"] {
+            let head = format!("POST / HTTP/1.1\r\nTransfer-Encoding: {}\r\n\r\n", codings);
+            let err = Request::from_head(head.as_bytes()).unwrap_err();
+            assert!(matches!(err, RequestError::UnsupportedTransferEncoding(_)), "{}", codings);
+        }
+        let split = b"POST / HTTP/1.1\r\nTransfer-Encoding: gzip\r\nTransfer-Encoding: chunked\r\n\r\n";
+        assert!(Request::from_head(split).is_err());
+        assert!(Request::from_head(b"POST / HTTP/1.1\r\nTransfer-Encoding: Chunked\r\n\r\n").unwrap().is_chunked());
+    }
+
+    #[test]
+    fn rejects_conflicting_content_lengths() {
+        let request = Request::from_head(b"POST / HTTP/1.1\r\nContent-Length: 3\r\nContent-Length: 4\r\n\r\n").unwrap();
+        assert!(request.content_length().is_err());
+    }
+}
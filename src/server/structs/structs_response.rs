@@ -1,20 +1,40 @@
 // src/server/structs/structs_response.rs
 
-use std::collections::HashMap;
 use std::fmt;
+use std::future::Future;
+use std::io::Write;
+
+use tokio::io::{AsyncWrite, AsyncWriteExt};
 
 use crate::util::logging::logln;
+use crate::server::server_chunked::{encode_chunk, LAST_CHUNK};
+use crate::server::websocket::websocket_core::{WebSocket, WebSocketUpgrade};
+use crate::server::websocket::websocket_handshake;
 
+use super::structs_cookie::Cookie;
+use super::structs_body::{body_channel, BodyStream, BodyWriter};
+use super::structs_event::EventSender;
 use super::structs_header::StatusCode;
+use super::structs_headermap::HeaderMap;
 use super::structs_mime::Mime;
+use super::structs_request::{Request, RequestError};
 
 /// Represents an HTTP response.
 #[derive(Default, Debug)]
 pub struct Response {
     pub status_code: u16,
     pub status_message: String,
-    pub header_fields: HashMap<String, String>,
+    pub header_fields: HeaderMap,
+    /// Cookies to set, each sent as its own `Set-Cookie` header line.
+    pub cookies: Vec<Cookie>,
     pub body: Vec<u8>,
+    /// Incrementally produced body, sent with `Transfer-Encoding: chunked` instead of `body`,
+    /// or unframed to HTTP/1.0 clients.
+    pub stream: Option<BodyStream>,
+    /// Handler that takes over the connection after a `101 Switching Protocols` response.
+    pub upgrade: Option<WebSocketUpgrade>,
+    /// Sends `stream` unframed and ends it by closing the connection, for HTTP/1.0 clients.
+    pub(crate) close_delimited: bool,
 }
 
 /// Custom error type for Response operations.
@@ -22,6 +42,7 @@ pub struct Response {
 pub enum ResponseError {
     InvalidStatusCode(u16),
     InvalidHeader(String),
+    InvalidCookie(String),
 }
 
 impl fmt::Display for ResponseError {
@@ -29,6 +50,7 @@ impl fmt::Display for ResponseError {
         match self {
             ResponseError::InvalidStatusCode(code) => write!(f, "Invalid status code: {}", code),
             ResponseError::InvalidHeader(header) => write!(f, "Invalid header: {}", header),
+            ResponseError::InvalidCookie(cookie) => write!(f, "Invalid cookie: {}", cookie),
         }
     }
 }
@@ -41,28 +63,62 @@ impl Response {
         Self {
             status_code,
             status_message: status_message.to_string(),
-            header_fields: HashMap::new(),
+            header_fields: HeaderMap::new(),
+            cookies: Vec::new(),
             body: Vec::new(),
+            stream: None,
+            upgrade: None,
+            close_delimited: false,
         }
     }
 
-    /// Sets a header field, replacing any existing value.
-    
-    pub fn set_header(&mut self, key: &str, value: &str) {
-        self.header_fields.insert(key.to_string(), value.to_string());
+    /// Creates a new `Response` with the given status and its standard reason phrase.
+    pub fn from_status(status: StatusCode) -> Self {
+        Self::new(status.to_code(), status.to_msg())
+    }
+
+    /// Replaces the status code and reason phrase.
+    pub fn set_status(&mut self, status: StatusCode) {
+        self.status_code = status.to_code();
+        self.status_message = status.to_msg().to_string();
+    }
+
+    /// Returns the status of the response.
+    ///
+    /// Fails with `ResponseError::InvalidStatusCode` if `status_code` is not a known HTTP status.
+    pub fn status(&self) -> Result<StatusCode, ResponseError> {
+        StatusCode::from_code(self.status_code)
+    }
+
+    /// Sets a header field, replacing any existing value regardless of the name's case.
+    ///
+    /// Fails with `ResponseError::InvalidHeader` if the name is not a token or the value
+    /// contains control characters such as CR or LF.
+    pub fn set_header(&mut self, key: &str, value: &str) -> Result<(), ResponseError> {
+        self.header_fields.insert(key, value)
+    }
+
+    /// Adds a header field. If the header already exists, the value is sent on an additional line.
+    ///
+    /// Validated like `set_header`.
+    pub fn add_header(&mut self, key: &str, value: &str) -> Result<(), ResponseError> {
+        self.header_fields.append(key, value)
     }
 
-    /// Adds a header field. If the header already exists, appends the value separated by a comma.
-   
-    pub fn add_header(&mut self, key: &str, value: &str) {
-        let key_lower = key.to_lowercase();
-        self.header_fields
-            .entry(key_lower)
-            .and_modify(|existing| {
-                existing.push_str(", ");
-                existing.push_str(value);
-            })
-            .or_insert_with(|| value.to_string());
+    /// Retrieves the first value of a header field, matched case-insensitively.
+    pub fn get_header(&self, key: &str) -> Option<&str> {
+        self.header_fields.get(key)
+    }
+
+    /// Adds a cookie to the response, replacing any cookie already set with the same name and path.
+    ///
+    /// Fails with `ResponseError::InvalidCookie` if the cookie does not pass `Cookie::validate`.
+    pub fn set_cookie(&mut self, cookie: Cookie) -> Result<(), ResponseError> {
+        cookie.validate()?;
+        self.cookies
+            .retain(|existing| existing.name != cookie.name || existing.path != cookie.path);
+        self.cookies.push(cookie);
+        Ok(())
     }
 
     /// Sets the body of the response with raw bytes.
@@ -75,46 +131,249 @@ impl Response {
         self.body = body.as_bytes().to_vec();
     }
 
+    /// Replaces the body with a stream and returns the writer that feeds it.
+    pub fn set_body_stream(&mut self) -> BodyWriter {
+        let (writer, stream) = body_channel();
+        self.body.clear();
+        self.stream = Some(stream);
+        writer
+    }
+
+    /// Returns `true` if the body is streamed rather than held in `body`.
+    pub fn is_streamed(&self) -> bool {
+        self.stream.is_some()
+    }
+
+    /// Returns `false` for the statuses that never carry a body: 1xx, `204 No Content` and `304 Not Modified`.
+    pub fn allows_body(&self) -> bool {
+        !(self.status_code < 200 || self.status_code == 204 || self.status_code == 304)
+    }
+
     /// Serializes the `Response` into a byte vector suitable for sending over a network.
+    ///
+    /// For streamed responses only the head is produced; use `write_to` to send the chunks.
     pub fn to_bytes(&self) -> Vec<u8> {
+        let mut response = self.head_to_bytes();
+
+        // Body, left out for the statuses that cannot have one so it is not read as the next response
+        if !self.is_streamed() && self.allows_body() {
+            response.extend_from_slice(&self.body);
+        }
+
+        response
+    }
+
+    /// Serializes the status line and headers, including the blank line that ends them.
+    pub fn head_to_bytes(&self) -> Vec<u8> {
         let mut response = Vec::new();
 
         // Status line
         let status_line = format!("HTTP/1.1 {} {}\r\n", self.status_code, self.status_message);
         response.extend_from_slice(status_line.as_bytes());
 
-        // Headers
-        for (key, value) in &self.header_fields {
+        // Headers, with the body framing always derived by the server so clients can delimit the response
+        for (key, value) in self.header_fields.iter() {
+            if key.eq_ignore_ascii_case("content-length") || key.eq_ignore_ascii_case("transfer-encoding") {
+                continue;
+            }
             let header_line = format!("{}: {}\r\n", key, value);
             response.extend_from_slice(header_line.as_bytes());
         }
+        for cookie in &self.cookies {
+            match cookie.to_header_value() {
+                Ok(value) => response.extend_from_slice(format!("Set-Cookie: {}\r\n", value).as_bytes()),
+                Err(err) => logln(&format!("Dropped cookie: {}", err)),
+            }
+        }
+        if !self.allows_body() || (self.is_streamed() && self.close_delimited) {
+            // These statuses never carry a body, so they must not announce a length,
+            // and a close-delimited stream ends when the connection does
+        } else if self.is_streamed() {
+            response.extend_from_slice(b"Transfer-Encoding: chunked\r\n");
+        } else {
+            response.extend_from_slice(format!("Content-Length: {}\r\n", self.body.len()).as_bytes());
+        }
 
         // Blank line to indicate end of headers
         response.extend_from_slice(b"\r\n");
 
-        // Body
-        response.extend_from_slice(&self.body);
-
         response
     }
+
+    /// Writes the full response to `writer`, sending a streamed body chunk by chunk as it is produced.
+    ///
+    /// Blocks while waiting for chunks, so it must not be called from an async context.
+    pub fn write_to<W: Write>(mut self, writer: &mut W) -> std::io::Result<()> {
+        writer.write_all(&self.to_bytes())?;
+
+        let allows_body = self.allows_body();
+        if let Some(stream) = self.stream.as_mut().filter(|_| allows_body) {
+            writer.flush()?;
+            while let Some(chunk) = stream.blocking_next_chunk() {
+                if self.close_delimited {
+                    writer.write_all(&chunk)?;
+                } else {
+                    writer.write_all(&encode_chunk(&chunk))?;
+                }
+                writer.flush()?;
+            }
+            if !self.close_delimited {
+                writer.write_all(LAST_CHUNK)?;
+            }
+        }
+
+        writer.flush()
+    }
+
+    /// Async counterpart of `write_to`, used by the server to send responses without blocking.
+    pub async fn write_to_async<W: AsyncWrite + Unpin>(self, writer: &mut W) -> std::io::Result<()> {
+        self.write_to_async_until(writer, std::future::pending()).await
+    }
+
+    /// Like `write_to_async`, but ends a streamed body early once `stop` completes.
+    ///
+    /// The body is terminated properly and the stream is dropped, so its writers see the
+    /// response as closed. The server uses this to end long-lived streams on shutdown.
+    /// A close-delimited stream is written without chunk framing; the caller must close
+    /// the connection after it.
+    pub async fn write_to_async_until<W, F>(mut self, writer: &mut W, stop: F) -> std::io::Result<()>
+    where
+        W: AsyncWrite + Unpin,
+        F: Future<Output = ()>,
+    {
+        writer.write_all(&self.to_bytes()).await?;
+
+        if let Some(mut stream) = self.stream.take().filter(|_| self.allows_body()) {
+            writer.flush().await?;
+            tokio::pin!(stop);
+            loop {
+                let chunk = tokio::select! {
+                    chunk = stream.next_chunk() => chunk,
+                    _ = &mut stop => None,
+                };
+                let Some(chunk) = chunk else { break };
+                if self.close_delimited {
+                    writer.write_all(&chunk).await?;
+                } else {
+                    writer.write_all(&encode_chunk(&chunk)).await?;
+                }
+                writer.flush().await?;
+            }
+            drop(stream);
+            if !self.close_delimited {
+                writer.write_all(LAST_CHUNK).await?;
+            }
+        }
+
+        writer.flush().await
+    }
 }
 
 
 impl Response {
+    /// Sets the content type of a preset, leaving it unset if a custom `Mime` is not a valid header value.
+    fn set_preset_content_type(&mut self, mime: &Mime) {
+        if let Err(e) = self.header_fields.set_content_type(mime) {
+            logln(&format!("Content-Type not set: {}", e));
+        }
+    }
+
     // Response presets
     pub fn response_ok(body: Vec<u8>, mime: Mime) -> Self {
         let code = StatusCode::Ok.to_code();
         let msg = StatusCode::Ok.to_msg();
         let mut response = Self::new(code, msg);
-        response.set_header("Content-Type", mime.to_string());
+        response.set_preset_content_type(&mime);
 
         response.set_body(body);
         response
     }
 
+    pub fn response_stream(mime: Mime) -> (Self, BodyWriter) {
+        let code = StatusCode::Ok.to_code();
+        let msg = StatusCode::Ok.to_msg();
+        let mut response = Self::new(code, msg);
+        response.set_preset_content_type(&mime);
+
+        let writer = response.set_body_stream();
+        (response, writer)
+    }
+
+    /// Starts a Server-Sent Events stream; events are written with the returned sender.
+    ///
+    /// Clients resuming a stream send the id of the last event they received, available
+    /// from `Request::last_event_id`.
+    pub fn response_sse() -> (Self, EventSender) {
+        let (mut response, writer) = Self::response_stream(Mime::EventStream);
+        response.set_header("Cache-Control", "no-cache").expect("Cache-Control is a valid header");
+        (response, EventSender::new(writer))
+    }
+
+    pub fn response_created(body: Vec<u8>, mime: Mime) -> Self {
+        let mut response = Self::from_status(StatusCode::Created);
+        response.set_preset_content_type(&mime);
+
+        response.set_body(body);
+        response
+    }
+
+    pub fn response_no_content() -> Self {
+        Self::from_status(StatusCode::NoContent)
+    }
+
+    pub fn response_not_modified() -> Self {
+        Self::from_status(StatusCode::NotModified)
+    }
+
+    /// Redirects to `location`; `code` should be one of the 3xx statuses such as `Found` or `SeeOther`.
+    ///
+    /// Fails if `location` contains characters that cannot be sent in a header.
+    pub fn response_redirect(location: &str, code: StatusCode) -> Result<Self, ResponseError> {
+        let mut response = Self::from_status(code);
+        response.set_header("Location", location)?;
+        Ok(response)
+    }
+
+    /// Accepts a WebSocket handshake; once the response is sent, `handler` runs with the open socket.
+    ///
+    /// Handshakes from pages on another origin are answered with `403 Forbidden`.
+    pub fn response_websocket<F, Fut>(request: &Request, handler: F) -> Result<Self, RequestError>
+    where
+        F: FnOnce(WebSocket) -> Fut + Send + 'static,
+        Fut: std::future::Future<Output = ()> + Send + 'static,
+    {
+        Self::response_websocket_with_origins(request, &[], handler)
+    }
+
+    /// Like `response_websocket`, also accepting pages served from `allowed_origins`,
+    /// such as `http://localhost:5173`.
+    pub fn response_websocket_with_origins<F, Fut>(request: &Request, allowed_origins: &[String], handler: F) -> Result<Self, RequestError>
+    where
+        F: FnOnce(WebSocket) -> Fut + Send + 'static,
+        Fut: std::future::Future<Output = ()> + Send + 'static,
+    {
+        websocket_handshake::accept(request, WebSocketUpgrade::new(handler), allowed_origins)
+    }
+
+    /// Like `response_websocket`, for a handler that uses the blocking socket methods.
+    pub fn response_websocket_blocking<F>(request: &Request, handler: F) -> Result<Self, RequestError>
+    where
+        F: FnOnce(WebSocket) + Send + 'static,
+    {
+        websocket_handshake::accept(request, WebSocketUpgrade::blocking(handler), &[])
+    }
+
+    pub fn response_method_not_allowed(allowed: &[String]) -> Self {
+        let mut response = Self::response_error("Method Not Allowed".to_owned(), StatusCode::MethodNotAllowed);
+        if let Err(e) = response.set_header("Allow", &allowed.join(", ")) {
+            logln(&format!("Allow not set: {}", e));
+        }
+        response
+    }
+
     pub fn response_error (error: String, code: StatusCode) -> Self {
         let mut response = Self::new(code.to_code(), code.to_msg());
-        response.set_header("Content-Type", Mime::TextPlain.to_string());
+        response.set_preset_content_type(&Mime::TextPlain);
 
         response.set_body(error.to_string().as_bytes().to_vec());
         response
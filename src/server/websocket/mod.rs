@@ -0,0 +1,6 @@
+#[allow(unused)]
+pub mod websocket_core;
+#[allow(unused)]
+pub mod websocket_frame;
+#[allow(unused)]
+pub mod websocket_handshake;
\ No newline at end of file
@@ -0,0 +1,381 @@
+// src/server/websocket/websocket_core.rs
+
+use std::fmt;
+use std::future::Future;
+use std::io::{Error, ErrorKind, Result};
+use std::pin::Pin;
+use std::time::Duration;
+
+use tokio::io::{AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt};
+use tokio::sync::{mpsc, oneshot};
+use tokio::time::{timeout_at, Instant};
+
+use crate::util::logging::logln;
+
+use super::websocket_frame::{
+    decode_close_payload, encode_close_payload, encode_frame, parse_frame, FrameError, Opcode, CLOSE_GOING_AWAY,
+    CLOSE_NORMAL,
+};
+
+/// Number of messages buffered in each direction before the sender has to wait.
+const CHANNEL_CAPACITY: usize = 16;
+
+/// How long to wait for the client to answer a close frame before dropping the connection.
+const CLOSE_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// A complete WebSocket data message.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Message {
+    Text(String),
+    Binary(Vec<u8>),
+}
+
+/// Frames queued for the connection by a `WebSocketSender`.
+#[derive(Debug)]
+enum Outgoing {
+    Frame(Opcode, Vec<u8>),
+    Close(u16, String),
+}
+
+/// Sending half of a WebSocket, cheap to clone so any task or thread can push messages.
+///
+/// The connection is closed normally once the `WebSocket` and all of its senders are dropped.
+#[derive(Debug, Clone)]
+pub struct WebSocketSender {
+    tx: mpsc::Sender<Outgoing>,
+}
+
+impl WebSocketSender {
+    async fn queue(&self, outgoing: Outgoing) -> Result<()> {
+        self.tx.send(outgoing).await.map_err(|_| closed_error())
+    }
+
+    fn blocking_queue(&self, outgoing: Outgoing) -> Result<()> {
+        self.tx.blocking_send(outgoing).map_err(|_| closed_error())
+    }
+
+    /// Sends a message, waiting while the outgoing buffer is full.
+    ///
+    /// Fails with `ErrorKind::NotConnected` once the connection is closed.
+    pub async fn send(&self, message: Message) -> Result<()> {
+        self.queue(message_frame(message)).await
+    }
+
+    /// Blocking counterpart of `send`, for use outside an async context.
+    pub fn blocking_send(&self, message: Message) -> Result<()> {
+        self.blocking_queue(message_frame(message))
+    }
+
+    /// Sends a text message.
+    pub async fn send_text(&self, text: &str) -> Result<()> {
+        self.send(Message::Text(text.to_string())).await
+    }
+
+    /// Sends a ping; the client's pong is handled by the connection.
+    pub async fn ping(&self, payload: &[u8]) -> Result<()> {
+        let payload = payload[..payload.len().min(125)].to_vec();
+        self.queue(Outgoing::Frame(Opcode::Ping, payload)).await
+    }
+
+    /// Starts the closing handshake with the given close code and reason.
+    pub async fn close(&self, code: u16, reason: &str) -> Result<()> {
+        self.queue(Outgoing::Close(code, reason.to_string())).await
+    }
+
+    /// Returns `true` once the connection has been closed.
+    pub fn is_closed(&self) -> bool {
+        self.tx.is_closed()
+    }
+}
+
+/// An accepted WebSocket connection, handed to the handler passed to `Response::response_websocket`.
+///
+/// Pings are answered and fragmented messages are reassembled by the server, so the handler
+/// only sees complete text and binary messages. Received messages wait in a small buffer until
+/// `recv` is called; a handler that only sends should drop the `WebSocket` and keep a sender.
+///
+/// ```ignore
+/// plugin_manager.route("GET", "/ws", |request| {
+///     Ok(Response::response_websocket(request, |mut socket: WebSocket| async move {
+///         while let Some(message) = socket.recv().await {
+///             if let Message::Text(text) = message {
+///                 let _ = socket.send(Message::Text(text.to_uppercase())).await;
+///             }
+///         }
+///     })?)
+/// })?;
+/// ```
+#[derive(Debug)]
+pub struct WebSocket {
+    sender: WebSocketSender,
+    incoming: mpsc::Receiver<Message>,
+}
+
+impl WebSocket {
+    /// Waits for the next message, or returns `None` once the connection is closed.
+    pub async fn recv(&mut self) -> Option<Message> {
+        self.incoming.recv().await
+    }
+
+    /// Blocking counterpart of `recv`, for use outside an async context.
+    pub fn blocking_recv(&mut self) -> Option<Message> {
+        self.incoming.blocking_recv()
+    }
+
+    /// Sends a message; see `WebSocketSender::send`.
+    pub async fn send(&self, message: Message) -> Result<()> {
+        self.sender.send(message).await
+    }
+
+    /// Blocking counterpart of `send`, for use outside an async context.
+    pub fn blocking_send(&self, message: Message) -> Result<()> {
+        self.sender.blocking_send(message)
+    }
+
+    /// Starts the closing handshake; `recv` returns `None` once it completes.
+    pub async fn close(&self, code: u16, reason: &str) -> Result<()> {
+        self.sender.close(code, reason).await
+    }
+
+    /// Returns a sender that can push messages from other tasks or threads.
+    pub fn sender(&self) -> WebSocketSender {
+        self.sender.clone()
+    }
+}
+
+/// Boxed form of the handler passed to `WebSocketUpgrade::new`.
+type UpgradeHandler = Box<dyn FnOnce(WebSocket) -> Pin<Box<dyn Future<Output = ()> + Send>> + Send>;
+
+/// The handler that takes over a connection once the WebSocket handshake response is sent.
+pub struct WebSocketUpgrade {
+    handler: UpgradeHandler,
+}
+
+impl WebSocketUpgrade {
+    /// Wraps an async handler.
+    pub fn new<F, Fut>(handler: F) -> Self
+    where
+        F: FnOnce(WebSocket) -> Fut + Send + 'static,
+        Fut: Future<Output = ()> + Send + 'static,
+    {
+        Self {
+            handler: Box::new(move |socket| Box::pin(handler(socket))),
+        }
+    }
+
+    /// Wraps a handler that uses the blocking socket methods; it runs on a blocking thread.
+    pub fn blocking<F>(handler: F) -> Self
+    where
+        F: FnOnce(WebSocket) + Send + 'static,
+    {
+        Self::new(move |socket| async move {
+            if let Err(e) = tokio::task::spawn_blocking(move || handler(socket)).await {
+                logln(&format!("WebSocket handler panicked: {}", e));
+            }
+        })
+    }
+}
+
+impl fmt::Debug for WebSocketUpgrade {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("WebSocketUpgrade")
+    }
+}
+
+/// Runs the WebSocket protocol on an upgraded connection until it is closed.
+///
+/// `buf` holds any bytes the client sent after the handshake request. The handler runs as a
+/// separate task, while reading and writing run concurrently here so that a handler busy
+/// sending never stops pings and close frames from being answered.
+///
+/// Once `stop` completes, the client is sent a `CLOSE_GOING_AWAY` close frame.
+pub(crate) async fn run_websocket<S, F>(stream: &mut S, buf: Vec<u8>, upgrade: WebSocketUpgrade, max_message_size: usize, stop: F)
+where
+    S: AsyncRead + AsyncWrite + Unpin,
+    F: Future<Output = ()>,
+{
+    let (incoming_tx, incoming_rx) = mpsc::channel(CHANNEL_CAPACITY);
+    let (outgoing_tx, outgoing_rx) = mpsc::channel(CHANNEL_CAPACITY);
+    let (control_tx, control_rx) = mpsc::unbounded_channel();
+    let (close_sent_tx, close_sent_rx) = oneshot::channel();
+
+    let socket = WebSocket {
+        sender: WebSocketSender { tx: outgoing_tx },
+        incoming: incoming_rx,
+    };
+    tokio::spawn((upgrade.handler)(socket));
+
+    let (reader, writer) = tokio::io::split(stream);
+    let connection = Connection {
+        incoming_tx,
+        control_tx,
+        fragments: None,
+        max_message_size,
+    };
+    let (_, mut writer) = tokio::join!(
+        connection.read_frames(reader, buf, close_sent_rx),
+        write_frames(writer, outgoing_rx, control_rx, close_sent_tx, stop),
+    );
+    let _ = writer.shutdown().await;
+}
+
+/// Writes frames queued by the handler and by the reader until a close frame has been sent.
+///
+/// Frames from the reader, such as pongs and the reply to a client's close, take priority.
+async fn write_frames<W: AsyncWrite + Unpin, F: Future<Output = ()>>(
+    mut writer: W,
+    mut outgoing_rx: mpsc::Receiver<Outgoing>,
+    mut control_rx: mpsc::UnboundedReceiver<Outgoing>,
+    close_sent_tx: oneshot::Sender<()>,
+    stop: F,
+) -> W {
+    tokio::pin!(stop);
+    loop {
+        let outgoing = tokio::select! {
+            biased;
+            control = control_rx.recv() => match control {
+                Some(control) => control,
+                // The reader is done and the connection is gone
+                None => break,
+            },
+            _ = &mut stop => Outgoing::Close(CLOSE_GOING_AWAY, "Server shutting down".to_string()),
+            // Once the handler and all senders are dropped, close normally
+            outgoing = outgoing_rx.recv() => outgoing.unwrap_or(Outgoing::Close(CLOSE_NORMAL, String::new())),
+        };
+
+        let is_close = matches!(outgoing, Outgoing::Close(..));
+        if write_outgoing(&mut writer, outgoing).await.is_err() || is_close {
+            break;
+        }
+    }
+
+    let _ = close_sent_tx.send(());
+    writer
+}
+
+/// Reading side of a connection, which reassembles messages for the handler.
+struct Connection {
+    incoming_tx: mpsc::Sender<Message>,
+    control_tx: mpsc::UnboundedSender<Outgoing>,
+    fragments: Option<(Opcode, Vec<u8>)>,
+    max_message_size: usize,
+}
+
+impl Connection {
+    /// Reads frames until the client closes the connection or the closing handshake completes.
+    async fn read_frames<R: AsyncRead + Unpin>(mut self, mut reader: R, mut buf: Vec<u8>, mut close_sent_rx: oneshot::Receiver<()>) {
+        // Set once the server has sent its close frame and is waiting for the client's
+        let mut deadline: Option<Instant> = None;
+
+        loop {
+            match self.process(&mut buf).await {
+                Ok(Some(code)) => {
+                    if deadline.is_none() {
+                        let _ = self.control_tx.send(Outgoing::Close(code, String::new()));
+                    }
+                    return;
+                }
+                Ok(None) => {}
+                Err(e) => {
+                    logln(&format!("{}", e));
+                    if deadline.is_none() {
+                        let _ = self.control_tx.send(Outgoing::Close(e.close_code(), String::new()));
+                    }
+                    return;
+                }
+            }
+
+            let read = match deadline {
+                Some(deadline) => match timeout_at(deadline, reader.read_buf(&mut buf)).await {
+                    Ok(read) => read,
+                    Err(_) => return,
+                },
+                None => tokio::select! {
+                    read = reader.read_buf(&mut buf) => read,
+                    _ = &mut close_sent_rx => {
+                        deadline = Some(Instant::now() + CLOSE_TIMEOUT);
+                        continue;
+                    }
+                },
+            };
+            match read {
+                Ok(0) | Err(_) => return,
+                Ok(_) => {}
+            }
+        }
+    }
+
+    /// Handles the complete frames in `buf`, returning the close code once the client sends a close frame.
+    async fn process(&mut self, buf: &mut Vec<u8>) -> std::result::Result<Option<u16>, FrameError> {
+        while let Some(frame) = parse_frame(buf, self.max_message_size)? {
+            match frame.opcode {
+                Opcode::Ping => {
+                    let _ = self.control_tx.send(Outgoing::Frame(Opcode::Pong, frame.payload));
+                }
+                Opcode::Pong => {}
+                Opcode::Close => {
+                    let (code, _reason) = decode_close_payload(&frame.payload)?;
+                    return Ok(Some(code));
+                }
+                Opcode::Text | Opcode::Binary => {
+                    if self.fragments.is_some() {
+                        return Err(FrameError::Protocol("New message started before the previous one ended".to_string()));
+                    }
+                    if frame.fin {
+                        self.deliver(frame.opcode, frame.payload).await?;
+                    } else {
+                        self.fragments = Some((frame.opcode, frame.payload));
+                    }
+                }
+                Opcode::Continuation => {
+                    let (opcode, mut payload) = self
+                        .fragments
+                        .take()
+                        .ok_or_else(|| FrameError::Protocol("Continuation frame without a message".to_string()))?;
+                    payload.extend_from_slice(&frame.payload);
+                    if payload.len() > self.max_message_size {
+                        return Err(FrameError::TooLarge(payload.len()));
+                    }
+                    if frame.fin {
+                        self.deliver(opcode, payload).await?;
+                    } else {
+                        self.fragments = Some((opcode, payload));
+                    }
+                }
+            }
+        }
+        Ok(None)
+    }
+
+    /// Passes a complete message to the handler, waiting while its buffer is full.
+    async fn deliver(&mut self, opcode: Opcode, payload: Vec<u8>) -> std::result::Result<(), FrameError> {
+        let message = if opcode == Opcode::Text {
+            Message::Text(String::from_utf8(payload).map_err(|_| FrameError::InvalidText)?)
+        } else {
+            Message::Binary(payload)
+        };
+        // A handler that dropped its socket simply misses the message
+        let _ = self.incoming_tx.send(message).await;
+        Ok(())
+    }
+}
+
+fn message_frame(message: Message) -> Outgoing {
+    match message {
+        Message::Text(text) => Outgoing::Frame(Opcode::Text, text.into_bytes()),
+        Message::Binary(data) => Outgoing::Frame(Opcode::Binary, data),
+    }
+}
+
+async fn write_outgoing<S: AsyncWrite + Unpin>(stream: &mut S, outgoing: Outgoing) -> Result<()> {
+    let frame = match outgoing {
+        Outgoing::Frame(opcode, payload) => encode_frame(opcode, &payload),
+        Outgoing::Close(code, reason) => encode_frame(Opcode::Close, &encode_close_payload(code, &reason)),
+    };
+    stream.write_all(&frame).await?;
+    stream.flush().await
+}
+
+fn closed_error() -> Error {
+    Error::new(ErrorKind::NotConnected, "WebSocket connection is closed")
+}
@@ -0,0 +1,307 @@
+// src/server/websocket/websocket_frame.rs
+
+use std::fmt;
+
+/// Close code for a normal closure.
+pub const CLOSE_NORMAL: u16 = 1000;
+/// Close code sent when the server is going away, for example on shutdown.
+pub const CLOSE_GOING_AWAY: u16 = 1001;
+/// Close code for a frame that violates the protocol.
+pub const CLOSE_PROTOCOL_ERROR: u16 = 1002;
+/// Close code for a text message that is not valid UTF-8.
+pub const CLOSE_INVALID_PAYLOAD: u16 = 1007;
+/// Close code for a message larger than `ServerConfig::max_message_size`.
+pub const CLOSE_TOO_LARGE: u16 = 1009;
+
+/// Largest payload allowed in a control frame.
+const MAX_CONTROL_PAYLOAD: usize = 125;
+
+/// WebSocket frame types (RFC 6455, section 5.2).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Opcode {
+    Continuation = 0x0,
+    Text = 0x1,
+    Binary = 0x2,
+    Close = 0x8,
+    Ping = 0x9,
+    Pong = 0xA,
+}
+
+impl Opcode {
+    fn from_bits(bits: u8) -> Option<Self> {
+        match bits {
+            0x0 => Some(Opcode::Continuation),
+            0x1 => Some(Opcode::Text),
+            0x2 => Some(Opcode::Binary),
+            0x8 => Some(Opcode::Close),
+            0x9 => Some(Opcode::Ping),
+            0xA => Some(Opcode::Pong),
+            _ => None,
+        }
+    }
+
+    /// Returns `true` for close, ping and pong frames.
+    pub fn is_control(&self) -> bool {
+        (*self as u8) & 0x8 != 0
+    }
+}
+
+/// A single frame received from a client, with its payload already unmasked.
+#[derive(Debug)]
+pub struct Frame {
+    pub fin: bool,
+    pub opcode: Opcode,
+    pub payload: Vec<u8>,
+}
+
+/// A frame that cannot be accepted, with the close code to answer it with.
+#[derive(Debug)]
+pub enum FrameError {
+    Protocol(String),
+    TooLarge(usize),
+    InvalidText,
+}
+
+impl FrameError {
+    /// Returns the close code to send to the client.
+    pub fn close_code(&self) -> u16 {
+        match self {
+            FrameError::Protocol(_) => CLOSE_PROTOCOL_ERROR,
+            FrameError::TooLarge(_) => CLOSE_TOO_LARGE,
+            FrameError::InvalidText => CLOSE_INVALID_PAYLOAD,
+        }
+    }
+}
+
+impl fmt::Display for FrameError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            FrameError::Protocol(msg) => write!(f, "WebSocket protocol error: {}", msg),
+            FrameError::TooLarge(size) => write!(f, "WebSocket message of {} bytes is too large", size),
+            FrameError::InvalidText => write!(f, "WebSocket text message is not valid UTF-8"),
+        }
+    }
+}
+
+impl std::error::Error for FrameError {}
+
+/// Parses one client frame from the front of `buf`, removing its bytes.
+///
+/// Returns `Ok(None)` if `buf` does not hold a complete frame yet.
+pub fn parse_frame(buf: &mut Vec<u8>, max_payload: usize) -> Result<Option<Frame>, FrameError> {
+    if buf.len() < 2 {
+        return Ok(None);
+    }
+
+    let fin = buf[0] & 0x80 != 0;
+    if buf[0] & 0x70 != 0 {
+        return Err(FrameError::Protocol("Reserved bits set without a negotiated extension".to_string()));
+    }
+    let opcode = Opcode::from_bits(buf[0] & 0x0F)
+        .ok_or_else(|| FrameError::Protocol(format!("Unknown opcode {:#x}", buf[0] & 0x0F)))?;
+    if buf[1] & 0x80 == 0 {
+        return Err(FrameError::Protocol("Client frames must be masked".to_string()));
+    }
+
+    let (payload_len, mut offset) = match buf[1] & 0x7F {
+        126 => {
+            if buf.len() < 4 {
+                return Ok(None);
+            }
+            (u16::from_be_bytes([buf[2], buf[3]]) as u64, 4)
+        }
+        127 => {
+            if buf.len() < 10 {
+                return Ok(None);
+            }
+            let mut len = [0u8; 8];
+            len.copy_from_slice(&buf[2..10]);
+            (u64::from_be_bytes(len), 10)
+        }
+        len => (len as u64, 2),
+    };
+
+    if opcode.is_control() && (!fin || payload_len > MAX_CONTROL_PAYLOAD as u64) {
+        return Err(FrameError::Protocol("Control frames must be short and unfragmented".to_string()));
+    }
+    if payload_len > max_payload as u64 {
+        return Err(FrameError::TooLarge(payload_len.min(usize::MAX as u64) as usize));
+    }
+    let payload_len = payload_len as usize;
+
+    if buf.len() < offset + 4 + payload_len {
+        return Ok(None);
+    }
+    let mut mask = [0u8; 4];
+    mask.copy_from_slice(&buf[offset..offset + 4]);
+    offset += 4;
+
+    let payload = buf[offset..offset + payload_len]
+        .iter()
+        .enumerate()
+        .map(|(i, byte)| byte ^ mask[i % 4])
+        .collect();
+    buf.drain(..offset + payload_len);
+
+    Ok(Some(Frame { fin, opcode, payload }))
+}
+
+/// Encodes a complete, unmasked server frame.
+pub fn encode_frame(opcode: Opcode, payload: &[u8]) -> Vec<u8> {
+    let mut frame = Vec::with_capacity(payload.len() + 10);
+    frame.push(0x80 | opcode as u8);
+
+    match payload.len() {
+        len if len < 126 => frame.push(len as u8),
+        len if len <= u16::MAX as usize => {
+            frame.push(126);
+            frame.extend_from_slice(&(len as u16).to_be_bytes());
+        }
+        len => {
+            frame.push(127);
+            frame.extend_from_slice(&(len as u64).to_be_bytes());
+        }
+    }
+
+    frame.extend_from_slice(payload);
+    frame
+}
+
+/// Encodes the payload of a close frame.
+pub fn encode_close_payload(code: u16, reason: &str) -> Vec<u8> {
+    let mut payload = code.to_be_bytes().to_vec();
+    // Keep the whole frame within the control frame limit, cutting on a character boundary
+    let mut end = reason.len().min(MAX_CONTROL_PAYLOAD - 2);
+    while !reason.is_char_boundary(end) {
+        end -= 1;
+    }
+    payload.extend_from_slice(&reason.as_bytes()[..end]);
+    payload
+}
+
+/// Decodes the payload of a close frame into its code and reason.
+///
+/// An empty payload means no code was given, reported as `CLOSE_NORMAL`.
+pub fn decode_close_payload(payload: &[u8]) -> Result<(u16, String), FrameError> {
+    match payload.len() {
+        0 => Ok((CLOSE_NORMAL, String::new())),
+        1 => Err(FrameError::Protocol("Close frame with a truncated code".to_string())),
+        _ => {
+            let code = u16::from_be_bytes([payload[0], payload[1]]);
+            let reason = String::from_utf8(payload[2..].to_vec())
+                .map_err(|_| FrameError::Protocol("Close reason is not valid UTF-8".to_string()))?;
+            Ok((code, reason))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Builds a masked client frame, as a browser would send it.
+    fn client_frame(first_byte: u8, payload: &[u8]) -> Vec<u8> {
+        let mask = [0x37, 0xfa, 0x21, 0x3d];
+        let mut frame = vec![first_byte];
+        match payload.len() {
+            len if len < 126 => frame.push(0x80 | len as u8),
+            len if len <= u16::MAX as usize => {
+                frame.push(0x80 | 126);
+                frame.extend_from_slice(&(len as u16).to_be_bytes());
+            }
+            len => {
+                frame.push(0x80 | 127);
+                frame.extend_from_slice(&(len as u64).to_be_bytes());
+            }
+        }
+        frame.extend_from_slice(&mask);
+        frame.extend(payload.iter().enumerate().map(|(i, byte)| byte ^ mask[i % 4]));
+        frame
+    }
+
+    #[test]
+    fn parses_masked_text_frame() {
+        // Single-frame masked text message from RFC 6455, section 5.7
+        let mut buf = vec![0x81, 0x85, 0x37, 0xfa, 0x21, 0x3d, 0x7f, 0x9f, 0x4d, 0x51, 0x58, 0x01];
+        let frame = parse_frame(&mut buf, 1024).unwrap().unwrap();
+        assert!(frame.fin);
+        assert_eq!(frame.opcode, Opcode::Text);
+        assert_eq!(frame.payload, b"Hello");
+        assert_eq!(buf, [0x01]);
+    }
+
+    #[test]
+    fn parses_extended_lengths() {
+        for len in [126, 65_535, 65_536] {
+            let payload = vec![b'x'; len];
+            let mut buf = client_frame(0x82, &payload);
+            let frame = parse_frame(&mut buf, 1 << 20).unwrap().unwrap();
+            assert_eq!(frame.opcode, Opcode::Binary);
+            assert_eq!(frame.payload, payload);
+            assert!(buf.is_empty());
+        }
+    }
+
+    #[test]
+    fn waits_for_complete_frame() {
+        let frame = client_frame(0x81, &[b'x'; 300]);
+        for end in [0, 1, 3, 8, frame.len() - 1] {
+            let mut buf = frame[..end].to_vec();
+            assert!(parse_frame(&mut buf, 1024).unwrap().is_none(), "{}", end);
+            assert_eq!(buf.len(), end);
+        }
+    }
+
+    #[test]
+    fn parses_fragments() {
+        let mut buf = client_frame(0x01, b"Hel");
+        buf.extend_from_slice(&client_frame(0x80, b"lo"));
+        let first = parse_frame(&mut buf, 1024).unwrap().unwrap();
+        let last = parse_frame(&mut buf, 1024).unwrap().unwrap();
+        assert!(!first.fin && first.opcode == Opcode::Text);
+        assert!(last.fin && last.opcode == Opcode::Continuation);
+    }
+
+    #[test]
+    fn rejects_protocol_violations() {
+        let invalid = [
+            vec![0x81, 0x05, b'H', b'e', b'l', b'l', b'o'],
+            client_frame(0xC1, b"x"),
+            client_frame(0x83, b"x"),
+            client_frame(0x09, b"ping"),
+            client_frame(0x89, &[0; 126]),
+        ];
+        for mut buf in invalid {
+            let err = parse_frame(&mut buf, 1024).unwrap_err();
+            assert_eq!(err.close_code(), CLOSE_PROTOCOL_ERROR, "{}", err);
+        }
+    }
+
+    #[test]
+    fn rejects_oversized_payload_before_it_arrives() {
+        let mut buf = client_frame(0x82, &[0; 2048])[..8].to_vec();
+        let err = parse_frame(&mut buf, 1024).unwrap_err();
+        assert!(matches!(err, FrameError::TooLarge(2048)));
+        assert_eq!(err.close_code(), CLOSE_TOO_LARGE);
+    }
+
+    #[test]
+    fn encodes_unmasked_frames() {
+        assert_eq!(encode_frame(Opcode::Text, b"Hello"), b"\x81\x05Hello");
+        assert_eq!(&encode_frame(Opcode::Binary, &[0; 256])[..4], [0x82, 126, 0x01, 0x00]);
+        assert_eq!(&encode_frame(Opcode::Binary, &[0; 65_536])[..10], [0x82, 127, 0, 0, 0, 0, 0, 1, 0, 0]);
+    }
+
+    #[test]
+    fn close_payload_round_trip() {
+        let payload = encode_close_payload(CLOSE_GOING_AWAY, "Server shutting down");
+        assert_eq!(decode_close_payload(&payload).unwrap(), (CLOSE_GOING_AWAY, "Server shutting down".to_string()));
+        assert_eq!(decode_close_payload(&[]).unwrap(), (CLOSE_NORMAL, String::new()));
+        assert!(decode_close_payload(&[0x03]).is_err());
+
+        // Long reasons are cut to fit a control frame without splitting a character
+        let payload = encode_close_payload(CLOSE_NORMAL, &"é".repeat(100));
+        assert!(payload.len() <= MAX_CONTROL_PAYLOAD);
+        assert!(decode_close_payload(&payload).is_ok());
+    }
+}
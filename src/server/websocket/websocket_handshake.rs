@@ -0,0 +1,159 @@
+// src/server/websocket/websocket_handshake.rs
+
+use crate::server::structs::structs_header::StatusCode;
+use crate::server::structs::structs_request::{Request, RequestError};
+use crate::server::structs::structs_response::Response;
+use crate::util::crypto::sha1;
+use crate::util::encoding::{base64_decode, base64_encode};
+
+use super::websocket_core::WebSocketUpgrade;
+
+/// Value appended to the client key before hashing it (RFC 6455, section 1.3).
+const WEBSOCKET_GUID: &str = "258EAFA5-E914-47DA-95CA-C5AB0DC85B11";
+
+/// The only protocol version defined by RFC 6455.
+const WEBSOCKET_VERSION: &str = "13";
+
+/// Computes the `Sec-WebSocket-Accept` value for a client's `Sec-WebSocket-Key`.
+pub fn accept_key(key: &str) -> String {
+    base64_encode(&sha1(format!("{}{}", key.trim(), WEBSOCKET_GUID).as_bytes()))
+}
+
+/// Returns `true` if the request asks to upgrade the connection to a WebSocket.
+pub fn is_websocket_request(request: &Request) -> bool {
+    request.header_fields.has_token("upgrade", "websocket") && request.header_fields.has_token("connection", "upgrade")
+}
+
+/// Returns `true` if the request's `Origin` is the server's own origin or one of `allowed_origins`.
+///
+/// Browsers send `Origin` with every WebSocket handshake but do not apply CORS to it, so this
+/// is what stops other sites the user visits from connecting. Requests without an `Origin`
+/// come from clients other than browsers and are allowed.
+pub fn is_allowed_origin(request: &Request, allowed_origins: &[String]) -> bool {
+    let Some(origin) = request.header_fields.get("origin").map(|origin| origin.trim().trim_end_matches('/')) else {
+        return true;
+    };
+    if allowed_origins.iter().any(|allowed| allowed.trim_end_matches('/').eq_ignore_ascii_case(origin)) {
+        return true;
+    }
+    // Same origin: the page was loaded from this server, so its host matches the Host header
+    let authority = origin.split_once("://").map(|(_, authority)| authority);
+    match (authority, request.header_fields.get("host")) {
+        (Some(authority), Some(host)) => authority.eq_ignore_ascii_case(host.trim()),
+        _ => false,
+    }
+}
+
+/// Validates a WebSocket handshake request and builds the `101 Switching Protocols` response
+/// that hands the connection to `upgrade`.
+///
+/// A handshake from a page on another origin that is not in `allowed_origins` gets
+/// `403 Forbidden`, and a client asking for an unsupported protocol version gets
+/// `426 Upgrade Required` listing the supported one; any other malformed handshake is an
+/// `InvalidRequest` error.
+pub fn accept(request: &Request, upgrade: WebSocketUpgrade, allowed_origins: &[String]) -> Result<Response, RequestError> {
+    if request.method != "GET" || request.version != "HTTP/1.1" {
+        return Err(RequestError::InvalidRequest(
+            "WebSocket handshake must be an HTTP/1.1 GET request".to_string(),
+        ));
+    }
+    if !is_websocket_request(request) {
+        return Err(RequestError::InvalidRequest("Missing WebSocket upgrade headers".to_string()));
+    }
+    if !is_allowed_origin(request, allowed_origins) {
+        let status = StatusCode::Forbidden;
+        return Ok(Response::response_error("WebSocket origin not allowed".to_string(), status));
+    }
+    if request.get_header_value_or_default("sec-websocket-version", "").trim() != WEBSOCKET_VERSION {
+        let mut response = Response::response_error(
+            "Unsupported WebSocket version".to_string(),
+            StatusCode::UpgradeRequired,
+        );
+        response.set_header("Sec-WebSocket-Version", WEBSOCKET_VERSION).expect("Sec-WebSocket-Version is a valid header");
+        return Ok(response);
+    }
+
+    let key = request.get_header_value("sec-websocket-key")?;
+    if base64_decode(key.trim()).is_none_or(|nonce| nonce.len() != 16) {
+        return Err(RequestError::InvalidRequest("Invalid Sec-WebSocket-Key".to_string()));
+    }
+
+    let mut response = Response::from_status(StatusCode::SwitchingProtocols);
+    response.set_header("Upgrade", "websocket").expect("Upgrade is a valid header");
+    response.set_header("Connection", "Upgrade").expect("Connection is a valid header");
+    response.set_header("Sec-WebSocket-Accept", &accept_key(key)).expect("Sec-WebSocket-Accept is a valid header");
+    response.upgrade = Some(upgrade);
+    Ok(response)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Example from RFC 6455, section 1.3
+    #[test]
+    fn rfc6455_accept_key() {
+        assert_eq!(accept_key("dGhlIHNhbXBsZSBub25jZQ=="), "s3pPLMBiTxaQ9kYGzzhZRbK+xOo=");
+    }
+
+    const HANDSHAKE: &str = "GET /ws HTTP/1.1\r\nHost: test\r\nUpgrade: websocket\r\n\
+                             Connection: keep-alive, Upgrade\r\nSec-WebSocket-Version: 13\r\n\
+                             Sec-WebSocket-Key: dGhlIHNhbXBsZSBub25jZQ==\r\n\r\n";
+
+    fn upgrade() -> WebSocketUpgrade {
+        WebSocketUpgrade::new(|_socket| async {})
+    }
+
+    fn handshake(request: &str) -> Result<Response, RequestError> {
+        accept(&Request::from_string(request).unwrap(), upgrade(), &[])
+    }
+
+    #[test]
+    fn accepts_valid_handshake() {
+        let response = handshake(HANDSHAKE).unwrap();
+        assert_eq!(response.status_code, 101);
+        assert_eq!(response.get_header("upgrade"), Some("websocket"));
+        assert_eq!(response.get_header("connection"), Some("Upgrade"));
+        assert_eq!(response.get_header("sec-websocket-accept"), Some("s3pPLMBiTxaQ9kYGzzhZRbK+xOo="));
+        assert!(response.upgrade.is_some());
+    }
+
+    #[test]
+    fn checks_the_origin() {
+        let same_origin = HANDSHAKE.replace("Host: test\r\n", "Host: 127.0.0.1:3030\r\nOrigin: http://127.0.0.1:3030\r\n");
+        assert_eq!(handshake(&same_origin).unwrap().status_code, 101);
+
+        let cross_origin = HANDSHAKE.replace("Host: test\r\n", "Host: 127.0.0.1:3030\r\nOrigin: https://evil.example\r\n");
+        let response = handshake(&cross_origin).unwrap();
+        assert_eq!(response.status_code, 403);
+        assert!(response.upgrade.is_none());
+        assert_eq!(handshake(&HANDSHAKE.replace("Host: test\r\n", "Origin: null\r\n")).unwrap().status_code, 403);
+
+        let request = Request::from_string(&cross_origin).unwrap();
+        let allowed = ["https://evil.example/".to_string()];
+        assert_eq!(accept(&request, upgrade(), &allowed).unwrap().status_code, 101);
+    }
+
+    #[test]
+    fn asks_for_supported_version() {
+        let response = handshake(&HANDSHAKE.replace("Version: 13", "Version: 8")).unwrap();
+        assert_eq!(response.status_code, 426);
+        assert_eq!(response.get_header("sec-websocket-version"), Some("13"));
+        assert!(response.upgrade.is_none());
+    }
+
+    #[test]
+    fn rejects_malformed_handshakes() {
+        let invalid = [
+            HANDSHAKE.replace("GET", "POST"),
+            HANDSHAKE.replace("HTTP/1.1", "HTTP/1.0"),
+            HANDSHAKE.replace("Upgrade: websocket", "Upgrade: h2c"),
+            HANDSHAKE.replace("keep-alive, Upgrade", "keep-alive"),
+            HANDSHAKE.replace("dGhlIHNhbXBsZSBub25jZQ==", "c2hvcnQ="),
+            HANDSHAKE.replace("Sec-WebSocket-Key: dGhlIHNhbXBsZSBub25jZQ==\r\n", ""),
+        ];
+        for request in invalid {
+            assert!(handshake(&request).is_err(), "{}", request);
+        }
+    }
+}
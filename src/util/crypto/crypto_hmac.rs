@@ -0,0 +1,74 @@
+// src/util/crypto/crypto_hmac.rs
+
+use super::crypto_sha1::{sha1, SHA1_BLOCK_LEN, SHA1_LEN};
+
+/// Computes the HMAC-SHA1 of `message` under `key` (RFC 2104).
+pub fn hmac_sha1(key: &[u8], message: &[u8]) -> [u8; SHA1_LEN] {
+    let mut block_key = [0u8; SHA1_BLOCK_LEN];
+    if key.len() > SHA1_BLOCK_LEN {
+        block_key[..SHA1_LEN].copy_from_slice(&sha1(key));
+    } else {
+        block_key[..key.len()].copy_from_slice(key);
+    }
+
+    let mut inner = Vec::with_capacity(SHA1_BLOCK_LEN + message.len());
+    inner.extend(block_key.iter().map(|b| b ^ 0x36));
+    inner.extend_from_slice(message);
+
+    let mut outer = Vec::with_capacity(SHA1_BLOCK_LEN + SHA1_LEN);
+    outer.extend(block_key.iter().map(|b| b ^ 0x5c));
+    outer.extend_from_slice(&sha1(&inner));
+
+    sha1(&outer)
+}
+
+/// Compares two byte strings in time that depends only on their length, for checking signatures.
+pub fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    if a.len() != b.len() {
+        return false;
+    }
+    a.iter().zip(b).fold(0u8, |diff, (x, y)| diff | (x ^ y)) == 0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::util::encoding::hex_encode;
+
+    // Test cases from RFC 2202, section 3
+    #[test]
+    fn rfc2202_vectors() {
+        let cases: [(Vec<u8>, Vec<u8>, &str); 7] = [
+            (vec![0x0b; 20], b"Hi There".to_vec(), "b617318655057264e28bc0b6fb378c8ef146be00"),
+            (b"Jefe".to_vec(), b"what do ya want for nothing?".to_vec(), "effcdf6ae5eb2fa2d27416d5f184df9c259a7c79"),
+            (vec![0xaa; 20], vec![0xdd; 50], "125d7342b9ac11cd91a39af48aa17b4f63f175d3"),
+            (
+                (0x01..=0x19).collect(),
+                vec![0xcd; 50],
+                "4c9007f4026250c6bc8414f9bf50c86c2d7235da",
+            ),
+            (vec![0x0c; 20], b"Test With Truncation".to_vec(), "4c1a03424b55e07fe7f27be1d58bb9324a9a5a04"),
+            (
+                vec![0xaa; 80],
+                b"Test Using Larger Than Block-Size Key - Hash Key First".to_vec(),
+                "aa4ae5e15272d00e95705637ce8a3b55ed402112",
+            ),
+            (
+                vec![0xaa; 80],
+                b"Test Using Larger Than Block-Size Key and Larger Than One Block-Size Data".to_vec(),
+                "e8e99d0f45237d786d6bbaa7965c7808bbff1a91",
+            ),
+        ];
+        for (key, data, digest) in cases {
+            assert_eq!(hex_encode(&hmac_sha1(&key, &data)), digest);
+        }
+    }
+
+    #[test]
+    fn constant_time_eq_compares_contents_and_length() {
+        assert!(constant_time_eq(b"secret", b"secret"));
+        assert!(!constant_time_eq(b"secret", b"secreT"));
+        assert!(!constant_time_eq(b"secret", b"secrets"));
+        assert!(constant_time_eq(b"", b""));
+    }
+}
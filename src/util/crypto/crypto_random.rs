@@ -0,0 +1,29 @@
+// src/util/crypto/crypto_random.rs
+
+/// Returns `len` bytes from the operating system's secure random number generator,
+/// suitable for session identifiers and signing keys.
+///
+/// # Panics
+/// Panics if the operating system cannot provide random bytes, as no identifier or key
+/// should ever be generated from a weaker source.
+pub fn random_bytes(len: usize) -> Vec<u8> {
+    let mut bytes = vec![0u8; len];
+    getrandom::getrandom(&mut bytes).expect("Failed to read from the OS random number generator");
+    bytes
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn returns_requested_length() {
+        assert_eq!(random_bytes(0).len(), 0);
+        assert_eq!(random_bytes(33).len(), 33);
+    }
+
+    #[test]
+    fn calls_differ() {
+        assert_ne!(random_bytes(32), random_bytes(32));
+    }
+}
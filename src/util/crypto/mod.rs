@@ -0,0 +1,10 @@
+mod crypto_sha1;
+mod crypto_hmac;
+mod crypto_random;
+
+#[allow(unused)]
+pub use crypto_sha1::{sha1, SHA1_LEN};
+#[allow(unused)]
+pub use crypto_hmac::{hmac_sha1, constant_time_eq};
+#[allow(unused)]
+pub use crypto_random::random_bytes;
\ No newline at end of file
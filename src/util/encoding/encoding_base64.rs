@@ -0,0 +1,91 @@
+// src/util/encoding/encoding_base64.rs
+
+const ALPHABET: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+
+/// Encodes bytes as standard base64 with padding (RFC 4648).
+pub fn base64_encode(bytes: &[u8]) -> String {
+    let mut encoded = String::with_capacity(bytes.len().div_ceil(3) * 4);
+    for chunk in bytes.chunks(3) {
+        let group = match chunk.len() {
+            3 => (chunk[0] as u32) << 16 | (chunk[1] as u32) << 8 | chunk[2] as u32,
+            2 => (chunk[0] as u32) << 16 | (chunk[1] as u32) << 8,
+            _ => (chunk[0] as u32) << 16,
+        };
+        for i in 0..4 {
+            if i <= chunk.len() {
+                encoded.push(ALPHABET[(group >> (18 - 6 * i) & 0x3f) as usize] as char);
+            } else {
+                encoded.push('=');
+            }
+        }
+    }
+    encoded
+}
+
+/// Decodes standard base64 with padding.
+///
+/// Returns `None` if the input length is not a multiple of four or contains characters outside
+/// the alphabet.
+pub fn base64_decode(input: &str) -> Option<Vec<u8>> {
+    let input = input.as_bytes();
+    if !input.len().is_multiple_of(4) {
+        return None;
+    }
+
+    let mut decoded = Vec::with_capacity(input.len() / 4 * 3);
+    for (index, chunk) in input.chunks(4).enumerate() {
+        let is_last = index == input.len() / 4 - 1;
+        let padding = chunk.iter().rev().take_while(|&&b| b == b'=').count();
+        if padding > 2 || (padding > 0 && !is_last) {
+            return None;
+        }
+
+        let mut group = 0u32;
+        for &byte in &chunk[..4 - padding] {
+            let value = ALPHABET.iter().position(|&c| c == byte)? as u32;
+            group = group << 6 | value;
+        }
+        group <<= 6 * padding as u32;
+
+        let bytes = group.to_be_bytes();
+        decoded.extend_from_slice(&bytes[1..4 - padding]);
+    }
+    Some(decoded)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Test vectors from RFC 4648, section 10
+    const VECTORS: [(&str, &str); 7] = [
+        ("", ""),
+        ("f", "Zg=="),
+        ("fo", "Zm8="),
+        ("foo", "Zm9v"),
+        ("foob", "Zm9vYg=="),
+        ("fooba", "Zm9vYmE="),
+        ("foobar", "Zm9vYmFy"),
+    ];
+
+    #[test]
+    fn rfc4648_encode() {
+        for (plain, encoded) in VECTORS {
+            assert_eq!(base64_encode(plain.as_bytes()), encoded);
+        }
+    }
+
+    #[test]
+    fn rfc4648_decode() {
+        for (plain, encoded) in VECTORS {
+            assert_eq!(base64_decode(encoded).as_deref(), Some(plain.as_bytes()));
+        }
+    }
+
+    #[test]
+    fn rejects_malformed_input() {
+        assert_eq!(base64_decode("Zm9"), None);
+        assert_eq!(base64_decode("Zm9v!A=="), None);
+        assert_eq!(base64_decode("Z==="), None);
+    }
+}
@@ -0,0 +1,42 @@
+// src/util/encoding/encoding_hex.rs
+
+/// Encodes bytes as lowercase hexadecimal.
+pub fn hex_encode(bytes: &[u8]) -> String {
+    bytes.iter().map(|byte| format!("{:02x}", byte)).collect()
+}
+
+/// Decodes a hexadecimal string, accepting either case.
+///
+/// Returns `None` for an odd length or a character that is not a hex digit.
+pub fn hex_decode(input: &str) -> Option<Vec<u8>> {
+    if input.len() % 2 == 1 || !input.bytes().all(|b| b.is_ascii_hexdigit()) {
+        return None;
+    }
+    (0..input.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(&input[i..i + 2], 16).ok())
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn round_trip() {
+        let bytes = [0x00, 0x01, 0x7f, 0x80, 0xab, 0xff];
+        assert_eq!(hex_encode(&bytes), "00017f80abff");
+        assert_eq!(hex_decode("00017f80abff").as_deref(), Some(&bytes[..]));
+    }
+
+    #[test]
+    fn decodes_upper_case() {
+        assert_eq!(hex_decode("ABff"), Some(vec![0xab, 0xff]));
+    }
+
+    #[test]
+    fn rejects_malformed_input() {
+        assert_eq!(hex_decode("abc"), None);
+        assert_eq!(hex_decode("zz"), None);
+    }
+}
@@ -0,0 +1,105 @@
+/// Decodes `%XX` escapes in a URL component.
+///
+/// Returns `None` if an escape is malformed or the decoded bytes are not valid UTF-8.
+pub fn percent_decode(input: &str) -> Option<String> {
+    let bytes = input.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+
+    while i < bytes.len() {
+        if bytes[i] == b'%' {
+            let hex = bytes.get(i + 1..i + 3)?;
+            if !hex.iter().all(u8::is_ascii_hexdigit) {
+                return None;
+            }
+            decoded.push(u8::from_str_radix(std::str::from_utf8(hex).ok()?, 16).ok()?);
+            i += 3;
+        } else {
+            decoded.push(bytes[i]);
+            i += 1;
+        }
+    }
+
+    String::from_utf8(decoded).ok()
+}
+
+/// Encodes a single URL path segment, escaping every byte other than the unreserved
+/// characters `A-Z a-z 0-9 - . _ ~`.
+pub fn percent_encode_segment(input: &str) -> String {
+    let mut encoded = String::with_capacity(input.len());
+    for byte in input.bytes() {
+        if byte.is_ascii_alphanumeric() || b"-._~".contains(&byte) {
+            encoded.push(byte as char);
+        } else {
+            encoded.push_str(&format!("%{:02X}", byte));
+        }
+    }
+    encoded
+}
+
+/// Decodes an `application/x-www-form-urlencoded` component, where `+` stands for a space.
+///
+/// Returns `None` if an escape is malformed or the decoded bytes are not valid UTF-8.
+pub fn form_decode(input: &str) -> Option<String> {
+    percent_decode(&input.replace('+', " "))
+}
+
+/// Parses `key=value&key=value` pairs as used by query strings and urlencoded forms.
+///
+/// Pairs keep their original order and repeated keys are preserved. A key without `=`
+/// gets an empty value. Returns `None` if any component is not validly encoded.
+pub fn parse_urlencoded(input: &str) -> Option<Vec<(String, String)>> {
+    input
+        .split('&')
+        .filter(|pair| !pair.is_empty())
+        .map(|pair| {
+            let (key, value) = pair.split_once('=').unwrap_or((pair, ""));
+            Some((form_decode(key)?, form_decode(value)?))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn percent_decode_escapes() {
+        assert_eq!(percent_decode("/a%20b/%2e%2E").as_deref(), Some("/a b/.."));
+        assert_eq!(percent_decode("caf%C3%A9").as_deref(), Some("café"));
+        assert_eq!(percent_decode("a+b").as_deref(), Some("a+b"));
+        assert_eq!(percent_decode("").as_deref(), Some(""));
+    }
+
+    #[test]
+    fn percent_decode_rejects_malformed_input() {
+        for input in ["%", "%2", "%zz", "a%2", "%C3", "%FF"] {
+            assert_eq!(percent_decode(input), None, "{}", input);
+        }
+    }
+    #[test]
+    fn percent_encode_segment_escapes_reserved_bytes() {
+        assert_eq!(percent_encode_segment("a b/c?d"), "a%20b%2Fc%3Fd");
+        assert_eq!(percent_encode_segment("café-1.txt"), "caf%C3%A9-1.txt");
+        assert_eq!(percent_decode(&percent_encode_segment("50% off")).as_deref(), Some("50% off"));
+    }
+
+    #[test]
+    fn form_decode_turns_plus_into_space() {
+        assert_eq!(form_decode("a+b%2Bc").as_deref(), Some("a b+c"));
+    }
+
+    #[test]
+    fn parse_urlencoded_keeps_order_and_repeats() {
+        let pairs = parse_urlencoded("b=2&a=1&&b=3&flag&q=x%3Dy+z").unwrap();
+        let expected = [("b", "2"), ("a", "1"), ("b", "3"), ("flag", ""), ("q", "x=y z")];
+        assert_eq!(pairs, expected.map(|(k, v)| (k.to_string(), v.to_string())));
+        assert_eq!(parse_urlencoded(""), Some(Vec::new()));
+    }
+
+    #[test]
+    fn parse_urlencoded_rejects_malformed_pairs() {
+        assert_eq!(parse_urlencoded("a=1&b=%zz"), None);
+        assert_eq!(parse_urlencoded("%=1"), None);
+    }
+}
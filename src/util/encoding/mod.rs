@@ -0,0 +1,16 @@
+mod encoding_percent;
+mod encoding_hex;
+mod encoding_base64;
+
+#[allow(unused)]
+pub use encoding_percent::percent_decode;
+#[allow(unused)]
+pub use encoding_percent::percent_encode_segment;
+#[allow(unused)]
+pub use encoding_percent::form_decode;
+#[allow(unused)]
+pub use encoding_percent::parse_urlencoded;
+#[allow(unused)]
+pub use encoding_hex::{hex_encode, hex_decode};
+#[allow(unused)]
+pub use encoding_base64::{base64_encode, base64_decode};
\ No newline at end of file
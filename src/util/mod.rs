@@ -1 +1,4 @@
-pub mod logging;
\ No newline at end of file
+pub mod logging;
+pub mod time;
+pub mod encoding;
+pub mod crypto;
\ No newline at end of file
@@ -0,0 +1,4 @@
+mod time_http;
+
+#[allow(unused)]
+pub use time_http::http_date;
\ No newline at end of file
@@ -1,2 +1,4 @@
 pub mod window_core;
+pub mod window_icon;
+pub mod window_protocol;
 pub mod structs;
\ No newline at end of file
@@ -1,2 +1,3 @@
 #![allow(unused)]
-pub mod struct_windowconfig;
\ No newline at end of file
+pub mod struct_windowconfig;
+pub mod struct_windowerror;
\ No newline at end of file
@@ -1,9 +1,82 @@
-use wry::application::dpi::LogicalSize;
+use std::fmt;
+use std::path::PathBuf;
+
+use wry::application::dpi::{LogicalPosition, LogicalSize};
+
+use crate::server::server_protocol::ProtocolHandler;
+use crate::window::window_protocol::{PROTOCOL_NAME, PROTOCOL_URL};
+
+/// Where the window is placed when it first opens.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum WindowPosition {
+    /// Let the platform pick a position.
+    Default,
+    /// Centre the window on the monitor it opens on.
+    Centered,
+    /// Place the outer top-left corner at the given logical position.
+    At(LogicalPosition<f64>),
+}
+
+/// A background colour as red, green, blue and alpha components.
+pub type Rgba = (u8, u8, u8, u8);
+
+/// A combination of `WindowConfig` options that cannot be honoured.
+#[derive(Debug)]
+pub enum WindowConfigError {
+    /// A size is zero, negative or not finite.
+    InvalidSize(&'static str),
+    /// The minimum size is larger than the maximum size in some dimension.
+    MinLargerThanMax,
+    /// The initial size lies outside the minimum and maximum sizes.
+    SizeOutOfBounds,
+    /// Two options that cannot both be enabled.
+    Conflict(&'static str, &'static str),
+    /// A background colour with an alpha below 255 on a window that is not transparent.
+    TranslucentBackground,
+    /// The icon file could not be read or decoded.
+    Icon(PathBuf, String),
+    /// The URL uses the custom protocol scheme but no `ProtocolHandler` serves it.
+    MissingProtocol(String),
+}
+
+impl fmt::Display for WindowConfigError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            WindowConfigError::InvalidSize(field) => write!(f, "Window {} must be positive and finite", field),
+            WindowConfigError::MinLargerThanMax => write!(f, "Window min size is larger than its max size"),
+            WindowConfigError::SizeOutOfBounds => write!(f, "Window size lies outside its min and max sizes"),
+            WindowConfigError::Conflict(a, b) => write!(f, "Window options '{}' and '{}' cannot be combined", a, b),
+            WindowConfigError::TranslucentBackground => {
+                write!(f, "A translucent background colour requires a transparent window")
+            }
+            WindowConfigError::Icon(path, msg) => write!(f, "Failed to load window icon {}: {}", path.display(), msg),
+            WindowConfigError::MissingProtocol(url) => {
+                write!(f, "Window URL {} needs a protocol handler, see `set_protocol`", url)
+            }
+        }
+    }
+}
+
+impl std::error::Error for WindowConfigError {}
 
 pub struct WindowConfig {
     pub title: String,
     pub size: LogicalSize<f64>,
+    pub min_size: Option<LogicalSize<f64>>,
+    pub max_size: Option<LogicalSize<f64>>,
+    pub position: WindowPosition,
+    pub resizable: bool,
+    pub maximized: bool,
+    pub fullscreen: bool,
+    pub decorations: bool,
+    pub always_on_top: bool,
+    pub transparent: bool,
+    pub background_color: Option<Rgba>,
+    pub icon: Option<PathBuf>,
+    /// The page to open, `about:blank` until `set_url` or `set_protocol` is called.
     pub url: String,
+    /// Serves the `smn://` scheme from these plugins instead of a TCP server.
+    pub protocol: Option<ProtocolHandler>,
 }
 
 impl WindowConfig {
@@ -11,7 +84,19 @@ impl WindowConfig {
         Self {
             title: "SmnView".to_string(),
             size: LogicalSize::new(800.0, 600.0),
-            url: "http://127.0.0.1:3030/".to_string(),
+            min_size: None,
+            max_size: None,
+            position: WindowPosition::Default,
+            resizable: true,
+            maximized: false,
+            fullscreen: false,
+            decorations: true,
+            always_on_top: false,
+            transparent: false,
+            background_color: None,
+            icon: None,
+            url: "about:blank".to_string(),
+            protocol: None,
         }
     }
 
@@ -25,9 +110,224 @@ impl WindowConfig {
         self
     }
 
+    pub fn set_min_size(mut self, width: f64, height: f64) -> Self {
+        self.min_size = Some(LogicalSize::new(width, height));
+        self
+    }
+
+    pub fn set_max_size(mut self, width: f64, height: f64) -> Self {
+        self.max_size = Some(LogicalSize::new(width, height));
+        self
+    }
+
+    pub fn set_position(mut self, x: f64, y: f64) -> Self {
+        self.position = WindowPosition::At(LogicalPosition::new(x, y));
+        self
+    }
+
+    pub fn set_centered(mut self, centered: bool) -> Self {
+        self.position = if centered { WindowPosition::Centered } else { WindowPosition::Default };
+        self
+    }
+
+    pub fn set_resizable(mut self, resizable: bool) -> Self {
+        self.resizable = resizable;
+        self
+    }
+
+    pub fn set_maximized(mut self, maximized: bool) -> Self {
+        self.maximized = maximized;
+        self
+    }
+
+    /// Opens the window borderless fullscreen on its current monitor.
+    pub fn set_fullscreen(mut self, fullscreen: bool) -> Self {
+        self.fullscreen = fullscreen;
+        self
+    }
+
+    pub fn set_decorations(mut self, decorations: bool) -> Self {
+        self.decorations = decorations;
+        self
+    }
+
+    pub fn set_always_on_top(mut self, always_on_top: bool) -> Self {
+        self.always_on_top = always_on_top;
+        self
+    }
+
+    /// Makes both the window and the webview transparent.
+    ///
+    /// The page itself must also leave its background transparent, or use
+    /// `set_background_color` with an alpha below 255.
+    pub fn set_transparent(mut self, transparent: bool) -> Self {
+        self.transparent = transparent;
+        self
+    }
+
+    /// Sets the page background shown before and behind the loaded content.
+    pub fn set_background_color(mut self, r: u8, g: u8, b: u8, a: u8) -> Self {
+        self.background_color = Some((r, g, b, a));
+        self
+    }
+
+    /// Sets the window icon, loaded from a PNG file when the window opens.
+    pub fn set_icon(mut self, path: &str) -> Self {
+        self.icon = Some(PathBuf::from(path));
+        self
+    }
+
     pub fn set_url(mut self, url: &str) -> Self {
         self.url = url.to_string();
         self
     }
+
+    /// Serves the page through the `smn://` custom protocol, answered by the plugins
+    /// behind `handler`, and points the window at `smn://app/`.
+    ///
+    /// Call `set_url` afterwards to open a different page of the app.
+    /// The webview answers protocol requests on the UI thread, which stays blocked until the
+    /// plugin responds, and event streams and WebSockets are not available; prefer a TCP
+    /// server for apps with slow commands or live events.
+    pub fn set_protocol(mut self, handler: ProtocolHandler) -> Self {
+        self.protocol = Some(handler);
+        self.url = PROTOCOL_URL.to_string();
+        self
+    }
+
+    /// Checks that the options can all be honoured together.
+    ///
+    /// # Returns
+    /// * `Result<(), WindowConfigError>` - The first conflict found, if any.
+    pub fn validate(&self) -> Result<(), WindowConfigError> {
+        check_size(&self.size, "size")?;
+        if let Some(min) = &self.min_size {
+            check_size(min, "min size")?;
+        }
+        if let Some(max) = &self.max_size {
+            check_size(max, "max size")?;
+        }
+
+        if let (Some(min), Some(max)) = (&self.min_size, &self.max_size) {
+            if min.width > max.width || min.height > max.height {
+                return Err(WindowConfigError::MinLargerThanMax);
+            }
+        }
+        let too_small = self.min_size.is_some_and(|min| self.size.width < min.width || self.size.height < min.height);
+        let too_large = self.max_size.is_some_and(|max| self.size.width > max.width || self.size.height > max.height);
+        if too_small || too_large {
+            return Err(WindowConfigError::SizeOutOfBounds);
+        }
+
+        if self.fullscreen && self.maximized {
+            return Err(WindowConfigError::Conflict("fullscreen", "maximized"));
+        }
+        if self.fullscreen && self.position != WindowPosition::Default {
+            return Err(WindowConfigError::Conflict("fullscreen", "position"));
+        }
+        if self.maximized && !self.resizable {
+            return Err(WindowConfigError::Conflict("maximized", "resizable(false)"));
+        }
+
+        if let Some((_, _, _, alpha)) = self.background_color {
+            if alpha < 255 && !self.transparent {
+                return Err(WindowConfigError::TranslucentBackground);
+            }
+        }
+
+        if let Some(path) = &self.icon {
+            if !path.is_file() {
+                return Err(WindowConfigError::Icon(path.clone(), "File not found".to_string()));
+            }
+        }
+
+        let scheme = self.url.split_once(':').map(|(scheme, _)| scheme);
+        if self.protocol.is_none() && scheme.is_some_and(|scheme| scheme.eq_ignore_ascii_case(PROTOCOL_NAME)) {
+            return Err(WindowConfigError::MissingProtocol(self.url.clone()));
+        }
+
+        Ok(())
+    }
+}
+
+fn check_size(size: &LogicalSize<f64>, field: &'static str) -> Result<(), WindowConfigError> {
+    let valid = |v: f64| v.is_finite() && v > 0.0;
+    if valid(size.width) && valid(size.height) {
+        Ok(())
+    } else {
+        Err(WindowConfigError::InvalidSize(field))
+    }
 }
 
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn accepts_the_default_config() {
+        assert!(WindowConfig::default().validate().is_ok());
+    }
+
+    #[test]
+    fn rejects_invalid_sizes() {
+        for (width, height) in [(0.0, 600.0), (800.0, -1.0), (f64::NAN, 600.0), (800.0, f64::INFINITY)] {
+            let result = WindowConfig::default().set_size(width, height).validate();
+            assert!(matches!(result, Err(WindowConfigError::InvalidSize("size"))), "{}x{}", width, height);
+        }
+        let result = WindowConfig::default().set_min_size(0.0, 100.0).validate();
+        assert!(matches!(result, Err(WindowConfigError::InvalidSize("min size"))));
+        let result = WindowConfig::default().set_max_size(1000.0, f64::NAN).validate();
+        assert!(matches!(result, Err(WindowConfigError::InvalidSize("max size"))));
+    }
+
+    #[test]
+    fn rejects_sizes_outside_the_bounds() {
+        let result = WindowConfig::default().set_min_size(900.0, 100.0).set_max_size(850.0, 1000.0).validate();
+        assert!(matches!(result, Err(WindowConfigError::MinLargerThanMax)));
+
+        let result = WindowConfig::default().set_min_size(1024.0, 768.0).validate();
+        assert!(matches!(result, Err(WindowConfigError::SizeOutOfBounds)));
+        let result = WindowConfig::default().set_max_size(640.0, 480.0).validate();
+        assert!(matches!(result, Err(WindowConfigError::SizeOutOfBounds)));
+
+        let config = WindowConfig::default().set_min_size(800.0, 600.0).set_max_size(800.0, 600.0);
+        assert!(config.validate().is_ok());
+    }
+
+    #[test]
+    fn rejects_conflicting_options() {
+        let result = WindowConfig::default().set_fullscreen(true).set_maximized(true).validate();
+        assert!(matches!(result, Err(WindowConfigError::Conflict("fullscreen", "maximized"))));
+
+        let result = WindowConfig::default().set_fullscreen(true).set_position(10.0, 10.0).validate();
+        assert!(matches!(result, Err(WindowConfigError::Conflict("fullscreen", "position"))));
+        let result = WindowConfig::default().set_fullscreen(true).set_centered(true).validate();
+        assert!(matches!(result, Err(WindowConfigError::Conflict("fullscreen", "position"))));
+
+        let result = WindowConfig::default().set_maximized(true).set_resizable(false).validate();
+        assert!(matches!(result, Err(WindowConfigError::Conflict("maximized", "resizable(false)"))));
+    }
+
+    #[test]
+    fn requires_a_transparent_window_for_translucent_backgrounds() {
+        let result = WindowConfig::default().set_background_color(0, 0, 0, 128).validate();
+        assert!(matches!(result, Err(WindowConfigError::TranslucentBackground)));
+
+        let config = WindowConfig::default().set_background_color(0, 0, 0, 128).set_transparent(true);
+        assert!(config.validate().is_ok());
+        assert!(WindowConfig::default().set_background_color(0, 0, 0, 255).validate().is_ok());
+    }
+
+    #[test]
+    fn rejects_a_missing_icon_file() {
+        let result = WindowConfig::default().set_icon("does/not/exist.png").validate();
+        assert!(matches!(result, Err(WindowConfigError::Icon(path, _)) if path == PathBuf::from("does/not/exist.png")));
+    }
+
+    #[test]
+    fn rejects_protocol_urls_without_a_handler() {
+        let result = WindowConfig::default().set_url("smn://app/index.html").validate();
+        assert!(matches!(result, Err(WindowConfigError::MissingProtocol(_))));
+        assert!(WindowConfig::default().set_url("http://127.0.0.1:8080/").validate().is_ok());
+    }
+}
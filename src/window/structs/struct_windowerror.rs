@@ -0,0 +1,41 @@
+use std::fmt;
+
+use super::struct_windowconfig::WindowConfigError;
+
+/// Errors raised while opening a window.
+#[derive(Debug)]
+pub enum WindowError {
+    /// The `WindowConfig` could not be honoured.
+    Config(WindowConfigError),
+    /// The window or webview failed to build.
+    Wry(wry::Error),
+}
+
+impl fmt::Display for WindowError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            WindowError::Config(err) => write!(f, "Invalid window config: {}", err),
+            WindowError::Wry(err) => write!(f, "Window error: {}", err),
+        }
+    }
+}
+
+impl std::error::Error for WindowError {}
+
+impl From<WindowConfigError> for WindowError {
+    fn from(err: WindowConfigError) -> Self {
+        WindowError::Config(err)
+    }
+}
+
+impl From<wry::Error> for WindowError {
+    fn from(err: wry::Error) -> Self {
+        WindowError::Wry(err)
+    }
+}
+
+impl From<wry::application::error::OsError> for WindowError {
+    fn from(err: wry::application::error::OsError) -> Self {
+        WindowError::Wry(err.into())
+    }
+}
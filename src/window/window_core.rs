@@ -1,28 +1,73 @@
 use wry::{
     application::{
-        dpi::LogicalSize,
+        dpi::PhysicalPosition,
         event::{Event, WindowEvent},
         event_loop::{ControlFlow, EventLoop},
         platform::run_return::EventLoopExtRunReturn,
-        window::WindowBuilder,
+        window::{Fullscreen, Window, WindowBuilder},
     },
     webview::WebViewBuilder,
 };
 
 use crate::util::logging::{logln, logln_color, Color};
 
-use super::structs::struct_windowconfig::WindowConfig;
+use super::structs::struct_windowconfig::{Rgba, WindowConfig, WindowPosition};
+use super::structs::struct_windowerror::WindowError;
+use super::window_icon::load_icon;
+use super::window_protocol::{handle_protocol_request, PROTOCOL_NAME};
 
 
-pub fn start_window<F: FnOnce()>(config: WindowConfig, on_close: F) -> wry::Result<()> {
+pub fn start_window<F: FnOnce()>(config: WindowConfig, on_close: F) -> Result<(), WindowError> {
+    config.validate()?;
+
     let mut event_loop = EventLoop::new();
 
-    let window = WindowBuilder::new()
-        .with_title(config.title)
-        .with_inner_size(LogicalSize::new(800.0, 600.0))
-        .build(&event_loop)?;
+    let mut builder = WindowBuilder::new()
+        .with_title(&config.title)
+        .with_inner_size(config.size)
+        .with_resizable(config.resizable)
+        .with_maximized(config.maximized)
+        .with_decorations(config.decorations)
+        .with_always_on_top(config.always_on_top)
+        .with_transparent(config.transparent)
+        // A centred window stays hidden until it has been moved, so it does not visibly jump
+        .with_visible(config.position != WindowPosition::Centered);
+
+    if let Some(min_size) = config.min_size {
+        builder = builder.with_min_inner_size(min_size);
+    }
+    if let Some(max_size) = config.max_size {
+        builder = builder.with_max_inner_size(max_size);
+    }
+    if let WindowPosition::At(position) = config.position {
+        builder = builder.with_position(position);
+    }
+    if config.fullscreen {
+        builder = builder.with_fullscreen(Some(Fullscreen::Borderless(None)));
+    }
+    if let Some(path) = &config.icon {
+        builder = builder.with_window_icon(Some(load_icon(path)?));
+    }
+
+    let window = builder.build(&event_loop)?;
+    if config.position == WindowPosition::Centered {
+        center_window(&window);
+        window.set_visible(true);
+    }
 
-    let webview = WebViewBuilder::new(window)?.with_url(&config.url)?.build()?;
+    let mut webview_builder = WebViewBuilder::new(window)?
+        .with_transparent(config.transparent)
+        .with_url(&config.url)?;
+    if let Some(color) = config.background_color {
+        webview_builder = webview_builder.with_initialization_script(&background_script(color));
+    }
+    if let Some(handler) = config.protocol.clone() {
+        // Called on the UI thread, which waits for each response
+        webview_builder = webview_builder.with_custom_protocol(PROTOCOL_NAME.to_string(), move |request| {
+            handle_protocol_request(&handler, request)
+        });
+    }
+    let webview = webview_builder.build()?;
 
     logln_color("[Started: Window]", Color::Green);
     logln(&format!("{} {}", Color::BrightBlack.paint("Window starting on URL:"), Color::Blue.paint(&config.url)));
@@ -47,3 +92,34 @@ pub fn start_window<F: FnOnce()>(config: WindowConfig, on_close: F) -> wry::Resu
 
     Ok(())
 }
+
+/// Moves the window to the centre of the monitor it opened on.
+fn center_window(window: &Window) {
+    let Some(monitor) = window.current_monitor() else {
+        return;
+    };
+
+    let monitor_size = monitor.size();
+    let monitor_position = monitor.position();
+    let window_size = window.outer_size();
+
+    let x = monitor_position.x + (monitor_size.width as i32 - window_size.width as i32) / 2;
+    let y = monitor_position.y + (monitor_size.height as i32 - window_size.height as i32) / 2;
+    window.set_outer_position(PhysicalPosition::new(x.max(monitor_position.x), y.max(monitor_position.y)));
+}
+
+/// Builds a script that gives every page a default background colour.
+///
+/// The style is inserted before any of the page's own, so a page that sets its
+/// own background still wins.
+fn background_script((r, g, b, a): Rgba) -> String {
+    format!(
+        "(function () {{ var s = document.createElement('style'); \
+         s.textContent = 'html {{ background-color: rgba({}, {}, {}, {:.3}); }}'; \
+         (document.head || document.documentElement).prepend(s); }})();",
+        r,
+        g,
+        b,
+        a as f64 / 255.0
+    )
+}
@@ -0,0 +1,46 @@
+use std::fs::File;
+use std::io::BufReader;
+use std::path::Path;
+
+use wry::application::window::Icon;
+
+use super::structs::struct_windowconfig::WindowConfigError;
+
+/// Loads a PNG file into a window icon.
+///
+/// # Arguments
+/// * `path` - The PNG file to load.
+///
+/// # Returns
+/// * `Result<Icon, WindowConfigError>` - The icon, or why the file could not be used.
+pub fn load_icon(path: &Path) -> Result<Icon, WindowConfigError> {
+    let icon_error = |msg: String| WindowConfigError::Icon(path.to_path_buf(), msg);
+
+    let file = File::open(path).map_err(|e| icon_error(e.to_string()))?;
+    let (rgba, width, height) = decode_png_rgba(BufReader::new(file)).map_err(icon_error)?;
+
+    Icon::from_rgba(rgba, width, height).map_err(|e| icon_error(e.to_string()))
+}
+
+/// Decodes a PNG image into 8-bit RGBA pixels, returning them with the image width and height.
+fn decode_png_rgba<R: std::io::Read>(reader: R) -> Result<(Vec<u8>, u32, u32), String> {
+    let mut decoder = png::Decoder::new(reader);
+    // Expand palettes, low bit depths and tRNS chunks, strip 16-bit channels and add an alpha channel
+    decoder.set_transformations(png::Transformations::normalize_to_color8() | png::Transformations::ALPHA);
+
+    let mut reader = decoder.read_info().map_err(|e| e.to_string())?;
+    let mut buf = vec![0; reader.output_buffer_size()];
+    let info = reader.next_frame(&mut buf).map_err(|e| e.to_string())?;
+    buf.truncate(info.buffer_size());
+
+    let rgba = match info.color_type {
+        png::ColorType::Rgba => buf,
+        png::ColorType::GrayscaleAlpha => buf
+            .chunks_exact(2)
+            .flat_map(|px| [px[0], px[0], px[0], px[1]])
+            .collect(),
+        other => return Err(format!("Unsupported PNG color type {:?}", other)),
+    };
+
+    Ok((rgba, info.width, info.height))
+}
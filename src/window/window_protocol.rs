@@ -0,0 +1,132 @@
+use wry::http::{Request as HttpRequest, Response as HttpResponse, ResponseBuilder};
+
+use crate::server::server_protocol::ProtocolHandler;
+use crate::server::structs::structs_response::Response;
+use crate::util::logging::logln;
+
+/// Scheme registered with the webview when the window serves a `ProtocolHandler`.
+pub const PROTOCOL_NAME: &str = "smn";
+
+/// Root URL of the app when it is served through the custom protocol.
+pub const PROTOCOL_URL: &str = "smn://app/";
+
+/// Answers a custom protocol request from the webview with the plugins behind `handler`.
+///
+/// Runs on the UI thread and blocks it until the response arrives; see `ProtocolHandler::handle`.
+///
+/// # Arguments
+/// * `handler` - The handler of a server started with `start_protocol_server`.
+/// * `request` - The request made by the webview.
+///
+/// # Returns
+/// * `wry::Result<HttpResponse>` - The response, or an error if it has an invalid header.
+pub fn handle_protocol_request(handler: &ProtocolHandler, request: &HttpRequest) -> wry::Result<HttpResponse> {
+    to_http_response(handler.handle_bytes(&to_raw_request(request)))
+}
+
+/// Serialises a webview request as it would arrive on a socket, so it is parsed and
+/// limited the same way as one read by the TCP server.
+fn to_raw_request(http_request: &HttpRequest) -> Vec<u8> {
+    let mut head = format!("{} {} HTTP/1.1\r\n", http_request.method().as_str(), request_target(http_request.uri()));
+    let mut framed = false;
+    for (name, value) in http_request.headers() {
+        // Header values that are not visible ASCII cannot be represented in a `Request`
+        if let Ok(value) = value.to_str() {
+            head.push_str(&format!("{}: {}\r\n", name.as_str(), value));
+            framed |= name.as_str().eq_ignore_ascii_case("content-length")
+                || name.as_str().eq_ignore_ascii_case("transfer-encoding");
+        }
+    }
+    // The webview hands over the body without framing it
+    if !framed {
+        head.push_str(&format!("Content-Length: {}\r\n", http_request.body().len()));
+    }
+    head.push_str("\r\n");
+
+    let mut raw = head.into_bytes();
+    raw.extend_from_slice(http_request.body());
+    raw
+}
+
+/// Strips the scheme and host from a URI, so `smn://app/page?id=1` becomes `/page?id=1`.
+fn request_target(uri: &str) -> String {
+    let rest = uri.split_once("://").map_or(uri, |(_, rest)| rest);
+    match rest.find(['/', '?']) {
+        Some(start) if rest[start..].starts_with('/') => rest[start..].to_string(),
+        Some(start) => format!("/{}", &rest[start..]),
+        None => "/".to_string(),
+    }
+}
+
+/// Converts a `Response` with a fully collected body into a webview response.
+fn to_http_response(response: Response) -> wry::Result<HttpResponse> {
+    let mut builder = ResponseBuilder::new().status(response.status_code);
+    if let Some(content_type) = response.get_header("content-type") {
+        builder = builder.mimetype(content_type);
+    }
+
+    // The webview derives the body framing itself
+    for (key, value) in response.header_fields.iter() {
+        if key.eq_ignore_ascii_case("content-type")
+            || key.eq_ignore_ascii_case("content-length")
+            || key.eq_ignore_ascii_case("transfer-encoding")
+        {
+            continue;
+        }
+        builder = builder.header(key, value);
+    }
+    for cookie in &response.cookies {
+        match cookie.to_header_value() {
+            Ok(value) => builder = builder.header("Set-Cookie", value.as_str()),
+            Err(err) => logln(&format!("Dropped cookie: {}", err)),
+        }
+    }
+
+    builder.body(response.body)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use wry::http::header::{HeaderValue, CONTENT_LENGTH, CONTENT_TYPE};
+    use wry::http::method::Method;
+
+    fn http_request(method: Method, uri: &str, body: &[u8]) -> HttpRequest {
+        let mut request = HttpRequest::new(body.to_vec());
+        request.head.method = method;
+        request.head.uri = uri.to_string();
+        request
+    }
+
+    #[test]
+    fn strips_scheme_and_host_from_the_target() {
+        assert_eq!(request_target("smn://app"), "/");
+        assert_eq!(request_target("smn://app/"), "/");
+        assert_eq!(request_target("smn://app?x=1"), "/?x=1");
+        assert_eq!(request_target("smn://app/p?q"), "/p?q");
+        assert_eq!(request_target("smn://app/a/b"), "/a/b");
+    }
+
+    #[test]
+    fn frames_unframed_bodies_with_content_length() {
+        let mut request = http_request(Method::POST, "smn://app/echo", b"hello");
+        request.head.headers.insert(CONTENT_TYPE, HeaderValue::from_static("text/plain"));
+        let raw = String::from_utf8(to_raw_request(&request)).unwrap();
+        assert!(raw.starts_with("POST /echo HTTP/1.1\r\n"), "{}", raw);
+        assert!(raw.contains("content-type: text/plain\r\n"), "{}", raw);
+        assert!(raw.contains("Content-Length: 5\r\n"), "{}", raw);
+        assert!(raw.ends_with("\r\n\r\nhello"), "{}", raw);
+
+        let raw = String::from_utf8(to_raw_request(&http_request(Method::GET, "smn://app", b""))).unwrap();
+        assert_eq!(raw, "GET / HTTP/1.1\r\nContent-Length: 0\r\n\r\n");
+    }
+
+    #[test]
+    fn keeps_the_framing_sent_by_the_webview() {
+        let mut request = http_request(Method::POST, "smn://app/echo", b"hello");
+        request.head.headers.insert(CONTENT_LENGTH, HeaderValue::from_static("5"));
+        let raw = String::from_utf8(to_raw_request(&request)).unwrap();
+        assert_eq!(raw.matches("ontent-length: 5\r\n").count(), 1, "{}", raw);
+        assert!(!raw.contains("Content-Length"), "{}", raw);
+    }
+}
@@ -22,7 +22,7 @@ try {
 
     # Step 2: Build the Rust project in release mode
     Write-Host "Building the Rust project in release mode..." -ForegroundColor Cyan
-    $buildResult = cargo build --release
+    $buildResult = cargo build --release --features embed-statics
     if ($LASTEXITCODE -ne 0) {
         Throw "Error: Cargo build failed."
     }
@@ -69,15 +69,7 @@ try {
         Write-Warning "Warning: 'smn_view_test.exe' not found at $exeSource. Skipping copy."
     }
 
-    # Step 5: Copy the entire 'statics' folder to the build directory
-    $staticsSource = Join-Path $projectRoot "statics"
-    $staticsDestination = Join-Path $buildDir "statics"
-    if (Test-Path $staticsSource) {
-        Copy-Item -Path $staticsSource -Destination $buildDir -Recurse -Force
-        Write-Host "'statics' folder copied successfully." -ForegroundColor Green
-    } else {
-        Write-Warning "Warning: 'statics' folder not found at $staticsSource. Skipping copy."
-    }
+    # The 'statics' folder is embedded into the executable by build.rs, so it is not copied
 
     Write-Host "All tasks completed successfully!" -ForegroundColor Green
 